@@ -36,10 +36,13 @@ fn bench_simple_getsetdel_async(b: &mut Bencher) {
                 redis::cmd("SET")
                     .arg(key)
                     .arg(42)
-                    .query_async(&mut con)
+                    .query_async::<_, ()>(&mut con)
                     .await?;
                 let _: isize = redis::cmd("GET").arg(key).query_async(&mut con).await?;
-                redis::cmd("DEL").arg(key).query_async(&mut con).await?;
+                redis::cmd("DEL")
+                    .arg(key)
+                    .query_async::<_, ()>(&mut con)
+                    .await?;
                 Ok(())
             })
             .map_err(|err: RedisError| err)
@@ -169,6 +172,33 @@ fn bench_multiplexed_async_implicit_pipeline(b: &mut Bencher) {
     });
 }
 
+fn bench_multiplexed_async_coalesced_stampede(b: &mut Bencher) {
+    let client = get_client();
+    let runtime = current_thread_runtime();
+    let con = runtime
+        .block_on(client.get_multiplexed_tokio_connection())
+        .unwrap();
+    let con = redis::aio::CoalescingConnection::new(con);
+
+    let cmd = redis::cmd("GET").arg("hot_key").clone();
+    let mut connections = (0..PIPELINE_QUERIES)
+        .map(|_| con.clone())
+        .collect::<Vec<_>>();
+
+    b.iter(|| {
+        let () = runtime
+            .block_on(async {
+                connections
+                    .iter_mut()
+                    .map(|con| cmd.query_async::<_, Option<i32>>(con))
+                    .collect::<stream::FuturesUnordered<_>>()
+                    .try_for_each(|_| async { Ok(()) })
+                    .await
+            })
+            .unwrap();
+    });
+}
+
 fn bench_query(c: &mut Criterion) {
     let mut group = c.benchmark_group("query");
     group
@@ -191,12 +221,66 @@ fn bench_query(c: &mut Criterion) {
             "multiplexed_async_long_pipeline",
             bench_multiplexed_async_long_pipeline,
         )
+        .bench_function(
+            "multiplexed_async_coalesced_stampede",
+            bench_multiplexed_async_coalesced_stampede,
+        )
         .bench_function("async_long_pipeline", bench_async_long_pipeline)
         .bench_function("long_pipeline", bench_long_pipeline)
         .throughput(Throughput::Elements(PIPELINE_QUERIES as u64));
     group.finish();
 }
 
+const LARGE_SET_MEMBERS: u64 = 1_000_000;
+
+fn fill_large_set(con: &mut redis::Connection) {
+    redis::cmd("DEL").arg("large_set").execute(con);
+    let members: Vec<u64> = (0..LARGE_SET_MEMBERS).collect();
+    for chunk in members.chunks(10_000) {
+        redis::cmd("SADD").arg("large_set").arg(chunk).execute(con);
+    }
+}
+
+fn bench_large_set_collected(b: &mut Bencher) {
+    let client = get_client();
+    let mut con = client.get_connection().unwrap();
+    fill_large_set(&mut con);
+
+    b.iter(|| {
+        let members: Vec<u64> = redis::cmd("SMEMBERS")
+            .arg("large_set")
+            .query(&mut con)
+            .unwrap();
+        assert_eq!(members.len() as u64, LARGE_SET_MEMBERS);
+    });
+}
+
+fn bench_large_set_streaming(b: &mut Bencher) {
+    let client = get_client();
+    let mut con = client.get_connection().unwrap();
+    fill_large_set(&mut con);
+
+    b.iter(|| {
+        let sum: u64 = redis::cmd("SMEMBERS")
+            .arg("large_set")
+            .query_streaming::<u64>(&mut con)
+            .unwrap()
+            .map(|member| member.unwrap())
+            .sum();
+        assert_eq!(sum, LARGE_SET_MEMBERS * (LARGE_SET_MEMBERS - 1) / 2);
+    });
+}
+
+fn bench_large_reply(c: &mut Criterion) {
+    let mut group = c.benchmark_group("query_large_set");
+    group
+        .sample_size(10)
+        .throughput(Throughput::Elements(LARGE_SET_MEMBERS))
+        .bench_function("collected", bench_large_set_collected)
+        .bench_function("streaming", bench_large_set_streaming);
+    group.finish();
+}
+
 fn bench_encode_small(b: &mut Bencher) {
     b.iter(|| {
         let mut cmd = redis::cmd("HSETX");
@@ -246,13 +330,33 @@ fn bench_encode_pipeline_nested(b: &mut Bencher) {
     });
 }
 
+fn bench_encode_large_copied(b: &mut Bencher) {
+    let value = vec![b'x'; 4 * 1024 * 1024];
+    b.iter(|| {
+        let mut cmd = redis::cmd("SET");
+        cmd.arg("key").arg(&value[..]);
+        cmd
+    });
+}
+
+fn bench_encode_large_shared(b: &mut Bencher) {
+    let value = bytes::Bytes::from(vec![b'x'; 4 * 1024 * 1024]);
+    b.iter(|| {
+        let mut cmd = redis::cmd("SET");
+        cmd.arg("key").arg(value.clone());
+        cmd
+    });
+}
+
 fn bench_encode(c: &mut Criterion) {
     let mut group = c.benchmark_group("encode");
     group
         .bench_function("pipeline", bench_encode_pipeline)
         .bench_function("pipeline_nested", bench_encode_pipeline_nested)
         .bench_function("integer", bench_encode_integer)
-        .bench_function("small", bench_encode_small);
+        .bench_function("small", bench_encode_small)
+        .bench_function("large_copied", bench_encode_large_copied)
+        .bench_function("large_shared", bench_encode_large_shared);
     group.finish();
 }
 
@@ -279,5 +383,11 @@ fn bench_decode(c: &mut Criterion) {
     group.finish();
 }
 
-criterion_group!(bench, bench_query, bench_encode, bench_decode);
+criterion_group!(
+    bench,
+    bench_query,
+    bench_large_reply,
+    bench_encode,
+    bench_decode
+);
 criterion_main!(bench);
@@ -11,12 +11,12 @@ async fn test_cmd(con: &MultiplexedConnection, i: i32) -> RedisResult<()> {
     redis::cmd("SET")
         .arg(&key[..])
         .arg(&value)
-        .query_async(&mut con)
+        .query_async::<_, ()>(&mut con)
         .await?;
 
     redis::cmd("SET")
         .arg(&[&key2, "bar"])
-        .query_async(&mut con)
+        .query_async::<_, ()>(&mut con)
         .await?;
 
     redis::cmd("MGET")
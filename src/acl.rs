@@ -99,6 +99,153 @@ impl ToRedisArgs for Rule {
     }
 }
 
+/// A category of commands, as listed by the `ACL CAT` command.
+///
+/// Converts into the `String` expected by [`Rule::AddCategory`] and
+/// [`Rule::RemoveCategory`]:
+///
+/// ```rust
+/// use redis::acl::{AclCategory, Rule};
+///
+/// let rule = Rule::AddCategory(AclCategory::Read.into());
+/// assert_eq!(rule, Rule::AddCategory("read".to_owned()));
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq, Hash)]
+pub enum AclCategory {
+    /// Every command, including the ones added in the future.
+    All,
+    /// Commands working on keys regardless of their type.
+    Keyspace,
+    /// Commands reading from keys.
+    Read,
+    /// Commands writing to keys.
+    Write,
+    /// Set commands.
+    Set,
+    /// Sorted set commands.
+    SortedSet,
+    /// List commands.
+    List,
+    /// Hash commands.
+    Hash,
+    /// String commands.
+    String,
+    /// Bitmap commands.
+    Bitmap,
+    /// HyperLogLog commands.
+    HyperLogLog,
+    /// Geospatial index commands.
+    Geo,
+    /// Stream commands.
+    Stream,
+    /// Pub/sub commands.
+    PubSub,
+    /// Administrative commands.
+    Admin,
+    /// Commands running in O(1) or O(log N) time.
+    Fast,
+    /// All commands which are not `fast`.
+    Slow,
+    /// Commands which may block the connection.
+    Blocking,
+    /// Potentially dangerous commands.
+    Dangerous,
+    /// Commands affecting the connection or other connections.
+    Connection,
+    /// `WATCH`, `MULTI`, `EXEC` and related commands.
+    Transaction,
+    /// Scripting commands.
+    Scripting,
+    /// A category unknown to this version of the library.
+    Other(String),
+}
+
+impl AclCategory {
+    /// Returns the name of the category as used by the server.
+    pub fn as_str(&self) -> &str {
+        match self {
+            AclCategory::All => "all",
+            AclCategory::Keyspace => "keyspace",
+            AclCategory::Read => "read",
+            AclCategory::Write => "write",
+            AclCategory::Set => "set",
+            AclCategory::SortedSet => "sortedset",
+            AclCategory::List => "list",
+            AclCategory::Hash => "hash",
+            AclCategory::String => "string",
+            AclCategory::Bitmap => "bitmap",
+            AclCategory::HyperLogLog => "hyperloglog",
+            AclCategory::Geo => "geo",
+            AclCategory::Stream => "stream",
+            AclCategory::PubSub => "pubsub",
+            AclCategory::Admin => "admin",
+            AclCategory::Fast => "fast",
+            AclCategory::Slow => "slow",
+            AclCategory::Blocking => "blocking",
+            AclCategory::Dangerous => "dangerous",
+            AclCategory::Connection => "connection",
+            AclCategory::Transaction => "transaction",
+            AclCategory::Scripting => "scripting",
+            AclCategory::Other(name) => name,
+        }
+    }
+}
+
+impl From<&str> for AclCategory {
+    fn from(name: &str) -> AclCategory {
+        match name {
+            "all" => AclCategory::All,
+            "keyspace" => AclCategory::Keyspace,
+            "read" => AclCategory::Read,
+            "write" => AclCategory::Write,
+            "set" => AclCategory::Set,
+            "sortedset" => AclCategory::SortedSet,
+            "list" => AclCategory::List,
+            "hash" => AclCategory::Hash,
+            "string" => AclCategory::String,
+            "bitmap" => AclCategory::Bitmap,
+            "hyperloglog" => AclCategory::HyperLogLog,
+            "geo" => AclCategory::Geo,
+            "stream" => AclCategory::Stream,
+            "pubsub" => AclCategory::PubSub,
+            "admin" => AclCategory::Admin,
+            "fast" => AclCategory::Fast,
+            "slow" => AclCategory::Slow,
+            "blocking" => AclCategory::Blocking,
+            "dangerous" => AclCategory::Dangerous,
+            "connection" => AclCategory::Connection,
+            "transaction" => AclCategory::Transaction,
+            "scripting" => AclCategory::Scripting,
+            _ => AclCategory::Other(name.to_owned()),
+        }
+    }
+}
+
+impl From<AclCategory> for String {
+    fn from(category: AclCategory) -> String {
+        match category {
+            AclCategory::Other(name) => name,
+            category => category.as_str().to_owned(),
+        }
+    }
+}
+
+impl ToRedisArgs for AclCategory {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        out.write_arg(self.as_str().as_bytes())
+    }
+}
+
+impl FromRedisValue for AclCategory {
+    fn from_redis_value(v: &Value) -> RedisResult<Self> {
+        let name: String = FromRedisValue::from_redis_value(v)?;
+        Ok(AclCategory::from(&name[..]))
+    }
+}
+
 /// An info dictionary type storing Redis ACL information as multiple `Rule`.
 /// This type collects key/value data returned by the [`ACL GETUSER`][1] command.
 ///
@@ -271,6 +418,22 @@ mod tests {
         assert_args!(Reset, b"reset");
     }
 
+    #[test]
+    fn test_category() {
+        assert_args!(AclCategory::SortedSet, b"sortedset");
+        assert_args!(AclCategory::Other("module".to_owned()), b"module");
+        assert_args!(
+            Rule::AddCategory(AclCategory::HyperLogLog.into()),
+            b"+@hyperloglog"
+        );
+        assert_args!(Rule::RemoveCategory(AclCategory::All.into()), b"-@all");
+
+        let category = AclCategory::from_redis_value(&Value::Data("pubsub".into()));
+        assert_eq!(category, Ok(AclCategory::PubSub));
+        let category = AclCategory::from_redis_value(&Value::Data("search".into()));
+        assert_eq!(category, Ok(AclCategory::Other("search".to_owned())));
+    }
+
     #[test]
     fn test_from_redis_value() {
         let redis_value = Value::Bulk(vec![
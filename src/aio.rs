@@ -7,14 +7,17 @@ use std::net::SocketAddr;
 #[cfg(unix)]
 use std::path::Path;
 use std::pin::Pin;
+use std::sync::atomic::{AtomicBool, AtomicUsize, Ordering};
+use std::sync::{Arc, Weak};
 use std::task::{self, Poll};
+use std::time::Duration;
 
 use combine::{parser::combinator::AnySendSyncPartialState, stream::PointerOffset};
 
 use ::tokio::{
     io::{AsyncRead, AsyncWrite, AsyncWriteExt},
     net::lookup_host,
-    sync::{mpsc, oneshot},
+    sync::{mpsc, oneshot, Notify},
 };
 
 #[cfg(feature = "tls")]
@@ -32,11 +35,15 @@ use futures_util::{
 
 use pin_project_lite::pin_project;
 
-use crate::cmd::{cmd, Cmd};
-use crate::connection::{ConnectionAddr, ConnectionInfo, Msg, RedisConnectionInfo};
+use crate::cmd::{cmd, Cmd, CommandNamePolicy, PackedSegment};
+use crate::connection::{
+    check_handshake, handshake_commands, notification_channel, ConnectionAddr, ConnectionInfo,
+    Handshake, Msg, RedisConnectionInfo,
+};
+use crate::hook::DebugHook;
 
 #[cfg(any(feature = "tokio-comp", feature = "async-std-comp"))]
-use crate::parser::ValueCodec;
+use crate::parser::{parse_frame_async, Frame, ValueCodec};
 use crate::types::{ErrorKind, FromRedisValue, RedisError, RedisFuture, RedisResult, Value};
 use crate::{from_redis_value, ToRedisArgs};
 
@@ -54,7 +61,8 @@ pub mod tokio;
 #[async_trait]
 pub(crate) trait RedisRuntime: AsyncStream + Send + Sync + Sized + 'static {
     /// Performs a TCP connection
-    async fn connect_tcp(socket_addr: SocketAddr) -> RedisResult<Self>;
+    async fn connect_tcp(socket_addr: SocketAddr, keepalive: Option<Duration>)
+        -> RedisResult<Self>;
 
     // Performs a TCP TLS connection
     #[cfg(feature = "tls")]
@@ -62,6 +70,7 @@ pub(crate) trait RedisRuntime: AsyncStream + Send + Sync + Sized + 'static {
         hostname: &str,
         socket_addr: SocketAddr,
         insecure: bool,
+        keepalive: Option<Duration>,
     ) -> RedisResult<Self>;
 
     /// Performs a UNIX connection
@@ -119,6 +128,15 @@ impl Runtime {
             Runtime::AsyncStd => async_std::AsyncStd::spawn(f),
         }
     }
+
+    pub(crate) async fn sleep(&self, duration: Duration) {
+        match self {
+            #[cfg(feature = "tokio-comp")]
+            Runtime::Tokio => ::tokio::time::sleep(duration).await,
+            #[cfg(feature = "async-std-comp")]
+            Runtime::AsyncStd => ::async_std::task::sleep(duration).await,
+        }
+    }
 }
 
 /// Trait for objects that implements `AsyncRead` and `AsyncWrite`
@@ -155,6 +173,30 @@ where
             .await?)
     }
 
+    /// Subscribes to the keyspace notifications of the keys matching
+    /// `key_pattern` in database `db`, like
+    /// [`PubSub::psubscribe_keyspace`](crate::PubSub::psubscribe_keyspace).
+    pub async fn psubscribe_keyspace(
+        &mut self,
+        db: Option<i64>,
+        key_pattern: &str,
+    ) -> RedisResult<()> {
+        let channel = notification_channel("keyspace", self.0.db, db, key_pattern);
+        self.psubscribe(channel).await
+    }
+
+    /// Subscribes to the keyevent notifications of the events matching
+    /// `event_pattern` in database `db`, like
+    /// [`PubSub::psubscribe_keyevent`](crate::PubSub::psubscribe_keyevent).
+    pub async fn psubscribe_keyevent(
+        &mut self,
+        db: Option<i64>,
+        event_pattern: &str,
+    ) -> RedisResult<()> {
+        let channel = notification_channel("keyevent", self.0.db, db, event_pattern);
+        self.psubscribe(channel).await
+    }
+
     /// Unsubscribes from a channel.
     pub async fn unsubscribe<T: ToRedisArgs>(&mut self, channel: T) -> RedisResult<()> {
         Ok(cmd("UNSUBSCRIBE")
@@ -193,6 +235,22 @@ where
             .filter_map(|msg| Box::pin(async move { Msg::from_value(&msg.ok()?.ok()?) }))
     }
 
+    /// Returns [`Stream`] of every frame received on this [`PubSub`], including subscription
+    /// confirmations, which [`on_message`](Self::on_message) skips.
+    pub fn on_frame(&mut self) -> impl Stream<Item = Msg> + '_ {
+        ValueCodec::default()
+            .framed(&mut self.0.con)
+            .filter_map(|msg| Box::pin(async move { Some(Msg::from(msg.ok()?.ok()?)) }))
+    }
+
+    /// Returns [`Stream`] of every frame received on this [`PubSub`] consuming it, see
+    /// [`on_frame`](Self::on_frame).
+    pub fn into_on_frame(self) -> impl Stream<Item = Msg> {
+        ValueCodec::default()
+            .framed(self.0.con)
+            .filter_map(|msg| Box::pin(async move { Some(Msg::from(msg.ok()?.ok()?)) }))
+    }
+
     /// Exits from `PubSub` mode and converts [`PubSub`] into [`Connection`].
     pub async fn into_connection(mut self) -> Connection<C> {
         self.0.exit_pubsub().await.ok();
@@ -246,6 +304,14 @@ pub struct Connection<C = Pin<Box<dyn AsyncStream + Send + Sync>>> {
     // This flag is checked when attempting to send a command, and if it's raised, we attempt to
     // exit the pubsub state before executing the new request.
     pubsub: bool,
+
+    // The number of array elements left unread by a dropped
+    // `AsyncStreamingIter`, which are skipped before the next request.
+    unread: usize,
+
+    debug_hook: Option<DebugHook>,
+    client_id: Option<i64>,
+    command_name_policy: CommandNamePolicy,
 }
 
 fn assert_sync<T: Sync>() {}
@@ -263,6 +329,10 @@ impl<C> Connection<C> {
             decoder,
             db,
             pubsub,
+            unread,
+            debug_hook,
+            client_id,
+            command_name_policy,
         } = self;
         Connection {
             con: f(con),
@@ -270,8 +340,29 @@ impl<C> Connection<C> {
             decoder,
             db,
             pubsub,
+            unread,
+            debug_hook,
+            client_id,
+            command_name_policy,
         }
     }
+
+    /// Returns the id the server assigned to this connection, if
+    /// [`RedisConnectionInfo::fetch_client_id`] was set when connecting.
+    pub fn client_id(&self) -> Option<i64> {
+        self.client_id
+    }
+
+    /// Sets the [`DebugHook`] that sees all commands and replies of this
+    /// connection, or removes it with `None`.
+    pub fn set_debug_hook(&mut self, hook: Option<DebugHook>) {
+        self.debug_hook = hook;
+    }
+
+    /// Marks array elements as left unread by a dropped stream.
+    pub(crate) fn leave_unread(&mut self, count: usize) {
+        self.unread += count;
+    }
 }
 
 impl<C> Connection<C>
@@ -287,11 +378,42 @@ where
             decoder: combine::stream::Decoder::new(),
             db: connection_info.db,
             pubsub: false,
+            unread: 0,
+            debug_hook: None,
+            client_id: None,
+            command_name_policy: connection_info.command_name_policy,
         };
-        authenticate(connection_info, &mut rv).await?;
+        rv.handshake(connection_info).await?;
         Ok(rv)
     }
 
+    /// Sends all setup commands in one write and checks their replies.
+    async fn handshake(&mut self, connection_info: &RedisConnectionInfo) -> RedisResult<()> {
+        let mut legacy_auth = false;
+        loop {
+            let commands = handshake_commands(connection_info, legacy_auth);
+            if commands.is_empty() {
+                return Ok(());
+            }
+            self.buf.clear();
+            for (_, command) in &commands {
+                command.write_packed_command(&mut self.buf);
+            }
+            self.con.write_all(&self.buf).await?;
+            let mut replies = Vec::with_capacity(commands.len());
+            for (step, _) in &commands {
+                replies.push((*step, self.read_response().await));
+            }
+            match check_handshake(replies, legacy_auth)? {
+                Handshake::Done(client_id) => {
+                    self.client_id = client_id;
+                    return Ok(());
+                }
+                Handshake::RetryWithLegacyAuth => legacy_auth = true,
+            }
+        }
+    }
+
     /// Converts this [`Connection`] into [`PubSub`].
     pub fn into_pubsub(self) -> PubSub<C> {
         PubSub::new(self)
@@ -304,7 +426,34 @@ where
 
     /// Fetches a single response from the connection.
     async fn read_response(&mut self) -> RedisResult<Value> {
-        crate::parser::parse_redis_value_async(&mut self.decoder, &mut self.con).await
+        self.read_frame(false).await?.into_value()
+    }
+
+    /// Fetches a single response from the connection, or only its header if
+    /// it is an array and `array_header` is set.
+    pub(crate) async fn read_frame(&mut self, array_header: bool) -> RedisResult<Frame> {
+        parse_frame_async(&mut self.decoder, &mut self.con, array_header).await
+    }
+
+    /// Sends a command and reads the header of its reply, leaving the
+    /// elements of an array reply to be read with `read_frame`.
+    pub(crate) async fn req_streaming(&mut self, cmd: &Cmd) -> RedisResult<Frame> {
+        self.skip_unread().await?;
+        if self.pubsub {
+            self.exit_pubsub().await?;
+        }
+        self.buf.clear();
+        let segments = cmd.write_packed_segments(&mut self.buf);
+        write_all_vectored(&mut self.con, &self.buf, &segments).await?;
+        self.read_frame(true).await
+    }
+
+    async fn skip_unread(&mut self) -> RedisResult<()> {
+        while self.unread > 0 {
+            self.read_frame(false).await?;
+            self.unread -= 1;
+        }
+        Ok(())
     }
 
     /// Brings [`Connection`] out of `PubSub` mode.
@@ -394,70 +543,13 @@ where
     Connection::new(&connection_info.redis, con).await
 }
 
-async fn authenticate<C>(connection_info: &RedisConnectionInfo, con: &mut C) -> RedisResult<()>
-where
-    C: ConnectionLike,
-{
-    if let Some(password) = &connection_info.password {
-        let mut command = cmd("AUTH");
-        if let Some(username) = &connection_info.username {
-            command.arg(username);
-        }
-        match command.arg(password).query_async(con).await {
-            Ok(Value::Okay) => (),
-            Err(e) => {
-                let err_msg = e.detail().ok_or((
-                    ErrorKind::AuthenticationFailed,
-                    "Password authentication failed",
-                ))?;
-
-                if !err_msg.contains("wrong number of arguments for 'auth' command") {
-                    fail!((
-                        ErrorKind::AuthenticationFailed,
-                        "Password authentication failed",
-                    ));
-                }
-
-                let mut command = cmd("AUTH");
-                match command.arg(password).query_async(con).await {
-                    Ok(Value::Okay) => (),
-                    _ => {
-                        fail!((
-                            ErrorKind::AuthenticationFailed,
-                            "Password authentication failed"
-                        ));
-                    }
-                }
-            }
-            _ => {
-                fail!((
-                    ErrorKind::AuthenticationFailed,
-                    "Password authentication failed"
-                ));
-            }
-        }
-    }
-
-    if connection_info.db != 0 {
-        match cmd("SELECT").arg(connection_info.db).query_async(con).await {
-            Ok(Value::Okay) => (),
-            _ => fail!((
-                ErrorKind::ResponseError,
-                "Redis server refused to switch database"
-            )),
-        }
-    }
-
-    Ok(())
-}
-
 pub(crate) async fn connect_simple<T: RedisRuntime>(
     connection_info: &ConnectionInfo,
 ) -> RedisResult<T> {
     Ok(match connection_info.addr {
         ConnectionAddr::Tcp(ref host, port) => {
             let socket_addr = get_socket_addrs(host, port).await?;
-            <T>::connect_tcp(socket_addr).await?
+            <T>::connect_tcp(socket_addr, connection_info.tcp_keepalive).await?
         }
 
         #[cfg(feature = "tls")]
@@ -467,7 +559,7 @@ pub(crate) async fn connect_simple<T: RedisRuntime>(
             insecure,
         } => {
             let socket_addr = get_socket_addrs(host, port).await?;
-            <T>::connect_tcp_tls(host, socket_addr, insecure).await?
+            <T>::connect_tcp_tls(host, socket_addr, insecure, connection_info.tcp_keepalive).await?
         }
 
         #[cfg(not(feature = "tls"))]
@@ -524,6 +616,51 @@ pub trait ConnectionLike {
     /// also might be incorrect if the connection like object is not
     /// actually connected.
     fn get_db(&self) -> i64;
+
+    /// Returns how commands named with several words are handled, see
+    /// [`CommandNamePolicy`].
+    fn command_name_policy(&self) -> CommandNamePolicy {
+        CommandNamePolicy::Split
+    }
+}
+
+/// Writes the segments of a packed command, passing the arguments held in
+/// shared buffers to the writer without copying them.
+async fn write_all_vectored<W>(
+    writer: &mut W,
+    buf: &[u8],
+    segments: &[PackedSegment<'_>],
+) -> io::Result<()>
+where
+    W: AsyncWrite + Unpin,
+{
+    if let [PackedSegment::Buffered(range)] = segments {
+        return writer.write_all(&buf[range.clone()]).await;
+    }
+    let mut slices: Vec<&[u8]> = segments
+        .iter()
+        .map(|segment| match segment {
+            PackedSegment::Buffered(range) => &buf[range.clone()],
+            PackedSegment::Shared(bytes) => bytes,
+        })
+        .filter(|slice| !slice.is_empty())
+        .collect();
+    let mut idx = 0;
+    while idx < slices.len() {
+        let io_slices: Vec<_> = slices[idx..].iter().map(|s| io::IoSlice::new(s)).collect();
+        let mut written = writer.write_vectored(&io_slices).await?;
+        if written == 0 {
+            return Err(io::ErrorKind::WriteZero.into());
+        }
+        while idx < slices.len() && written >= slices[idx].len() {
+            written -= slices[idx].len();
+            idx += 1;
+        }
+        if written > 0 {
+            slices[idx] = &slices[idx][written..];
+        }
+    }
+    Ok(())
 }
 
 impl<C> ConnectionLike for Connection<C>
@@ -532,13 +669,25 @@ where
 {
     fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
         (async move {
+            self.skip_unread().await?;
             if self.pubsub {
                 self.exit_pubsub().await?;
             }
+            let names = self
+                .debug_hook
+                .as_ref()
+                .map(|hook| hook.sent(&cmd.get_packed_command()));
             self.buf.clear();
-            cmd.write_packed_command(&mut self.buf);
-            self.con.write_all(&self.buf).await?;
-            self.read_response().await
+            let segments = cmd.write_packed_segments(&mut self.buf);
+            write_all_vectored(&mut self.con, &self.buf, &segments).await?;
+            let response = self.read_response().await;
+            if let (Some(hook), Some(names)) = (&self.debug_hook, &names) {
+                hook.received(names, 0, response.as_ref());
+            }
+            if let (Ok(_), Some(db)) = (&response, cmd.selected_db()) {
+                self.db = db;
+            }
+            response
         })
         .boxed()
     }
@@ -550,18 +699,23 @@ where
         count: usize,
     ) -> RedisFuture<'a, Vec<Value>> {
         (async move {
+            self.skip_unread().await?;
             if self.pubsub {
                 self.exit_pubsub().await?;
             }
 
             self.buf.clear();
             cmd.write_packed_pipeline(&mut self.buf);
+            let names = self.debug_hook.as_ref().map(|hook| hook.sent(&self.buf));
             self.con.write_all(&self.buf).await?;
 
             let mut first_err = None;
 
-            for _ in 0..offset {
+            for idx in 0..offset {
                 let response = self.read_response().await;
+                if let (Some(hook), Some(names)) = (&self.debug_hook, &names) {
+                    hook.received(names, idx, response.as_ref());
+                }
                 if let Err(err) = response {
                     if first_err.is_none() {
                         first_err = Some(err);
@@ -570,8 +724,11 @@ where
             }
 
             let mut rv = Vec::with_capacity(count);
-            for _ in 0..count {
+            for idx in offset..offset + count {
                 let response = self.read_response().await;
+                if let (Some(hook), Some(names)) = (&self.debug_hook, &names) {
+                    hook.received(names, idx, response.as_ref());
+                }
                 match response {
                     Ok(item) => {
                         rv.push(item);
@@ -596,6 +753,10 @@ where
     fn get_db(&self) -> i64 {
         self.db
     }
+
+    fn command_name_policy(&self) -> CommandNamePolicy {
+        self.command_name_policy
+    }
 }
 
 // Senders which the result of a single request are sent through
@@ -844,13 +1005,85 @@ where
 
 /// A connection object which can be cloned, allowing requests to be be sent concurrently
 /// on the same underlying connection (tcp/unix socket).
+///
+/// If [`RedisConnectionInfo::ping_interval`] is set, the connection sends a `PING` whenever it
+/// has been idle for that long.  No `PING` is sent while requests wait for their replies, so
+/// blocking commands like `BLPOP` with long timeouts do not count as a dead connection.  Should
+/// the `PING` fail or time out, the connection is closed and all pending and future requests
+/// fail with an I/O error.
 #[derive(Clone)]
 pub struct MultiplexedConnection {
     pipeline: Pipeline<Vec<u8>, Value, RedisError>,
     db: i64,
+    // Raised by every request, cleared by the keepalive task. Also keeps the keepalive task
+    // alive for as long as there are handles to this connection.
+    active: Arc<AtomicBool>,
+    drain: Arc<DrainState>,
+    debug_hook: Option<DebugHook>,
+    client_id: Option<i64>,
+    command_name_policy: CommandNamePolicy,
+}
+
+// Shared between all clones of a `MultiplexedConnection` so that draining one handle stops
+// every handle from sending new requests.
+#[derive(Default)]
+struct DrainState {
+    closing: AtomicBool,
+    in_flight: AtomicUsize,
+    idle: Notify,
+}
+
+// Counts a request as in flight until it is dropped.
+struct PendingRequest(Arc<DrainState>);
+
+impl PendingRequest {
+    fn start(state: &Arc<DrainState>) -> RedisResult<Self> {
+        // Increment before checking `closing` so that `drain` either sees this request or the
+        // request sees the flag.
+        state.in_flight.fetch_add(1, Ordering::SeqCst);
+        let guard = PendingRequest(state.clone());
+        if state.closing.load(Ordering::SeqCst) {
+            fail!((ErrorKind::ClientClosed, "Connection is draining"));
+        }
+        Ok(guard)
+    }
+}
+
+impl Drop for PendingRequest {
+    fn drop(&mut self) {
+        if self.0.in_flight.fetch_sub(1, Ordering::SeqCst) == 1 {
+            self.0.idle.notify_waiters();
+        }
+    }
 }
 
 impl MultiplexedConnection {
+    /// Sends all setup commands without waiting for each other's replies.
+    ///
+    /// Every command is queued as its own request, so the replies and errors
+    /// stay attributed to the right step.
+    ///
+    /// Returns the id reported by `CLIENT ID`, if it was requested.
+    async fn handshake(&self, connection_info: &RedisConnectionInfo) -> RedisResult<Option<i64>> {
+        let mut legacy_auth = false;
+        loop {
+            let commands = handshake_commands(connection_info, legacy_auth);
+            if commands.is_empty() {
+                return Ok(None);
+            }
+            // `join_all` polls the requests in order, which queues them in order.
+            let replies = futures_util::future::join_all(commands.iter().map(|(step, command)| {
+                let mut con = self.clone();
+                async move { (*step, con.req_packed_command(command).await) }
+            }))
+            .await;
+            match check_handshake(replies, legacy_auth)? {
+                Handshake::Done(client_id) => return Ok(client_id),
+                Handshake::RetryWithLegacyAuth => legacy_auth = true,
+            }
+        }
+    }
+
     /// Constructs a new `MultiplexedConnection` out of a `AsyncRead + AsyncWrite` object
     /// and a `ConnectionInfo`
     pub async fn new<C>(
@@ -877,38 +1110,146 @@ impl MultiplexedConnection {
         let mut con = MultiplexedConnection {
             pipeline,
             db: connection_info.db,
+            active: Arc::new(AtomicBool::new(false)),
+            drain: Arc::default(),
+            debug_hook: None,
+            client_id: None,
+            command_name_policy: connection_info.command_name_policy,
         };
-        let driver = {
-            let auth = authenticate(connection_info, &mut con);
+        let (client_id, driver) = {
+            let auth = con.handshake(connection_info);
             futures_util::pin_mut!(auth);
 
             match futures_util::future::select(auth, driver).await {
-                futures_util::future::Either::Left((result, driver)) => {
-                    result?;
-                    driver
-                }
+                futures_util::future::Either::Left((result, driver)) => (result?, driver),
                 futures_util::future::Either::Right(((), _)) => {
                     unreachable!("Multiplexed connection driver unexpectedly terminated")
                 }
             }
         };
+        con.client_id = client_id;
+        let driver = match connection_info.ping_interval {
+            Some(interval) => {
+                let keepalive = keepalive(
+                    con.pipeline.clone(),
+                    Arc::downgrade(&con.active),
+                    con.drain.clone(),
+                    interval,
+                );
+                boxed(futures_util::future::select(driver, keepalive.boxed()).map(|_| ()))
+            }
+            None => driver,
+        };
         Ok((con, driver))
     }
+
+    /// Returns the id the server assigned to this connection, if
+    /// [`RedisConnectionInfo::fetch_client_id`] was set when connecting.
+    pub fn client_id(&self) -> Option<i64> {
+        self.client_id
+    }
+
+    /// Sets the [`DebugHook`] that sees all commands and replies sent through this handle, or
+    /// removes it with `None`.  Other clones of the connection keep their hook.
+    pub fn set_debug_hook(&mut self, hook: Option<DebugHook>) {
+        self.debug_hook = hook;
+    }
+
+    /// Returns `true` once the connection has been closed, either by the server or because a
+    /// keepalive `PING` failed.
+    pub fn is_closed(&self) -> bool {
+        self.pipeline.0.is_closed()
+    }
+
+    /// Gracefully shuts down the connection.
+    ///
+    /// New requests on this connection and all of its clones fail with
+    /// [`ErrorKind::ClientClosed`] from now on.  Requests that were already sent are given up
+    /// to `timeout` to receive their replies, after which `QUIT` is sent to close the
+    /// connection.  If the replies did not arrive in time an I/O error with
+    /// [`io::ErrorKind::TimedOut`] is returned and the remaining requests keep running until
+    /// the server answers them.
+    pub async fn drain(&self, timeout: Duration) -> RedisResult<()> {
+        self.drain.closing.store(true, Ordering::SeqCst);
+
+        let mut pipeline = self.pipeline.clone();
+        let state = self.drain.clone();
+        let shutdown = async move {
+            loop {
+                // `notified` has to be created before checking the counter, otherwise the
+                // wakeup of the last request could be missed.
+                let idle = state.idle.notified();
+                if state.in_flight.load(Ordering::SeqCst) == 0 {
+                    break;
+                }
+                idle.await;
+            }
+            // The server closes the connection after replying, which also ends the driver.
+            // An error only means that the connection is gone already.
+            let _ = pipeline.send(cmd("QUIT").get_packed_command()).await;
+        };
+        let runtime = Runtime::locate();
+        let sleep = runtime.sleep(timeout);
+        futures_util::pin_mut!(shutdown, sleep);
+        match futures_util::future::select(shutdown, sleep).await {
+            futures_util::future::Either::Left(((), _)) => Ok(()),
+            futures_util::future::Either::Right(((), _)) => {
+                Err(io::Error::from(io::ErrorKind::TimedOut).into())
+            }
+        }
+    }
+}
+
+// Sends a `PING` whenever the connection was idle for `interval`. Returns once the `PING` fails
+// or times out, which lets the caller tear down the connection, or once all handles to the
+// connection are gone.
+//
+// A connection with requests in flight is not idle even if nothing was sent for a while: a
+// `PING` would queue behind a blocking command and time out although the connection is fine.
+async fn keepalive(
+    mut pipeline: Pipeline<Vec<u8>, Value, RedisError>,
+    active: Weak<AtomicBool>,
+    requests: Arc<DrainState>,
+    interval: Duration,
+) {
+    let runtime = Runtime::locate();
+    loop {
+        runtime.sleep(interval).await;
+        match active.upgrade() {
+            Some(active) => {
+                let busy = requests.in_flight.load(Ordering::SeqCst) > 0;
+                if active.swap(false, Ordering::Relaxed) || busy {
+                    continue;
+                }
+            }
+            None => return,
+        }
+
+        let ping = pipeline.send(cmd("PING").get_packed_command());
+        let timeout = runtime.sleep(interval);
+        futures_util::pin_mut!(ping, timeout);
+        match futures_util::future::select(ping, timeout).await {
+            futures_util::future::Either::Left((Ok(_), _)) => (),
+            futures_util::future::Either::Left((Err(Some(err)), _)) if !err.is_io_error() => (),
+            _ => return,
+        }
+    }
 }
 
 impl ConnectionLike for MultiplexedConnection {
     fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
         (async move {
-            let value = self
-                .pipeline
-                .send(cmd.get_packed_command())
-                .await
-                .map_err(|err| {
-                    err.unwrap_or_else(|| {
-                        RedisError::from(io::Error::from(io::ErrorKind::BrokenPipe))
-                    })
-                })?;
-            Ok(value)
+            let _pending = PendingRequest::start(&self.drain)?;
+            self.active.store(true, Ordering::Relaxed);
+            let packed = cmd.get_packed_command();
+            let names = self.debug_hook.as_ref().map(|hook| hook.sent(&packed));
+            let value = self.pipeline.send(packed).await.map_err(|err| {
+                err.unwrap_or_else(|| RedisError::from(io::Error::from(io::ErrorKind::BrokenPipe)))
+            });
+            if let (Some(hook), Some(names)) = (&self.debug_hook, &names) {
+                hook.received(names, 0, value.as_ref());
+            }
+            value
         })
         .boxed()
     }
@@ -920,15 +1261,36 @@ impl ConnectionLike for MultiplexedConnection {
         count: usize,
     ) -> RedisFuture<'a, Vec<Value>> {
         (async move {
-            let mut value = self
+            let _pending = PendingRequest::start(&self.drain)?;
+            self.active.store(true, Ordering::Relaxed);
+            let packed = cmd.get_packed_pipeline();
+            let names = self.debug_hook.as_ref().map(|hook| hook.sent(&packed));
+            let value = self
                 .pipeline
-                .send_recv_multiple(cmd.get_packed_pipeline(), offset + count)
+                .send_recv_multiple(packed, offset + count)
                 .await
                 .map_err(|err| {
                     err.unwrap_or_else(|| {
                         RedisError::from(io::Error::from(io::ErrorKind::BrokenPipe))
                     })
-                })?;
+                });
+            let mut value = match (&self.debug_hook, &names) {
+                (Some(hook), Some(names)) => match value {
+                    Ok(value) => {
+                        for (idx, item) in value.iter().enumerate() {
+                            hook.received(names, idx, Ok(item));
+                        }
+                        value
+                    }
+                    // The replies of the other commands are lost, so the error
+                    // is reported for the last command of the pipeline.
+                    Err(err) => {
+                        hook.received(names, names.len().saturating_sub(1), Err(&err));
+                        return Err(err);
+                    }
+                },
+                _ => value?,
+            };
 
             value.drain(..offset);
             Ok(value)
@@ -939,13 +1301,289 @@ impl ConnectionLike for MultiplexedConnection {
     fn get_db(&self) -> i64 {
         self.db
     }
+
+    fn command_name_policy(&self) -> CommandNamePolicy {
+        self.command_name_policy
+    }
 }
 
+mod coalescing {
+    use super::*;
+
+    use std::collections::HashMap;
+    use std::sync::Mutex;
+
+    use crate::cmd::Arg;
+
+    /// Read-only commands whose reply only depends on the data set.  Identical
+    /// requests for these can share one reply; anything that writes or whose
+    /// reply is random or time dependent (`RANDOMKEY`, `SRANDMEMBER`, `TIME`,
+    /// ...) is always sent as is.
+    const COALESCABLE_COMMANDS: &[&str] = &[
+        "BITCOUNT",
+        "BITPOS",
+        "DBSIZE",
+        "DUMP",
+        "EXISTS",
+        "GEODIST",
+        "GEOHASH",
+        "GEOPOS",
+        "GET",
+        "GETBIT",
+        "GETRANGE",
+        "HEXISTS",
+        "HGET",
+        "HGETALL",
+        "HKEYS",
+        "HLEN",
+        "HMGET",
+        "HSTRLEN",
+        "HVALS",
+        "LINDEX",
+        "LLEN",
+        "LPOS",
+        "LRANGE",
+        "MGET",
+        "SCARD",
+        "SDIFF",
+        "SINTER",
+        "SISMEMBER",
+        "SMEMBERS",
+        "SMISMEMBER",
+        "STRLEN",
+        "SUNION",
+        "TYPE",
+        "XLEN",
+        "XRANGE",
+        "XREVRANGE",
+        "ZCARD",
+        "ZCOUNT",
+        "ZLEXCOUNT",
+        "ZMSCORE",
+        "ZRANGE",
+        "ZRANGEBYLEX",
+        "ZRANGEBYSCORE",
+        "ZRANK",
+        "ZREVRANGE",
+        "ZREVRANGEBYLEX",
+        "ZREVRANGEBYSCORE",
+        "ZREVRANK",
+        "ZSCORE",
+    ];
+
+    const DEFAULT_CAPACITY: usize = 1024;
+
+    type Waiters = Vec<oneshot::Sender<RedisResult<Value>>>;
+    type InFlightMap = Mutex<HashMap<Vec<u8>, Waiters>>;
+
+    fn is_coalescable(cmd: &Cmd) -> bool {
+        match cmd.args_iter().next() {
+            Some(Arg::Simple(name)) => COALESCABLE_COMMANDS
+                .iter()
+                .any(|known| known.as_bytes().eq_ignore_ascii_case(name)),
+            _ => false,
+        }
+    }
+
+    fn is_command(cmd: &Cmd, command: &str) -> bool {
+        match cmd.args_iter().next() {
+            Some(Arg::Simple(name)) => command.as_bytes().eq_ignore_ascii_case(name),
+            _ => false,
+        }
+    }
+
+    fn clone_result(result: &RedisResult<Value>) -> RedisResult<Value> {
+        match result {
+            Ok(value) => Ok(value.clone()),
+            Err(err) => Err(err.clone_mostly("Coalesced request failed")),
+        }
+    }
+
+    // Removes the in-flight entry if the request that owns it is dropped before
+    // it completes.  The waiters then see their sender go away and retry.
+    struct InFlightGuard<'a> {
+        in_flight: &'a InFlightMap,
+        key: &'a [u8],
+    }
+
+    impl Drop for InFlightGuard<'_> {
+        fn drop(&mut self) {
+            if let Ok(mut in_flight) = self.in_flight.lock() {
+                in_flight.remove(self.key);
+            }
+        }
+    }
+
+    enum Slot {
+        Owner,
+        Waiter(oneshot::Receiver<RedisResult<Value>>),
+        Full,
+    }
+
+    /// Wraps a connection and coalesces identical read-only requests.
+    ///
+    /// When a request is sent while an identical one (same command and
+    /// arguments) is still waiting for its reply, the second request is not
+    /// sent to the server.  Instead it receives a clone of the reply to the
+    /// first one.  This cuts the load on the server when many tasks read the
+    /// same hot key at once.
+    ///
+    /// Clones of a `CoalescingConnection` share their in-flight requests, so it
+    /// is meant to wrap a connection that is itself cloned to send requests
+    /// concurrently, such as a [`MultiplexedConnection`].
+    ///
+    /// Only read-only commands whose reply depends solely on the data set are
+    /// coalesced; everything else, including pipelines, is passed through.  A
+    /// coalesced reply may have been produced by the server just before the
+    /// request was made, as if the request had been sent slightly earlier.
+    ///
+    /// While a transaction opened with a `MULTI` command is not yet ended by
+    /// `EXEC` or `DISCARD`, all requests are passed through, as the server
+    /// queues them instead of replying.
+    ///
+    /// ```rust,no_run
+    /// # async fn do_something() -> redis::RedisResult<()> {
+    /// use redis::AsyncCommands;
+    ///
+    /// let client = redis::Client::open("redis://127.0.0.1/")?;
+    /// let con = client.get_multiplexed_async_connection().await?;
+    /// let mut con = redis::aio::CoalescingConnection::new(con);
+    /// let value: Option<String> = con.get("hot_key").await?;
+    /// # Ok(()) }
+    /// ```
+    pub struct CoalescingConnection<C> {
+        con: C,
+        in_flight: Arc<InFlightMap>,
+        capacity: usize,
+        // Raised between `MULTI` and `EXEC` or `DISCARD`.  Shared between
+        // clones, as they send to the same connection.
+        in_transaction: Arc<AtomicBool>,
+    }
+
+    impl<C: Clone> Clone for CoalescingConnection<C> {
+        fn clone(&self) -> Self {
+            CoalescingConnection {
+                con: self.con.clone(),
+                in_flight: self.in_flight.clone(),
+                capacity: self.capacity,
+                in_transaction: self.in_transaction.clone(),
+            }
+        }
+    }
+
+    impl<C> CoalescingConnection<C> {
+        /// Wraps `con`, coalescing up to 1024 distinct requests at a time.
+        pub fn new(con: C) -> Self {
+            Self::with_capacity(con, DEFAULT_CAPACITY)
+        }
+
+        /// Wraps `con`, coalescing up to `capacity` distinct requests at a
+        /// time.  Requests beyond that are sent without being coalesced.
+        pub fn with_capacity(con: C, capacity: usize) -> Self {
+            CoalescingConnection {
+                con,
+                in_flight: Arc::new(Mutex::new(HashMap::new())),
+                capacity,
+                in_transaction: Arc::new(AtomicBool::new(false)),
+            }
+        }
+
+        /// Returns a reference to the wrapped connection.
+        pub fn get_ref(&self) -> &C {
+            &self.con
+        }
+
+        /// Unwraps the connection.
+        pub fn into_inner(self) -> C {
+            self.con
+        }
+
+        fn claim(&self, key: &[u8]) -> Slot {
+            let mut in_flight = self.in_flight.lock().unwrap();
+            if let Some(waiters) = in_flight.get_mut(key) {
+                let (sender, receiver) = oneshot::channel();
+                waiters.push(sender);
+                Slot::Waiter(receiver)
+            } else if in_flight.len() < self.capacity {
+                in_flight.insert(key.to_vec(), Vec::new());
+                Slot::Owner
+            } else {
+                Slot::Full
+            }
+        }
+    }
+
+    impl<C> ConnectionLike for CoalescingConnection<C>
+    where
+        C: ConnectionLike + Send,
+    {
+        fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
+            (async move {
+                if is_command(cmd, "MULTI") {
+                    self.in_transaction.store(true, Ordering::SeqCst);
+                } else if is_command(cmd, "EXEC") || is_command(cmd, "DISCARD") {
+                    let result = self.con.req_packed_command(cmd).await;
+                    self.in_transaction.store(false, Ordering::SeqCst);
+                    return result;
+                }
+                if !is_coalescable(cmd) || self.in_transaction.load(Ordering::SeqCst) {
+                    return self.con.req_packed_command(cmd).await;
+                }
+                let key = cmd.get_packed_command();
+                loop {
+                    match self.claim(&key) {
+                        Slot::Owner => {
+                            let guard = InFlightGuard {
+                                in_flight: &self.in_flight,
+                                key: &key,
+                            };
+                            let result = self.con.req_packed_command(cmd).await;
+                            let waiters = self.in_flight.lock().unwrap().remove(&key);
+                            mem::forget(guard);
+                            for waiter in waiters.into_iter().flatten() {
+                                let _ = waiter.send(clone_result(&result));
+                            }
+                            return result;
+                        }
+                        Slot::Waiter(receiver) => {
+                            if let Ok(result) = receiver.await {
+                                return result;
+                            }
+                            // The request we were waiting for was dropped.
+                        }
+                        Slot::Full => return self.con.req_packed_command(cmd).await,
+                    }
+                }
+            })
+            .boxed()
+        }
+
+        fn req_packed_commands<'a>(
+            &'a mut self,
+            cmd: &'a crate::Pipeline,
+            offset: usize,
+            count: usize,
+        ) -> RedisFuture<'a, Vec<Value>> {
+            self.con.req_packed_commands(cmd, offset, count)
+        }
+
+        fn get_db(&self) -> i64 {
+            self.con.get_db()
+        }
+
+        fn command_name_policy(&self) -> CommandNamePolicy {
+            self.con.command_name_policy()
+        }
+    }
+}
+
+pub use coalescing::CoalescingConnection;
+
 #[cfg(feature = "connection-manager")]
 mod connection_manager {
     use super::*;
 
-    use std::sync::Arc;
+    use std::sync::{Arc, Weak};
 
     use arc_swap::{self, ArcSwap};
     use futures::future::{self, Shared};
@@ -976,6 +1614,9 @@ mod connection_manager {
     ///   initiated, will have to await the connection future.
     /// - If reconnecting fails, all pending commands will be failed as well. A
     ///   new reconnection attempt will be triggered if the error is an I/O error.
+    /// - When the underlying connection closes on its own, for example because a
+    ///   keepalive `PING` (see [`RedisConnectionInfo::ping_interval`]) failed, a
+    ///   reconnection is started right away without waiting for a command to fail.
     ///
     /// [multiplexed-connection]: struct.MultiplexedConnection.html
     #[derive(Clone)]
@@ -989,6 +1630,9 @@ mod connection_manager {
         connection: Arc<ArcSwap<SharedRedisFuture<MultiplexedConnection>>>,
 
         runtime: Runtime,
+
+        /// Set by `drain`, stops any further reconnection attempts.
+        closing: Arc<AtomicBool>,
     }
 
     /// A `RedisResult` that can be cloned because `RedisError` is behind an `Arc`.
@@ -1006,16 +1650,40 @@ mod connection_manager {
             // Create a MultiplexedConnection and wait for it to be established
 
             let runtime = Runtime::locate();
-            let connection = client.get_multiplexed_async_connection().await?;
+            let (connection, driver) = client.create_multiplexed_async_connection().await?;
 
             // Wrap the connection in an `ArcSwap` instance for fast atomic access
-            Ok(Self {
+            let manager = Self {
                 client,
                 connection: Arc::new(ArcSwap::from_pointee(
                     future::ok(connection).boxed().shared(),
                 )),
                 runtime,
-            })
+                closing: Arc::new(AtomicBool::new(false)),
+            };
+            Self::spawn_driver(
+                manager.client.clone(),
+                Arc::downgrade(&manager.connection),
+                manager.runtime.clone(),
+                manager.closing.clone(),
+                driver,
+            );
+            Ok(manager)
+        }
+
+        /// Gracefully shuts down the managed connection.
+        ///
+        /// Behaves like [`MultiplexedConnection::drain`] and additionally stops the manager
+        /// from reconnecting.  New requests on this manager and all of its clones fail with
+        /// [`ErrorKind::ClientClosed`] from now on.
+        pub async fn drain(&self, timeout: Duration) -> RedisResult<()> {
+            self.closing.store(true, Ordering::SeqCst);
+            let guard = self.connection.load();
+            match (**guard).clone().await {
+                Ok(connection) => connection.drain(timeout).await,
+                // There is no connection left to drain.
+                Err(_) => Ok(()),
+            }
         }
 
         /// Reconnect and overwrite the old connection.
@@ -1026,11 +1694,20 @@ mod connection_manager {
             &self,
             current: arc_swap::Guard<Arc<SharedRedisFuture<MultiplexedConnection>>>,
         ) {
+            if self.closing.load(Ordering::SeqCst) {
+                return;
+            }
             let client = self.client.clone();
-            let new_connection: SharedRedisFuture<MultiplexedConnection> =
-                async move { Ok(client.get_multiplexed_async_connection().await?) }
-                    .boxed()
-                    .shared();
+            let connection = Arc::downgrade(&self.connection);
+            let runtime = self.runtime.clone();
+            let closing = self.closing.clone();
+            let new_connection: SharedRedisFuture<MultiplexedConnection> = async move {
+                let (con, driver) = client.create_multiplexed_async_connection().await?;
+                Self::spawn_driver(client, connection, runtime, closing, driver);
+                Ok(con)
+            }
+            .boxed()
+            .shared();
 
             // Update the connection in the connection manager
             let new_connection_arc = Arc::new(new_connection.clone());
@@ -1044,6 +1721,40 @@ mod connection_manager {
                 self.runtime.spawn(new_connection.map(|_| ()));
             }
         }
+
+        /// Drives a freshly created connection in the background.
+        ///
+        /// The driver only completes once the connection is gone, e.g. because the
+        /// server closed it or a keepalive `PING` failed. In that case a reconnect is
+        /// started right away instead of waiting for the next command to fail.
+        fn spawn_driver(
+            client: Client,
+            connection: Weak<ArcSwap<SharedRedisFuture<MultiplexedConnection>>>,
+            runtime: Runtime,
+            closing: Arc<AtomicBool>,
+            driver: Pin<Box<dyn Future<Output = ()> + Send>>,
+        ) {
+            runtime.clone().spawn(async move {
+                driver.await;
+                let connection = match connection.upgrade() {
+                    Some(connection) => connection,
+                    // The manager was dropped, nothing left to do
+                    None => return,
+                };
+                let manager = ConnectionManager {
+                    client,
+                    connection,
+                    runtime,
+                    closing,
+                };
+                let current = manager.connection.load();
+                if let Some(Ok(con)) = current.peek() {
+                    if con.is_closed() {
+                        manager.reconnect(current);
+                    }
+                }
+            });
+        }
     }
 
     /// Handle a command result. If the connection was dropped, reconnect.
@@ -1073,6 +1784,9 @@ mod connection_manager {
     impl ConnectionLike for ConnectionManager {
         fn req_packed_command<'a>(&'a mut self, cmd: &'a Cmd) -> RedisFuture<'a, Value> {
             (async move {
+                if self.closing.load(Ordering::SeqCst) {
+                    fail!((ErrorKind::ClientClosed, "Connection manager is draining"));
+                }
                 // Clone connection to avoid having to lock the ArcSwap in write mode
                 let guard = self.connection.load();
                 let connection_result = (**guard)
@@ -1094,6 +1808,9 @@ mod connection_manager {
             count: usize,
         ) -> RedisFuture<'a, Vec<Value>> {
             (async move {
+                if self.closing.load(Ordering::SeqCst) {
+                    fail!((ErrorKind::ClientClosed, "Connection manager is draining"));
+                }
                 // Clone shared connection future to avoid having to lock the ArcSwap in write mode
                 let guard = self.connection.load();
                 let connection_result = (**guard)
@@ -1113,6 +1830,10 @@ mod connection_manager {
         fn get_db(&self) -> i64 {
             self.client.connection_info().redis.db
         }
+
+        fn command_name_policy(&self) -> CommandNamePolicy {
+            self.client.connection_info().redis.command_name_policy
+        }
     }
 }
 
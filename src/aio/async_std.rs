@@ -6,9 +6,11 @@ use std::{
     net::SocketAddr,
     pin::Pin,
     task::{self, Poll},
+    time::Duration,
 };
 
 use crate::aio::{AsyncStream, RedisRuntime};
+use crate::connection::set_tcp_keepalive;
 use crate::types::RedisResult;
 #[cfg(feature = "tls")]
 use async_native_tls::{TlsConnector, TlsStream};
@@ -17,6 +19,7 @@ use async_std::net::TcpStream;
 use async_std::os::unix::net::UnixStream;
 use async_trait::async_trait;
 use futures_util::ready;
+use socket2::SockRef;
 use tokio::io::{AsyncRead, AsyncWrite, ReadBuf};
 
 pin_project_lite::pin_project! {
@@ -143,10 +146,13 @@ impl AsyncRead for AsyncStd {
 
 #[async_trait]
 impl RedisRuntime for AsyncStd {
-    async fn connect_tcp(socket_addr: SocketAddr) -> RedisResult<Self> {
-        Ok(TcpStream::connect(&socket_addr)
-            .await
-            .map(|con| Self::Tcp(AsyncStdWrapped::new(con)))?)
+    async fn connect_tcp(
+        socket_addr: SocketAddr,
+        keepalive: Option<Duration>,
+    ) -> RedisResult<Self> {
+        let tcp_stream = TcpStream::connect(&socket_addr).await?;
+        set_tcp_keepalive(SockRef::from(&tcp_stream), keepalive)?;
+        Ok(Self::Tcp(AsyncStdWrapped::new(tcp_stream)))
     }
 
     #[cfg(feature = "tls")]
@@ -154,8 +160,10 @@ impl RedisRuntime for AsyncStd {
         hostname: &str,
         socket_addr: SocketAddr,
         insecure: bool,
+        keepalive: Option<Duration>,
     ) -> RedisResult<Self> {
         let tcp_stream = TcpStream::connect(&socket_addr).await?;
+        set_tcp_keepalive(SockRef::from(&tcp_stream), keepalive)?;
         let tls_connector = if insecure {
             TlsConnector::new()
                 .danger_accept_invalid_certs(true)
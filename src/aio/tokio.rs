@@ -1,12 +1,16 @@
 use super::{async_trait, AsyncStream, RedisResult, RedisRuntime, SocketAddr};
+use crate::connection::set_tcp_keepalive;
 
 use std::{
     future::Future,
     io,
     pin::Pin,
     task::{self, Poll},
+    time::Duration,
 };
 
+use socket2::SockRef;
+
 #[cfg(unix)]
 use tokio::net::UnixStream as UnixStreamTokio;
 
@@ -89,10 +93,13 @@ impl AsyncRead for Tokio {
 
 #[async_trait]
 impl RedisRuntime for Tokio {
-    async fn connect_tcp(socket_addr: SocketAddr) -> RedisResult<Self> {
-        Ok(TcpStreamTokio::connect(&socket_addr)
-            .await
-            .map(Tokio::Tcp)?)
+    async fn connect_tcp(
+        socket_addr: SocketAddr,
+        keepalive: Option<Duration>,
+    ) -> RedisResult<Self> {
+        let tcp = TcpStreamTokio::connect(&socket_addr).await?;
+        set_tcp_keepalive(SockRef::from(&tcp), keepalive)?;
+        Ok(Tokio::Tcp(tcp))
     }
 
     #[cfg(feature = "tls")]
@@ -100,7 +107,10 @@ impl RedisRuntime for Tokio {
         hostname: &str,
         socket_addr: SocketAddr,
         insecure: bool,
+        keepalive: Option<Duration>,
     ) -> RedisResult<Self> {
+        let tcp = TcpStreamTokio::connect(&socket_addr).await?;
+        set_tcp_keepalive(SockRef::from(&tcp), keepalive)?;
         let tls_connector: tokio_native_tls::TlsConnector = if insecure {
             TlsConnector::builder()
                 .danger_accept_invalid_certs(true)
@@ -112,7 +122,7 @@ impl RedisRuntime for Tokio {
         }
         .into();
         Ok(tls_connector
-            .connect(hostname, TcpStreamTokio::connect(&socket_addr).await?)
+            .connect(hostname, tcp)
             .await
             .map(Tokio::TcpTls)?)
     }
@@ -4,14 +4,23 @@ use std::time::Duration;
 use std::pin::Pin;
 
 use crate::{
+    cmd::{Cmd, CommandNamePolicy},
+    commands::{Commands, UnblockKind},
     connection::{connect, Connection, ConnectionInfo, ConnectionLike, IntoConnectionInfo},
-    types::{RedisResult, Value},
+    hook::DebugHook,
+    types::{HelloInfo, RedisResult, Value},
 };
 
+/// The longest pause between two attempts of [`Client::cancellable`] to
+/// unblock a command.
+#[cfg(any(feature = "tokio-comp", feature = "async-std-comp"))]
+const MAX_UNBLOCK_BACKOFF: Duration = Duration::from_millis(100);
+
 /// The client type.
 #[derive(Debug, Clone)]
 pub struct Client {
     connection_info: ConnectionInfo,
+    debug_hook: Option<DebugHook>,
 }
 
 /// The client acts as connector to the redis server.  By itself it does not
@@ -37,6 +46,7 @@ impl Client {
     pub fn open<T: IntoConnectionInfo>(params: T) -> RedisResult<Client> {
         Ok(Client {
             connection_info: params.into_connection_info()?,
+            debug_hook: None,
         })
     }
 
@@ -46,7 +56,9 @@ impl Client {
     /// (like unreachable host) so it's important that you handle those
     /// errors.
     pub fn get_connection(&self) -> RedisResult<Connection> {
-        connect(&self.connection_info, None)
+        let mut con = connect(&self.connection_info, None)?;
+        con.set_debug_hook(self.debug_hook.clone());
+        Ok(con)
     }
 
     /// Instructs the client to actually connect to redis with specified
@@ -55,13 +67,76 @@ impl Client {
     /// a variety of errors (like unreachable host) so it's important
     /// that you handle those errors.
     pub fn get_connection_with_timeout(&self, timeout: Duration) -> RedisResult<Connection> {
-        connect(&self.connection_info, Some(timeout))
+        let mut con = connect(&self.connection_info, Some(timeout))?;
+        con.set_debug_hook(self.debug_hook.clone());
+        Ok(con)
     }
 
     /// Returns a reference of client connection info object.
     pub fn get_connection_info(&self) -> &ConnectionInfo {
         &self.connection_info
     }
+
+    /// Sets the [`DebugHook`] that sees all commands and replies of the
+    /// connections opened by this client from now on, or removes it with
+    /// `None`.
+    ///
+    /// A `ConnectionManager` created from the client also installs the hook
+    /// on every connection it reconnects with.  The commands sent while
+    /// connecting, like `AUTH`, are not reported.
+    pub fn set_debug_hook(&mut self, hook: Option<DebugHook>) {
+        self.debug_hook = hook;
+    }
+
+    /// Cancels the blocking command, like `BLPOP`, that the connection with
+    /// id `client_id` waits in, by sending `CLIENT UNBLOCK` over a new
+    /// connection.  Returns whether the connection was blocked.
+    ///
+    /// The id of a connection is reported by [`Connection::client_id`] if
+    /// [`fetch_client_id`](crate::RedisConnectionInfo::fetch_client_id) was
+    /// set.
+    ///
+    /// ```rust,no_run
+    /// # fn do_something() -> redis::RedisResult<()> {
+    /// use redis::{Commands, IntoConnectionInfo, UnblockKind};
+    ///
+    /// let mut info = "redis://127.0.0.1/".into_connection_info()?;
+    /// info.redis.fetch_client_id = true;
+    /// let client = redis::Client::open(info)?;
+    /// let mut con = client.get_connection()?;
+    /// let client_id = con.client_id().unwrap();
+    ///
+    /// let canceller = client.clone();
+    /// std::thread::spawn(move || canceller.unblock(client_id, UnblockKind::Timeout));
+    /// let popped: Option<(String, String)> = con.blpop("queue", 0)?;
+    /// # Ok(()) }
+    /// ```
+    pub fn unblock(&self, client_id: i64, kind: UnblockKind) -> RedisResult<bool> {
+        self.get_connection()?.client_unblock(client_id, kind)
+    }
+
+    /// Connects to redis and negotiates protocol version `protover` with
+    /// `HELLO`, returning the connection together with the details the
+    /// server reported about itself.
+    ///
+    /// Only protocol version 2 can be parsed by this library, other versions
+    /// fail with `ClientError` before connecting, like [`Commands::hello`](crate::Commands::hello).  Servers older than
+    /// Redis 6.0 do not know `HELLO` and fail with a response error.
+    ///
+    /// ```rust,no_run
+    /// # fn do_something() -> redis::RedisResult<()> {
+    /// let client = redis::Client::open("redis://127.0.0.1/")?;
+    /// let (con, hello) = client.connect_and_probe(2)?;
+    /// println!("connected to {} {}", hello.server, hello.version);
+    /// # Ok(()) }
+    /// ```
+    pub fn connect_and_probe(&self, protover: u8) -> RedisResult<(Connection, HelloInfo)> {
+        let hello = Cmd::hello(protover);
+        hello.check_args()?;
+        let mut con = self.get_connection()?;
+        let hello = hello.query(&mut con)?;
+        Ok((con, hello))
+    }
 }
 
 /// To enable async support you need to chose one of the supported runtimes and active its
@@ -85,7 +160,76 @@ impl Client {
             }
         };
 
-        crate::aio::Connection::new(&self.connection_info.redis, con).await
+        let mut con = crate::aio::Connection::new(&self.connection_info.redis, con).await?;
+        con.set_debug_hook(self.debug_hook.clone());
+        Ok(con)
+    }
+
+    /// Runs `blocked`, a blocking command sent on the connection with id
+    /// `client_id`, until `cancel` completes first.  The command is then
+    /// cancelled with `CLIENT UNBLOCK`, see [`unblock`](Self::unblock), and
+    /// returns promptly as determined by `kind`.
+    ///
+    /// `cancel` can be any future, e.g. `token.cancelled()` of a
+    /// `tokio_util::sync::CancellationToken`.
+    ///
+    /// ```rust,no_run
+    /// # async fn do_something(client: redis::Client) -> redis::RedisResult<()> {
+    /// use redis::{AsyncCommands, UnblockKind};
+    /// use std::time::Duration;
+    ///
+    /// let mut con = client.get_async_connection().await?;
+    /// let client_id = con.client_id().unwrap();
+    /// let cancel = tokio::time::sleep(Duration::from_secs(1));
+    /// let popped: Option<(String, String)> = client
+    ///     .cancellable(client_id, UnblockKind::Timeout, con.blpop("queue", 0), cancel)
+    ///     .await?;
+    /// # Ok(()) }
+    /// ```
+    #[cfg(any(feature = "tokio-comp", feature = "async-std-comp"))]
+    #[cfg_attr(
+        docsrs,
+        doc(cfg(any(feature = "tokio-comp", feature = "async-std-comp")))
+    )]
+    pub async fn cancellable<T, B, C>(
+        &self,
+        client_id: i64,
+        kind: UnblockKind,
+        blocked: B,
+        cancel: C,
+    ) -> RedisResult<T>
+    where
+        B: std::future::Future<Output = RedisResult<T>>,
+        C: std::future::Future<Output = ()>,
+    {
+        use crate::commands::AsyncCommands;
+        use futures_util::future::{select, Either};
+
+        futures_util::pin_mut!(blocked);
+        futures_util::pin_mut!(cancel);
+        let mut blocked = match select(blocked, cancel).await {
+            Either::Left((result, _)) => return result,
+            Either::Right(((), blocked)) => blocked,
+        };
+
+        // `CLIENT UNBLOCK` does nothing if the server did not start blocking yet, e.g. when
+        // `cancel` was complete from the start, so it is repeated until it succeeds or the
+        // command returns on its own.
+        let mut con = self.get_async_connection().await?;
+        let runtime = Runtime::locate();
+        let mut backoff = Duration::from_millis(1);
+        loop {
+            if con.client_unblock(client_id, kind).await? {
+                return blocked.await;
+            }
+            let pause = runtime.sleep(backoff);
+            futures_util::pin_mut!(pause);
+            blocked = match select(blocked, pause).await {
+                Either::Left((result, _)) => return result,
+                Either::Right(((), blocked)) => blocked,
+            };
+            backoff = (backoff * 2).min(MAX_UNBLOCK_BACKOFF);
+        }
     }
 
     /// Returns an async connection from the client.
@@ -93,11 +237,11 @@ impl Client {
     #[cfg_attr(docsrs, doc(cfg(feature = "tokio-comp")))]
     pub async fn get_tokio_connection(&self) -> RedisResult<crate::aio::Connection> {
         use crate::aio::RedisRuntime;
-        Ok(
-            crate::aio::connect::<crate::aio::tokio::Tokio>(&self.connection_info)
-                .await?
-                .map(RedisRuntime::boxed),
-        )
+        let mut con = crate::aio::connect::<crate::aio::tokio::Tokio>(&self.connection_info)
+            .await?
+            .map(RedisRuntime::boxed);
+        con.set_debug_hook(self.debug_hook.clone());
+        Ok(con)
     }
 
     /// Returns an async connection from the client.
@@ -105,11 +249,11 @@ impl Client {
     #[cfg_attr(docsrs, doc(cfg(feature = "async-std-comp")))]
     pub async fn get_async_std_connection(&self) -> RedisResult<crate::aio::Connection> {
         use crate::aio::RedisRuntime;
-        Ok(
-            crate::aio::connect::<crate::aio::async_std::AsyncStd>(&self.connection_info)
-                .await?
-                .map(RedisRuntime::boxed),
-        )
+        let mut con = crate::aio::connect::<crate::aio::async_std::AsyncStd>(&self.connection_info)
+            .await?
+            .map(RedisRuntime::boxed);
+        con.set_debug_hook(self.debug_hook.clone());
+        Ok(con)
     }
 
     /// Returns an async connection from the client.
@@ -212,6 +356,28 @@ impl Client {
         Ok(crate::aio::ConnectionManager::new(self.clone()).await?)
     }
 
+    #[cfg(feature = "connection-manager")]
+    pub(crate) async fn create_multiplexed_async_connection(
+        &self,
+    ) -> RedisResult<(
+        crate::aio::MultiplexedConnection,
+        Pin<Box<dyn std::future::Future<Output = ()> + Send>>,
+    )> {
+        let (connection, driver) = match Runtime::locate() {
+            #[cfg(feature = "tokio-comp")]
+            Runtime::Tokio => {
+                let (connection, driver) = self.create_multiplexed_tokio_connection().await?;
+                (connection, Box::pin(driver) as Pin<Box<_>>)
+            }
+            #[cfg(feature = "async-std-comp")]
+            Runtime::AsyncStd => {
+                let (connection, driver) = self.create_multiplexed_async_std_connection().await?;
+                (connection, Box::pin(driver) as Pin<Box<_>>)
+            }
+        };
+        Ok((connection, driver))
+    }
+
     async fn get_multiplexed_async_connection_inner<T>(
         &self,
     ) -> RedisResult<crate::aio::MultiplexedConnection>
@@ -235,7 +401,10 @@ impl Client {
         T: crate::aio::RedisRuntime,
     {
         let con = self.get_simple_async_connection::<T>().await?;
-        crate::aio::MultiplexedConnection::new(&self.connection_info.redis, con).await
+        let (mut con, driver) =
+            crate::aio::MultiplexedConnection::new(&self.connection_info.redis, con).await?;
+        con.set_debug_hook(self.debug_hook.clone());
+        Ok((con, driver))
     }
 
     async fn get_simple_async_connection<T>(
@@ -292,14 +461,72 @@ impl ConnectionLike for Client {
             false
         }
     }
+
+    fn command_name_policy(&self) -> CommandNamePolicy {
+        self.connection_info.redis.command_name_policy
+    }
 }
 
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::types::ErrorKind;
+    use std::io::{Read, Write};
+    use std::net::TcpListener;
 
     #[test]
     fn regression_293_parse_ipv6_with_interface() {
         assert!(Client::open(("fe80::cafe:beef%eno1", 6379)).is_ok());
     }
+
+    #[test]
+    fn test_connect_and_probe_sends_hello() {
+        let listener = TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (mut socket, _) = listener.accept().unwrap();
+            let mut received = vec![];
+            while !received.ends_with(b"\r\n2\r\n") {
+                let mut buf = [0; 512];
+                let n = socket.read(&mut buf).unwrap();
+                assert_ne!(n, 0);
+                received.extend_from_slice(&buf[..n]);
+            }
+            socket
+                .write_all(
+                    b"*14\r\n$6\r\nserver\r\n$5\r\nredis\r\n$7\r\nversion\r\n$5\r\n7.2.4\r\n\
+                      $5\r\nproto\r\n:2\r\n$2\r\nid\r\n:7\r\n$4\r\nmode\r\n$10\r\nstandalone\r\n\
+                      $4\r\nrole\r\n$6\r\nmaster\r\n$7\r\nmodules\r\n*0\r\n",
+                )
+                .unwrap();
+            received
+        });
+
+        let client = Client::open(("127.0.0.1", port)).unwrap();
+        let (_con, hello) = client.connect_and_probe(2).unwrap();
+        assert_eq!(server.join().unwrap(), b"*2\r\n$5\r\nHELLO\r\n$1\r\n2\r\n");
+        assert_eq!(hello.version, "7.2.4");
+        assert_eq!(hello.proto, 2);
+        assert_eq!(hello.id, 7);
+        assert_eq!(hello.mode, "standalone");
+        assert!(hello.modules.is_empty());
+    }
+
+    #[test]
+    fn test_connect_and_probe_rejects_unsupported_protocol() {
+        let client = Client::open("redis://127.0.0.1:1/").unwrap();
+        let err = client.connect_and_probe(3).err().unwrap();
+        assert_eq!(err.kind(), ErrorKind::ClientError);
+    }
+
+    #[test]
+    fn test_hello_rejects_unsupported_protocol() {
+        use crate::Commands;
+
+        // Nothing listens on port 1, so this only passes if the command
+        // fails before a connection is made.
+        let mut client = Client::open("redis://127.0.0.1:1/").unwrap();
+        let err = client.hello::<HelloInfo>(3).err().unwrap();
+        assert_eq!(err.kind(), ErrorKind::ClientError);
+    }
 }
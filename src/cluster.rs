@@ -40,7 +40,7 @@
 //! ```
 use std::cell::RefCell;
 use std::collections::{BTreeMap, HashMap, HashSet};
-use std::iter::Iterator;
+use std::iter::{self, Iterator};
 use std::thread;
 use std::time::Duration;
 
@@ -50,14 +50,19 @@ use rand::{
 };
 
 use super::{
-    cmd, parse_redis_value, Cmd, Connection, ConnectionAddr, ConnectionInfo, ConnectionLike,
-    ErrorKind, IntoConnectionInfo, RedisError, RedisResult, Value,
+    cmd, from_redis_value, parse_redis_value, Cmd, Connection, ConnectionAddr, ConnectionInfo,
+    ConnectionLike, ErrorKind, FromRedisValue, IntoConnectionInfo, RedisError, RedisResult,
+    ToRedisArgs, Value,
 };
 
+pub mod admin;
+
+pub use crate::cluster_breaker::BreakerState;
+use crate::cluster_breaker::{BreakerConfig, Breakers};
 pub use crate::cluster_client::{ClusterClient, ClusterClientBuilder};
 use crate::cluster_pipeline::UNROUTABLE_ERROR;
 pub use crate::cluster_pipeline::{cluster_pipe, ClusterPipeline};
-use crate::cluster_routing::{Routable, RoutingInfo, Slot, SLOT_SIZE};
+use crate::cluster_routing::{get_hashtag, slot_for_key, Routable, RoutingInfo, Slot, SLOT_SIZE};
 
 type SlotMap = BTreeMap<u16, String>;
 
@@ -66,6 +71,10 @@ pub struct ClusterConnection {
     initial_nodes: Vec<ConnectionInfo>,
     connections: RefCell<HashMap<String, Connection>>,
     slots: RefCell<SlotMap>,
+    // All nodes serving each slot range of `slots`, only kept for readonly
+    // connections.
+    slot_nodes: RefCell<BTreeMap<u16, Vec<String>>>,
+    breakers: RefCell<Breakers>,
     auto_reconnect: RefCell<bool>,
     readonly: bool,
     username: Option<String>,
@@ -73,6 +82,8 @@ pub struct ClusterConnection {
     read_timeout: RefCell<Option<Duration>>,
     write_timeout: RefCell<Option<Duration>>,
     tls: Option<TlsMode>,
+    retries: u32,
+    connection_timeout: Option<Duration>,
 }
 
 #[derive(Clone, Copy)]
@@ -97,17 +108,23 @@ impl ClusterConnection {
         readonly: bool,
         username: Option<String>,
         password: Option<String>,
+        retries: u32,
+        connection_timeout: Option<Duration>,
+        breaker: BreakerConfig,
     ) -> RedisResult<ClusterConnection> {
         let connections = Self::create_initial_connections(
             &initial_nodes,
             readonly,
             username.clone(),
             password.clone(),
+            connection_timeout,
         )?;
 
         let connection = ClusterConnection {
             connections: RefCell::new(connections),
             slots: RefCell::new(SlotMap::new()),
+            slot_nodes: RefCell::new(BTreeMap::new()),
+            breakers: RefCell::new(Breakers::new(breaker)),
             auto_reconnect: RefCell::new(true),
             readonly,
             username,
@@ -134,6 +151,8 @@ impl ClusterConnection {
             #[cfg(not(feature = "tls"))]
             tls: None,
             initial_nodes,
+            retries,
+            connection_timeout,
         };
         connection.refresh_slots()?;
 
@@ -147,6 +166,12 @@ impl ClusterConnection {
         *auto_reconnect = value;
     }
 
+    /// Returns the state of the circuit breaker of `node`, an address as
+    /// passed to the [`breaker_hook`](ClusterClientBuilder::breaker_hook).
+    pub fn breaker_state(&self, node: &str) -> BreakerState {
+        self.breakers.borrow().state(node)
+    }
+
     /// Sets the write timeout for the connection.
     ///
     /// If the provided value is `None`, then `send_packed_command` call will
@@ -188,6 +213,108 @@ impl ClusterConnection {
         true
     }
 
+    /// Gets the values of keys that may live in different hash slots.
+    ///
+    /// A plain `MGET` fails with `CROSSSLOT` unless all of its keys hash to
+    /// the same slot.  This splits the keys by slot, sends one `MGET` per slot
+    /// (pipelined per node) and returns the values in the order of `keys`,
+    /// with `None` for keys that do not exist.
+    ///
+    /// Any error fails the whole call; use
+    /// [`cluster_mget_partial`](Self::cluster_mget_partial) to get the values
+    /// of the slots that succeeded.
+    pub fn cluster_mget<K: ToRedisArgs, V: FromRedisValue>(
+        &mut self,
+        keys: K,
+    ) -> RedisResult<Vec<Option<V>>> {
+        let keys = keys.to_redis_args();
+        let groups = group_by_slot(keys.iter().map(|key| &key[..]));
+        let cmds: Vec<Cmd> = groups
+            .iter()
+            .map(|indexes| mget_cmd(&keys, indexes))
+            .collect();
+
+        let mut values = vec![Value::Nil; keys.len()];
+        for (indexes, reply) in groups.iter().zip(self.send_recv_and_retry_cmds(&cmds)?) {
+            for (&idx, value) in indexes.iter().zip(mget_values(reply, indexes.len())?) {
+                values[idx] = value;
+            }
+        }
+        values.iter().map(from_redis_value).collect()
+    }
+
+    /// Gets the values of keys that may live in different hash slots,
+    /// reporting errors per key.
+    ///
+    /// Like [`cluster_mget`](Self::cluster_mget), but the `MGET` for every
+    /// slot is sent on its own and an error only affects the keys of that
+    /// slot.  The result has one entry per key, in the order of `keys`.
+    pub fn cluster_mget_partial<K: ToRedisArgs, V: FromRedisValue>(
+        &mut self,
+        keys: K,
+    ) -> Vec<RedisResult<Option<V>>> {
+        let keys = keys.to_redis_args();
+        let mut results: Vec<RedisResult<Option<V>>> = keys.iter().map(|_| Ok(None)).collect();
+        for indexes in group_by_slot(keys.iter().map(|key| &key[..])) {
+            match self
+                .req_command(&mget_cmd(&keys, &indexes))
+                .and_then(|reply| mget_values(reply, indexes.len()))
+            {
+                Ok(values) => {
+                    for (idx, value) in indexes.into_iter().zip(values) {
+                        results[idx] = from_redis_value(&value);
+                    }
+                }
+                Err(err) => {
+                    for idx in indexes {
+                        results[idx] = Err(err.clone_mostly("MGET failed"));
+                    }
+                }
+            }
+        }
+        results
+    }
+
+    /// Sets keys that may live in different hash slots to their values.
+    ///
+    /// This splits the pairs by the slot of their key and sends one `MSET`
+    /// per slot (pipelined per node).  Unlike a plain `MSET` this is not
+    /// atomic: the keys of every slot are set atomically, but when an error
+    /// is returned the keys of other slots may already have been set.  Use
+    /// [`cluster_mset_partial`](Self::cluster_mset_partial) to learn which.
+    pub fn cluster_mset<K: ToRedisArgs, V: ToRedisArgs>(
+        &mut self,
+        items: &[(K, V)],
+    ) -> RedisResult<()> {
+        let cmds: Vec<Cmd> = group_pairs_by_slot(items)
+            .iter()
+            .map(|indexes| mset_cmd(items, indexes))
+            .collect();
+        self.send_recv_and_retry_cmds(&cmds)?;
+        Ok(())
+    }
+
+    /// Sets keys that may live in different hash slots to their values,
+    /// reporting errors per key.
+    ///
+    /// Like [`cluster_mset`](Self::cluster_mset), but the `MSET` for every
+    /// slot is sent on its own and an error only affects the keys of that
+    /// slot.  The result has one entry per pair, in the order of `items`.
+    pub fn cluster_mset_partial<K: ToRedisArgs, V: ToRedisArgs>(
+        &mut self,
+        items: &[(K, V)],
+    ) -> Vec<RedisResult<()>> {
+        let mut results: Vec<RedisResult<()>> = items.iter().map(|_| Ok(())).collect();
+        for indexes in group_pairs_by_slot(items) {
+            if let Err(err) = self.req_command(&mset_cmd(items, &indexes)) {
+                for idx in indexes {
+                    results[idx] = Err(err.clone_mostly("MSET failed"));
+                }
+            }
+        }
+        results
+    }
+
     pub(crate) fn execute_pipeline(&mut self, pipe: &ClusterPipeline) -> RedisResult<Vec<Value>> {
         self.send_recv_and_retry_cmds(pipe.commands())
     }
@@ -204,12 +331,15 @@ impl ClusterConnection {
         readonly: bool,
         username: Option<String>,
         password: Option<String>,
+        connection_timeout: Option<Duration>,
     ) -> RedisResult<HashMap<String, Connection>> {
         let mut connections = HashMap::with_capacity(initial_nodes.len());
 
         for info in initial_nodes.iter() {
             let addr = match info.addr {
-                ConnectionAddr::Tcp(ref host, port) => format!("redis://{}:{}", host, port),
+                ConnectionAddr::Tcp(ref host, port) => {
+                    build_connection_string(host, Some(port), None)
+                }
                 ConnectionAddr::TcpTls {
                     ref host,
                     port,
@@ -221,9 +351,13 @@ impl ClusterConnection {
                 _ => panic!("No reach."),
             };
 
-            if let Ok(mut conn) =
-                connect(info.clone(), readonly, username.clone(), password.clone())
-            {
+            if let Ok(mut conn) = connect(
+                info.clone(),
+                readonly,
+                username.clone(),
+                password.clone(),
+                connection_timeout,
+            ) {
                 if conn.check_connection() {
                     connections.insert(addr, conn);
                     break;
@@ -245,14 +379,25 @@ impl ClusterConnection {
         let mut slots = self.slots.borrow_mut();
         *slots = if self.readonly {
             let mut rng = thread_rng();
-            self.create_new_slots(|slot_data| {
+            let mut slot_nodes = BTreeMap::new();
+            let slots = self.create_new_slots(|slot_data| {
                 let replicas = slot_data.replicas();
+                slot_nodes.insert(
+                    slot_data.end(),
+                    replicas
+                        .iter()
+                        .cloned()
+                        .chain(iter::once(slot_data.master().to_string()))
+                        .collect(),
+                );
                 if replicas.is_empty() {
                     slot_data.master().to_string()
                 } else {
                     replicas.choose(&mut rng).unwrap().to_string()
                 }
-            })?
+            })?;
+            *self.slot_nodes.borrow_mut() = slot_nodes;
+            slots
         } else {
             self.create_new_slots(|slot_data| slot_data.master().to_string())?
         };
@@ -272,18 +417,26 @@ impl ClusterConnection {
                         }
                     }
 
+                    let mut breakers = self.breakers.borrow_mut();
+                    if !breakers.allow(addr) {
+                        continue;
+                    }
                     if let Ok(mut conn) = connect(
                         addr.as_ref(),
                         self.readonly,
                         self.username.clone(),
                         self.password.clone(),
+                        self.connection_timeout,
                     ) {
                         if conn.check_connection() {
                             conn.set_read_timeout(*self.read_timeout.borrow())?;
                             conn.set_write_timeout(*self.write_timeout.borrow())?;
+                            breakers.record_success(addr);
                             new_connections.insert(addr.to_string(), conn);
+                            continue;
                         }
                     }
+                    breakers.record_failure(addr);
                 }
             }
             new_connections
@@ -355,11 +508,10 @@ impl ClusterConnection {
         slot: u16,
     ) -> RedisResult<(String, &'a mut Connection)> {
         let slots = self.slots.borrow();
-        if let Some((_, addr)) = slots.range(&slot..).next() {
-            Ok((
-                addr.to_string(),
-                self.get_connection_by_addr(connections, addr)?,
-            ))
+        if let Some((&end, addr)) = slots.range(&slot..).next() {
+            let addr = self.available_node(end, addr);
+            let conn = self.get_connection_by_addr(connections, &addr)?;
+            Ok((addr, conn))
         } else {
             // try a random node next.  This is safe if slots are involved
             // as a wrong node would reject the request.
@@ -367,11 +519,27 @@ impl ClusterConnection {
         }
     }
 
+    // Returns `addr`, unless its circuit breaker is open and another node
+    // serving the slot range ending at `end` can take the request instead.
+    fn available_node(&self, end: u16, addr: &str) -> String {
+        let mut breakers = self.breakers.borrow_mut();
+        if !self.readonly || breakers.allow(addr) {
+            return addr.to_string();
+        }
+        self.slot_nodes
+            .borrow()
+            .get(&end)
+            .and_then(|nodes| nodes.iter().find(|node| breakers.allow(node)))
+            .map_or(addr, |node| node.as_str())
+            .to_string()
+    }
+
     fn get_connection_by_addr<'a>(
         &self,
         connections: &'a mut HashMap<String, Connection>,
         addr: &str,
     ) -> RedisResult<&'a mut Connection> {
+        self.breakers.borrow_mut().check(addr)?;
         if connections.contains_key(addr) {
             Ok(connections.get_mut(addr).unwrap())
         } else {
@@ -382,8 +550,19 @@ impl ClusterConnection {
                 self.readonly,
                 self.username.clone(),
                 self.password.clone(),
-            )?;
-            Ok(connections.entry(addr.to_string()).or_insert(conn))
+                self.connection_timeout,
+            );
+            let mut breakers = self.breakers.borrow_mut();
+            match conn {
+                Ok(conn) => {
+                    breakers.record_success(addr);
+                    Ok(connections.entry(addr.to_string()).or_insert(conn))
+                }
+                Err(err) => {
+                    breakers.record_failure(addr);
+                    Err(err)
+                }
+            }
         }
     }
 
@@ -403,8 +582,25 @@ impl ClusterConnection {
         Ok(T::merge_results(results))
     }
 
+    fn request<R, T, F>(&self, cmd: &R, func: F) -> RedisResult<T>
+    where
+        R: ?Sized + Routable,
+        T: MergeResults + std::fmt::Debug,
+        F: FnMut(&mut Connection) -> RedisResult<T>,
+    {
+        self.request_with_redirect(cmd, func, None)
+            .map_err(|err| with_hash_tag_hint(cmd, err))
+    }
+
+    // Like `request`, but if `asking` is given the first attempt is sent to that node, prefixed
+    // with `ASKING`. This is used to follow an `ASK` redirect that was received elsewhere.
     #[allow(clippy::unnecessary_unwrap)]
-    fn request<R, T, F>(&self, cmd: &R, mut func: F) -> RedisResult<T>
+    fn request_with_redirect<R, T, F>(
+        &self,
+        cmd: &R,
+        mut func: F,
+        asking: Option<String>,
+    ) -> RedisResult<T>
     where
         R: ?Sized + Routable,
         T: MergeResults + std::fmt::Debug,
@@ -419,10 +615,10 @@ impl ClusterConnection {
             None => fail!(UNROUTABLE_ERROR),
         };
 
-        let mut retries = 16;
+        let mut retries = self.retries;
         let mut excludes = HashSet::new();
-        let mut redirected = None::<String>;
-        let mut is_asking = false;
+        let mut is_asking = asking.is_some();
+        let mut redirected = asking;
         loop {
             // Get target address and response.
             let (addr, rv) = {
@@ -442,13 +638,19 @@ impl ClusterConnection {
                 } else {
                     self.get_connection(&mut *connections, slot.unwrap())?
                 };
-                (addr, func(conn))
+                let rv = func(conn);
+                let mut breakers = self.breakers.borrow_mut();
+                match rv {
+                    Err(ref err) if err.is_io_error() => breakers.record_failure(&addr),
+                    _ => breakers.record_success(&addr),
+                }
+                (addr, rv)
             };
 
             match rv {
                 Ok(rv) => return Ok(rv),
                 Err(err) => {
-                    retries -= 1;
+                    retries = retries.saturating_sub(1);
                     if retries == 0 {
                         return Err(err);
                     }
@@ -457,10 +659,13 @@ impl ClusterConnection {
                         let kind = err.kind();
 
                         if kind == ErrorKind::Ask {
+                            // The slot is being migrated, only this request goes to the
+                            // other node and the slot map stays untouched.
                             redirected = err
                                 .redirect_node()
                                 .map(|(node, _slot)| build_connection_string(node, None, self.tls));
-                            is_asking = true;
+                            is_asking = redirected.is_some();
+                            continue;
                         } else if kind == ErrorKind::Moved {
                             // Refresh slots.
                             self.refresh_slots()?;
@@ -474,7 +679,7 @@ impl ClusterConnection {
                             continue;
                         } else if kind == ErrorKind::TryAgain || kind == ErrorKind::ClusterDown {
                             // Sleep and retry.
-                            let sleep_time = 2u64.pow(16 - retries.max(9)) * 10;
+                            let sleep_time = 2u64.pow((self.retries - retries).min(7)) * 10;
                             thread::sleep(Duration::from_millis(sleep_time));
                             excludes.clear();
                             continue;
@@ -485,6 +690,7 @@ impl ClusterConnection {
                             self.readonly,
                             self.username.clone(),
                             self.password.clone(),
+                            self.connection_timeout,
                         )?;
                         {
                             let mut connections = self.connections.borrow_mut();
@@ -522,15 +728,25 @@ impl ClusterConnection {
             return Ok(results);
         }
 
-        // Refresh the slots to ensure that we have a clean slate for the retry attempts.
-        self.refresh_slots()?;
+        // An `ASK` redirect only concerns the command that received it, anything else means that
+        // the cluster topology changed. In that case refresh the slots to ensure that we have a
+        // clean slate for the retry attempts.
+        if to_retry.iter().any(|(_, err)| err.kind() != ErrorKind::Ask) {
+            self.refresh_slots()?;
+        }
 
-        // Given that there are commands that need to be retried, it means something in the cluster
-        // topology changed. Execute each command seperately to take advantage of the existing
-        // retry logic that handles these cases.
-        for retry_idx in to_retry {
+        // Execute each command seperately to take advantage of the existing retry logic that
+        // handles these cases.
+        for (retry_idx, err) in to_retry {
             let cmd = &cmds[retry_idx];
-            results[retry_idx] = self.request(cmd, move |conn| conn.req_command(cmd))?;
+            let asking = match err.kind() {
+                ErrorKind::Ask => err
+                    .redirect_node()
+                    .map(|(node, _slot)| build_connection_string(node, None, self.tls)),
+                _ => None,
+            };
+            results[retry_idx] =
+                self.request_with_redirect(cmd, move |conn| conn.req_command(cmd), asking)?;
         }
         Ok(results)
     }
@@ -592,7 +808,7 @@ impl ClusterConnection {
         &self,
         results: &mut Vec<Value>,
         node_cmds: &[NodeCmd],
-    ) -> RedisResult<Vec<usize>> {
+    ) -> RedisResult<Vec<(usize, RedisError)>> {
         let mut to_retry = Vec::new();
         let mut connections = self.connections.borrow_mut();
         let mut first_err = None;
@@ -604,7 +820,7 @@ impl ClusterConnection {
                     .recv_response()
                 {
                     Ok(item) => results[*cmd_idx] = item,
-                    Err(err) if err.is_cluster_error() => to_retry.push(*cmd_idx),
+                    Err(err) if err.is_cluster_error() => to_retry.push((*cmd_idx, err)),
                     Err(err) => first_err = first_err.or(Some(err)),
                 }
             }
@@ -616,6 +832,150 @@ impl ClusterConnection {
     }
 }
 
+/// Returns the hash slot of `key`, taking hash tags into account.
+pub fn key_slot(key: &[u8]) -> u16 {
+    slot_for_key(key)
+}
+
+/// Returns the part of `key` that is hashed to find its slot: the hash tag
+/// between the first `{` and the following `}`, or the whole key if there is
+/// no such tag or it is empty.
+///
+/// ```rust
+/// use redis::cluster::key_hash_tag;
+///
+/// assert_eq!(key_hash_tag(b"{user:1000}:visits"), &b"user:1000"[..]);
+/// assert_eq!(key_hash_tag(b"{}:visits"), &b"{}:visits"[..]);
+/// ```
+pub fn key_hash_tag(key: &[u8]) -> &[u8] {
+    get_hashtag(key).unwrap_or(key)
+}
+
+/// Returns the hash slot the cluster client routes `cmd` to, or `None` if
+/// the command is not routed by key, e.g. because it is sent to all nodes or
+/// has no key.
+pub fn command_slot(cmd: &Cmd) -> Option<u16> {
+    match RoutingInfo::for_routable(cmd) {
+        Some(RoutingInfo::Slot(slot)) => Some(slot),
+        _ => None,
+    }
+}
+
+/// Returns the key `{prefix}key`, which is stored in the same hash slot as all
+/// other keys built with the same `prefix`.
+///
+/// Commands that combine several keys, like `PFMERGE`, `SUNIONSTORE` or
+/// `ZUNIONSTORE`, fail with `CROSSSLOT` on a cluster unless all of their keys
+/// are in the same slot.  Only the part between the braces is hashed, so
+/// `prefix` must not be empty or contain a `}`.
+///
+/// ```rust
+/// use redis::cluster::{hash_tagged, key_slot};
+///
+/// let visits = hash_tagged("user:1000", ":visits");
+/// let total = hash_tagged("user:1000", ":visits:total");
+/// assert_eq!(visits, "{user:1000}:visits");
+/// assert_eq!(key_slot(visits.as_bytes()), key_slot(total.as_bytes()));
+/// ```
+pub fn hash_tagged(prefix: &str, key: &str) -> String {
+    format!("{{{}}}{}", prefix, key)
+}
+
+// Groups the indexes of `keys` by hash slot, keeping their order within a slot.
+fn group_by_slot<'a>(keys: impl Iterator<Item = &'a [u8]>) -> Vec<Vec<usize>> {
+    let mut groups: Vec<Vec<usize>> = Vec::new();
+    let mut group_for_slot: HashMap<u16, usize> = HashMap::new();
+    for (idx, key) in keys.enumerate() {
+        let group = *group_for_slot.entry(slot_for_key(key)).or_insert_with(|| {
+            groups.push(Vec::new());
+            groups.len() - 1
+        });
+        groups[group].push(idx);
+    }
+    groups
+}
+
+// Groups the indexes of `items` by the hash slot of their key.
+fn group_pairs_by_slot<K: ToRedisArgs, V>(items: &[(K, V)]) -> Vec<Vec<usize>> {
+    let keys: Vec<Vec<u8>> = items
+        .iter()
+        .map(|(key, _)| key.to_redis_args().into_iter().next().unwrap_or_default())
+        .collect();
+    group_by_slot(keys.iter().map(|key| &key[..]))
+}
+
+fn mget_cmd(keys: &[Vec<u8>], indexes: &[usize]) -> Cmd {
+    let mut c = cmd("MGET");
+    for &idx in indexes {
+        c.arg(&keys[idx][..]);
+    }
+    c
+}
+
+fn mget_values(reply: Value, count: usize) -> RedisResult<Vec<Value>> {
+    match reply {
+        Value::Bulk(values) if values.len() == count => Ok(values),
+        reply => fail!((
+            ErrorKind::ResponseError,
+            "MGET returned an unexpected reply",
+            format!("{:?}", reply)
+        )),
+    }
+}
+
+fn mset_cmd<K: ToRedisArgs, V: ToRedisArgs>(items: &[(K, V)], indexes: &[usize]) -> Cmd {
+    let mut c = cmd("MSET");
+    for &idx in indexes {
+        let (key, value) = &items[idx];
+        c.arg(key).arg(value);
+    }
+    c
+}
+
+// The server only reports that the keys of a command hash to different slots.
+// For the commands that combine several keys, point out how to avoid that.
+fn with_hash_tag_hint<R: ?Sized + Routable>(cmd: &R, err: RedisError) -> RedisError {
+    if err.kind() != ErrorKind::CrossSlot {
+        return err;
+    }
+    let combines_keys = match cmd.command() {
+        Some(name) => matches!(
+            &name[..],
+            b"PFCOUNT"
+                | b"PFMERGE"
+                | b"SINTER"
+                | b"SINTERCARD"
+                | b"SINTERSTORE"
+                | b"SUNION"
+                | b"SUNIONSTORE"
+                | b"SDIFF"
+                | b"SDIFFSTORE"
+                | b"ZINTER"
+                | b"ZINTERCARD"
+                | b"ZINTERSTORE"
+                | b"ZUNION"
+                | b"ZUNIONSTORE"
+                | b"ZDIFF"
+                | b"ZDIFFSTORE"
+        ),
+        None => false,
+    };
+    if !combines_keys {
+        return err;
+    }
+    let detail = format!(
+        "{}; give related keys a common hash tag, for example with \
+         redis::cluster::hash_tagged, to store them in the same slot",
+        err.detail()
+            .unwrap_or("Keys in request don't hash to the same slot")
+    );
+    RedisError::from((
+        ErrorKind::CrossSlot,
+        "An error was signalled by the server",
+        detail,
+    ))
+}
+
 trait MergeResults {
     fn merge_results(_values: HashMap<&str, Self>) -> Self
     where
@@ -715,6 +1075,7 @@ fn connect<T: IntoConnectionInfo>(
     readonly: bool,
     username: Option<String>,
     password: Option<String>,
+    timeout: Option<Duration>,
 ) -> RedisResult<Connection>
 where
     T: std::fmt::Debug,
@@ -724,9 +1085,12 @@ where
     connection_info.redis.password = password;
     let client = super::Client::open(connection_info)?;
 
-    let mut con = client.get_connection()?;
+    let mut con = match timeout {
+        Some(timeout) => client.get_connection_with_timeout(timeout)?,
+        None => client.get_connection()?,
+    };
     if readonly {
-        cmd("READONLY").query(&mut con)?;
+        cmd("READONLY").query::<()>(&mut con)?;
     }
     Ok(con)
 }
@@ -788,7 +1152,7 @@ fn get_slots(connection: &mut Connection, tls_mode: Option<TlsMode>) -> RedisRes
                         }
 
                         let ip = if let Value::Data(ref ip) = node[0] {
-                            String::from_utf8_lossy(ip)
+                            std::str::from_utf8(ip).ok()?
                         } else {
                             return None;
                         };
@@ -801,7 +1165,7 @@ fn get_slots(connection: &mut Connection, tls_mode: Option<TlsMode>) -> RedisRes
                         } else {
                             return None;
                         };
-                        Some(build_connection_string(&ip, Some(port), tls_mode))
+                        Some(build_connection_string(ip, Some(port), tls_mode))
                     } else {
                         None
                     }
@@ -821,9 +1185,15 @@ fn get_slots(connection: &mut Connection, tls_mode: Option<TlsMode>) -> RedisRes
 }
 
 fn build_connection_string(host: &str, port: Option<u16>, tls_mode: Option<TlsMode>) -> String {
+    // IPv6 addresses have to be bracketed to separate them from the port.
+    let host = if host.contains(':') {
+        format!("[{}]", host)
+    } else {
+        host.to_string()
+    };
     let host_port = match port {
         Some(port) => format!("{}:{}", host, port),
-        None => host.to_string(),
+        None => host,
     };
     match tls_mode {
         None => format!("redis://{}", host_port),
@@ -833,3 +1203,118 @@ fn build_connection_string(host: &str, port: Option<u16>, tls_mode: Option<TlsMo
         Some(TlsMode::Secure) => format!("rediss://{}", host_port),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use std::io::Write;
+    use std::net::{TcpListener, TcpStream};
+    use std::thread;
+
+    use super::{command_slot, hash_tagged, key_hash_tag, key_slot, with_hash_tag_hint};
+    use crate::cluster::ClusterClient;
+    use crate::{cmd, from_redis_value, ErrorKind, Parser, RedisError, Value};
+
+    // Answers like a node of a cluster whose slots are all served by the
+    // node at `[::1]:<master>`, which has every key set to "value".
+    fn serve_cluster_node(mut socket: TcpStream, master: u16) {
+        let mut parser = Parser::new();
+        while let Ok(Value::Bulk(args)) = parser.parse_value(&socket) {
+            let name: String = from_redis_value(&args[0]).unwrap();
+            let reply = match &name.to_ascii_uppercase()[..] {
+                "PING" => "+PONG\r\n".to_string(),
+                "CLUSTER" => format!(
+                    "*1\r\n*3\r\n:0\r\n:16383\r\n*2\r\n$3\r\n::1\r\n:{}\r\n",
+                    master
+                ),
+                "GET" => "$5\r\nvalue\r\n".to_string(),
+                _ => "+OK\r\n".to_string(),
+            };
+            socket.write_all(reply.as_bytes()).unwrap();
+        }
+    }
+
+    fn spawn_cluster_node(listener: TcpListener, master: u16) {
+        thread::spawn(move || {
+            for socket in listener.incoming() {
+                let socket = socket.unwrap();
+                thread::spawn(move || serve_cluster_node(socket, master));
+            }
+        });
+    }
+
+    #[test]
+    fn test_ipv6_nodes() {
+        let (seed, master) = match (TcpListener::bind("[::1]:0"), TcpListener::bind("[::1]:0")) {
+            (Ok(seed), Ok(master)) => (seed, master),
+            // No IPv6 loopback to test with.
+            _ => return,
+        };
+        let seed_port = seed.local_addr().unwrap().port();
+        let master_port = master.local_addr().unwrap().port();
+        spawn_cluster_node(seed, master_port);
+        spawn_cluster_node(master, master_port);
+
+        // The seed node is reached through the URL, the master through the
+        // address reported by CLUSTER SLOTS.
+        let url = format!("redis+cluster://[::1]:{}", seed_port);
+        let mut con = ClusterClient::from_url(&url)
+            .unwrap()
+            .get_connection()
+            .unwrap();
+        assert_eq!(
+            cmd("GET").arg("key").query(&mut con),
+            Ok("value".to_string())
+        );
+    }
+
+    #[test]
+    fn test_hash_tagged_keys_share_a_slot() {
+        let keys: Vec<String> = ["hll:a", "hll:b", "merged", ""]
+            .iter()
+            .map(|key| hash_tagged("stats", key))
+            .collect();
+        assert_eq!(keys[0], "{stats}hll:a");
+        assert!(keys
+            .iter()
+            .all(|key| key_slot(key.as_bytes()) == key_slot(b"stats")));
+        assert_ne!(key_slot(b"hll:a"), key_slot(b"hll:b"));
+    }
+
+    #[test]
+    fn test_key_hash_tag() {
+        assert_eq!(key_hash_tag(b"{tag}key"), &b"tag"[..]);
+        assert_eq!(key_hash_tag(b"key{tag}{other}"), &b"tag"[..]);
+        assert_eq!(key_hash_tag(b"key"), &b"key"[..]);
+        assert_eq!(key_hash_tag(b"{}key"), &b"{}key"[..]);
+        // The slot of the tag is stable, 8338 as reported by CLUSTER KEYSLOT.
+        assert_eq!(key_slot(b"{tag}key"), key_slot(b"tag"));
+        assert_eq!(key_slot(b"tag"), 8338);
+
+        assert_eq!(command_slot(cmd("GET").arg("{tag}key")), Some(8338));
+        assert_eq!(command_slot(&cmd("FLUSHALL")), None);
+    }
+
+    #[test]
+    fn test_cross_slot_hint() {
+        let err = || -> RedisError {
+            (
+                ErrorKind::CrossSlot,
+                "An error was signalled by the server",
+                "Keys in request don't hash to the same slot".to_string(),
+            )
+                .into()
+        };
+
+        let hinted = with_hash_tag_hint(&cmd("pfmerge"), err());
+        assert_eq!(hinted.kind(), ErrorKind::CrossSlot);
+        assert_eq!(hinted.code(), Some("CROSSSLOT"));
+        assert!(hinted.detail().unwrap().contains("hash_tagged"));
+        let hinted = with_hash_tag_hint(&cmd("ZUNIONSTORE"), err());
+        assert!(hinted.detail().unwrap().contains("hash_tagged"));
+
+        let plain = with_hash_tag_hint(&cmd("MSET"), err());
+        assert_eq!(plain.detail(), err().detail());
+        let other = with_hash_tag_hint(&cmd("PFCOUNT"), (ErrorKind::Moved, "moved").into());
+        assert_eq!(other.kind(), ErrorKind::Moved);
+    }
+}
@@ -0,0 +1,344 @@
+//! Operator tooling for changing the slot layout of a redis cluster.
+//!
+//! [`reshard`] moves hash slots together with their keys from one master to
+//! another, following the migration procedure described in the documentation
+//! of `CLUSTER SETSLOT`.
+//!
+//! # Example
+//! ```rust,no_run
+//! # fn do_something() -> redis::RedisResult<()> {
+//! use redis::cluster::admin::{reshard, ReshardEvent};
+//!
+//! let summary = reshard("redis://127.0.0.1:7000/", "redis://127.0.0.1:7001/", &[42, 43], 100)?
+//!     .on_progress(|event| {
+//!         if let ReshardEvent::SlotFinished { slot, keys } = event {
+//!             println!("moved slot {} with {} keys", slot, keys);
+//!         }
+//!     })
+//!     .run()?;
+//! println!("moved {} keys", summary.keys);
+//! # Ok(()) }
+//! ```
+use std::time::Duration;
+
+use crate::commands::{Commands, SlotState};
+use crate::{
+    cmd, Client, Connection, ConnectionAddr, ConnectionInfo, ErrorKind, IntoConnectionInfo,
+    RedisResult,
+};
+
+/// The default timeout of a single `MIGRATE` batch.
+const DEFAULT_MIGRATE_TIMEOUT: Duration = Duration::from_secs(60);
+
+/// A progress report emitted while resharding.
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum ReshardEvent {
+    /// The migration of a slot has started.
+    SlotStarted {
+        /// The slot being moved.
+        slot: u16,
+    },
+    /// A batch of keys was moved to the target node.
+    KeysMigrated {
+        /// The slot the keys belong to.
+        slot: u16,
+        /// The number of keys in the batch.
+        count: usize,
+    },
+    /// The slot is now served by the target node.  In dry-run mode this is
+    /// emitted without anything having been moved.
+    SlotFinished {
+        /// The slot that was moved.
+        slot: u16,
+        /// The number of keys that were moved, or would be moved in dry-run
+        /// mode.
+        keys: usize,
+    },
+}
+
+/// The outcome of a [`Reshard`] run.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ReshardSummary {
+    /// The number of slots that were moved.
+    pub slots: usize,
+    /// The number of keys that were moved.
+    pub keys: usize,
+}
+
+type ProgressCallback<'a> = Box<dyn FnMut(&ReshardEvent) + 'a>;
+
+/// A planned move of hash slots from one master to another.
+///
+/// Created by [`reshard`] and started with [`Reshard::run`].
+pub struct Reshard<'a> {
+    from: ConnectionInfo,
+    to: ConnectionInfo,
+    slots: Vec<u16>,
+    keys_per_batch: usize,
+    migrate_timeout: Duration,
+    dry_run: bool,
+    on_progress: Option<ProgressCallback<'a>>,
+}
+
+/// Prepares moving `slots` from the master `from_node` to the master
+/// `to_node`, migrating at most `keys_per_batch` keys per `MIGRATE`.
+///
+/// The remaining masters of the cluster are discovered through
+/// `CLUSTER NODES` and informed about the new owner of every slot.  They are
+/// connected to with the credentials of `from_node`.
+pub fn reshard<'a, T: IntoConnectionInfo>(
+    from_node: T,
+    to_node: T,
+    slots: &[u16],
+    keys_per_batch: usize,
+) -> RedisResult<Reshard<'a>> {
+    if keys_per_batch == 0 {
+        fail!((
+            ErrorKind::InvalidClientConfig,
+            "keys_per_batch must be greater than zero"
+        ));
+    }
+    Ok(Reshard {
+        from: from_node.into_connection_info()?,
+        to: to_node.into_connection_info()?,
+        slots: slots.to_vec(),
+        keys_per_batch,
+        migrate_timeout: DEFAULT_MIGRATE_TIMEOUT,
+        dry_run: false,
+        on_progress: None,
+    })
+}
+
+impl<'a> Reshard<'a> {
+    /// Only validates the plan and counts the keys that would be moved,
+    /// without changing the cluster.
+    pub fn dry_run(mut self, dry_run: bool) -> Self {
+        self.dry_run = dry_run;
+        self
+    }
+
+    /// Sets the timeout of a single `MIGRATE` batch.  Defaults to 60 seconds.
+    pub fn migrate_timeout(mut self, timeout: Duration) -> Self {
+        self.migrate_timeout = timeout;
+        self
+    }
+
+    /// Registers a callback which is invoked with every [`ReshardEvent`].
+    pub fn on_progress<F: FnMut(&ReshardEvent) + 'a>(mut self, callback: F) -> Self {
+        self.on_progress = Some(Box::new(callback));
+        self
+    }
+
+    /// Moves the slots one after another.
+    ///
+    /// If an error occurs the slot that was being moved is left in its
+    /// importing/migrating state, just like with `redis-cli --cluster
+    /// reshard`, and can be fixed by running the reshard again or with
+    /// `redis-cli --cluster fix`.
+    pub fn run(mut self) -> RedisResult<ReshardSummary> {
+        let mut source = connect(&self.from)?;
+        let mut target = connect(&self.to)?;
+        let source_id: String = source.cluster_myid()?;
+        let target_id: String = target.cluster_myid()?;
+        if source_id == target_id {
+            fail!((
+                ErrorKind::InvalidClientConfig,
+                "Source and target are the same node"
+            ));
+        }
+        let (host, port) = match &self.to.addr {
+            ConnectionAddr::Tcp(host, port) | ConnectionAddr::TcpTls { host, port, .. } => {
+                (host.clone(), *port)
+            }
+            ConnectionAddr::Unix(_) => fail!((
+                ErrorKind::InvalidClientConfig,
+                "The target node must be reachable over TCP"
+            )),
+        };
+
+        let nodes: String = cmd("CLUSTER").arg("NODES").query(&mut source)?;
+        let nodes = parse_nodes(&nodes);
+        let slots = std::mem::take(&mut self.slots);
+        for &slot in &slots {
+            let owned = nodes
+                .iter()
+                .any(|node| node.id == source_id && node.serves(slot));
+            if !owned {
+                fail!((
+                    ErrorKind::InvalidClientConfig,
+                    "Slot is not served by the source node",
+                    slot.to_string()
+                ));
+            }
+        }
+
+        let mut summary = ReshardSummary::default();
+        if self.dry_run {
+            for slot in slots {
+                self.emit(ReshardEvent::SlotStarted { slot });
+                let keys: usize = source.cluster_countkeysinslot(slot)?;
+                self.emit(ReshardEvent::SlotFinished { slot, keys });
+                summary.slots += 1;
+                summary.keys += keys;
+            }
+            return Ok(summary);
+        }
+
+        let mut others = nodes
+            .iter()
+            .filter(|node| node.is_master() && node.id != source_id && node.id != target_id)
+            .map(|node| connect(&self.node_info(&node.host, node.port)))
+            .collect::<RedisResult<Vec<_>>>()?;
+
+        for slot in slots {
+            self.emit(ReshardEvent::SlotStarted { slot });
+            target.cluster_setslot::<()>(slot, SlotState::Importing(source_id.clone()))?;
+            source.cluster_setslot::<()>(slot, SlotState::Migrating(target_id.clone()))?;
+
+            let mut moved = 0;
+            loop {
+                let keys: Vec<Vec<u8>> = source.cluster_getkeysinslot(slot, self.keys_per_batch)?;
+                if keys.is_empty() {
+                    break;
+                }
+                self.migrate(&mut source, &host, port, &keys)?;
+                moved += keys.len();
+                self.emit(ReshardEvent::KeysMigrated {
+                    slot,
+                    count: keys.len(),
+                });
+            }
+
+            // The target goes first so that it never redirects clients back
+            // to the source, which has already handed the slot over.
+            let owner = SlotState::Node(target_id.clone());
+            target.cluster_setslot::<()>(slot, owner.clone())?;
+            source.cluster_setslot::<()>(slot, owner.clone())?;
+            for con in &mut others {
+                con.cluster_setslot::<()>(slot, owner.clone())?;
+            }
+
+            self.emit(ReshardEvent::SlotFinished { slot, keys: moved });
+            summary.slots += 1;
+            summary.keys += moved;
+        }
+        Ok(summary)
+    }
+
+    fn migrate(
+        &self,
+        source: &mut Connection,
+        host: &str,
+        port: u16,
+        keys: &[Vec<u8>],
+    ) -> RedisResult<()> {
+        let mut migrate = cmd("MIGRATE");
+        migrate
+            .arg(host)
+            .arg(port)
+            .arg("")
+            .arg(0)
+            .arg(self.migrate_timeout.as_millis() as u64);
+        match (&self.to.redis.username, &self.to.redis.password) {
+            (Some(username), Some(password)) => {
+                migrate.arg("AUTH2").arg(username).arg(password);
+            }
+            (None, Some(password)) => {
+                migrate.arg("AUTH").arg(password);
+            }
+            _ => (),
+        }
+        migrate.arg("KEYS").arg(keys).query::<()>(source)
+    }
+
+    fn node_info(&self, host: &str, port: u16) -> ConnectionInfo {
+        let addr = match self.from.addr {
+            ConnectionAddr::TcpTls { insecure, .. } => ConnectionAddr::TcpTls {
+                host: host.to_string(),
+                port,
+                insecure,
+            },
+            _ => ConnectionAddr::Tcp(host.to_string(), port),
+        };
+        ConnectionInfo {
+            addr,
+            ..self.from.clone()
+        }
+    }
+
+    fn emit(&mut self, event: ReshardEvent) {
+        if let Some(callback) = &mut self.on_progress {
+            callback(&event);
+        }
+    }
+}
+
+fn connect(info: &ConnectionInfo) -> RedisResult<Connection> {
+    Client::open(info.clone())?.get_connection()
+}
+
+/// A node as listed by `CLUSTER NODES`.
+struct Node {
+    id: String,
+    host: String,
+    port: u16,
+    flags: Vec<String>,
+    slots: Vec<(u16, u16)>,
+}
+
+impl Node {
+    fn is_master(&self) -> bool {
+        self.flags.iter().any(|flag| flag == "master")
+            && !self
+                .flags
+                .iter()
+                .any(|flag| flag == "fail" || flag == "noaddr" || flag == "handshake")
+    }
+
+    fn serves(&self, slot: u16) -> bool {
+        self.slots
+            .iter()
+            .any(|&(start, end)| start <= slot && slot <= end)
+    }
+}
+
+// Each line has the form
+// `<id> <ip:port@cport[,hostname]> <flags> <master> <ping-sent> <pong-recv> <config-epoch>
+// <link-state> <slot> <slot> ... <slot>`, where a slot is either a single slot, a range or an
+// importing/migrating marker in square brackets.
+fn parse_nodes(nodes: &str) -> Vec<Node> {
+    nodes
+        .lines()
+        .filter_map(|line| {
+            let mut fields = line.split_whitespace();
+            let id = fields.next()?.to_string();
+            let addr = fields.next()?;
+            let addr = addr.split(['@', ',']).next()?;
+            let colon = addr.rfind(':')?;
+            let host = addr[..colon].to_string();
+            let port = addr[colon + 1..].parse().ok()?;
+            let flags = fields.next()?.split(',').map(str::to_string).collect();
+            let slots = fields
+                .skip(5)
+                .filter(|slot| !slot.starts_with('['))
+                .filter_map(|slot| {
+                    let mut bounds = slot.splitn(2, '-');
+                    let start = bounds.next()?.parse().ok()?;
+                    let end = match bounds.next() {
+                        Some(end) => end.parse().ok()?,
+                        None => start,
+                    };
+                    Some((start, end))
+                })
+                .collect();
+            Some(Node {
+                id,
+                host,
+                port,
+                flags,
+                slots,
+            })
+        })
+        .collect()
+}
@@ -0,0 +1,201 @@
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+use crate::types::{ErrorKind, RedisResult};
+
+pub(crate) type BreakerHook = dyn Fn(&str, BreakerState) + Send + Sync;
+
+/// The state of the circuit breaker of a cluster node.
+///
+/// See [`ClusterClientBuilder::circuit_breaker`](crate::cluster::ClusterClientBuilder::circuit_breaker).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum BreakerState {
+    /// Requests are sent to the node.
+    Closed,
+    /// Connecting to the node failed too often.  Requests that must go to
+    /// the node fail without trying to connect until the cooldown is over.
+    Open,
+    /// The cooldown is over.  The next connection attempt either closes the
+    /// breaker or opens it for another cooldown.
+    HalfOpen,
+}
+
+/// The circuit breaker settings of a cluster client.  A failure threshold
+/// of zero disables the breakers.
+#[derive(Clone, Default)]
+pub(crate) struct BreakerConfig {
+    pub(crate) failure_threshold: u32,
+    pub(crate) cooldown: Duration,
+    pub(crate) hook: Option<Arc<BreakerHook>>,
+}
+
+struct Node {
+    failures: u32,
+    opened_at: Option<Instant>,
+    reported: BreakerState,
+}
+
+/// The circuit breakers of all nodes of a cluster connection, by address.
+/// Only nodes with failures since their last success are tracked.
+pub(crate) struct Breakers {
+    config: BreakerConfig,
+    nodes: HashMap<String, Node>,
+}
+
+impl Breakers {
+    pub(crate) fn new(config: BreakerConfig) -> Breakers {
+        Breakers {
+            config,
+            nodes: HashMap::new(),
+        }
+    }
+
+    pub(crate) fn state(&self, addr: &str) -> BreakerState {
+        match self.nodes.get(addr).and_then(|node| node.opened_at) {
+            Some(opened_at) if opened_at.elapsed() < self.config.cooldown => BreakerState::Open,
+            Some(_) => BreakerState::HalfOpen,
+            None => BreakerState::Closed,
+        }
+    }
+
+    /// Returns whether `addr` may be used, i.e. its breaker is not open.
+    pub(crate) fn allow(&mut self, addr: &str) -> bool {
+        let state = self.state(addr);
+        self.report(addr, state);
+        state != BreakerState::Open
+    }
+
+    /// Like [`allow`](Self::allow), but fails if the breaker is open.
+    pub(crate) fn check(&mut self, addr: &str) -> RedisResult<()> {
+        if !self.allow(addr) {
+            fail!((
+                ErrorKind::CircuitOpen,
+                "Circuit breaker is open for node",
+                addr.to_string()
+            ));
+        }
+        Ok(())
+    }
+
+    pub(crate) fn record_success(&mut self, addr: &str) {
+        if let Some(node) = self.nodes.remove(addr) {
+            if node.reported != BreakerState::Closed {
+                self.notify(addr, BreakerState::Closed);
+            }
+        }
+    }
+
+    pub(crate) fn record_failure(&mut self, addr: &str) {
+        let threshold = self.config.failure_threshold;
+        if threshold == 0 {
+            return;
+        }
+        let node = self.nodes.entry(addr.to_string()).or_insert(Node {
+            failures: 0,
+            opened_at: None,
+            reported: BreakerState::Closed,
+        });
+        node.failures = node.failures.saturating_add(1);
+        if node.failures >= threshold {
+            // A failed probe of a half-open breaker opens it again.
+            node.opened_at = Some(Instant::now());
+        }
+        let state = self.state(addr);
+        self.report(addr, state);
+    }
+
+    fn report(&mut self, addr: &str, state: BreakerState) {
+        if let Some(node) = self.nodes.get_mut(addr) {
+            if node.reported != state {
+                node.reported = state;
+                self.notify(addr, state);
+            }
+        }
+    }
+
+    fn notify(&self, addr: &str, state: BreakerState) {
+        if let Some(hook) = &self.config.hook {
+            hook(addr, state);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+
+    type Events = Arc<Mutex<Vec<(String, BreakerState)>>>;
+
+    fn breakers(failure_threshold: u32, cooldown: Duration) -> (Breakers, Events) {
+        let events = Arc::new(Mutex::new(vec![]));
+        let sink = events.clone();
+        let breakers = Breakers::new(BreakerConfig {
+            failure_threshold,
+            cooldown,
+            hook: Some(Arc::new(move |addr: &str, state| {
+                sink.lock().unwrap().push((addr.to_string(), state))
+            })),
+        });
+        (breakers, events)
+    }
+
+    #[test]
+    fn test_breaker_opens_after_consecutive_failures() {
+        let (mut breakers, events) = breakers(3, Duration::from_secs(60));
+        breakers.record_failure("a");
+        breakers.record_failure("a");
+        breakers.record_success("a");
+        breakers.record_failure("a");
+        breakers.record_failure("a");
+        assert_eq!(breakers.state("a"), BreakerState::Closed);
+        assert!(events.lock().unwrap().is_empty());
+
+        breakers.record_failure("a");
+        assert_eq!(breakers.state("a"), BreakerState::Open);
+        assert_eq!(breakers.state("b"), BreakerState::Closed);
+        assert!(!breakers.allow("a"));
+        assert!(breakers.allow("b"));
+        assert_eq!(
+            breakers.check("a").unwrap_err().kind(),
+            ErrorKind::CircuitOpen
+        );
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![("a".to_string(), BreakerState::Open)]
+        );
+    }
+
+    #[test]
+    fn test_breaker_half_open_probe() {
+        let (mut breakers, events) = breakers(1, Duration::from_secs(0));
+        breakers.record_failure("a");
+        assert_eq!(breakers.state("a"), BreakerState::HalfOpen);
+
+        // A failed probe keeps the breaker from closing.
+        assert!(breakers.allow("a"));
+        breakers.record_failure("a");
+        assert!(breakers.allow("a"));
+        breakers.record_success("a");
+        assert_eq!(breakers.state("a"), BreakerState::Closed);
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                ("a".to_string(), BreakerState::HalfOpen),
+                ("a".to_string(), BreakerState::Closed),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_breaker_disabled() {
+        let (mut breakers, events) = breakers(0, Duration::from_secs(60));
+        for _ in 0..10 {
+            breakers.record_failure("a");
+        }
+        assert!(breakers.allow("a"));
+        assert!(events.lock().unwrap().is_empty());
+    }
+}
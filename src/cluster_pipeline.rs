@@ -1,7 +1,7 @@
 use crate::cluster::ClusterConnection;
 use crate::cmd::{cmd, Cmd};
+use crate::pipeline::ReplyRecord;
 use crate::types::{from_redis_value, ErrorKind, FromRedisValue, RedisResult, ToRedisArgs, Value};
-use std::collections::HashSet;
 
 pub(crate) const UNROUTABLE_ERROR: (ErrorKind, &str) = (
     ErrorKind::ClientError,
@@ -44,7 +44,7 @@ fn is_illegal_cmd(cmd: &str) -> bool {
 #[derive(Clone)]
 pub struct ClusterPipeline {
     commands: Vec<Cmd>,
-    ignored_commands: HashSet<usize>,
+    replies: Vec<ReplyRecord>,
 }
 
 /// A cluster pipeline is almost identical to a normal [Pipeline](Pipeline), with two exceptions:
@@ -79,7 +79,7 @@ impl ClusterPipeline {
     pub fn with_capacity(capacity: usize) -> ClusterPipeline {
         ClusterPipeline {
             commands: Vec::with_capacity(capacity),
-            ignored_commands: HashSet::new(),
+            replies: vec![],
         }
     }
 
@@ -103,10 +103,19 @@ impl ClusterPipeline {
     #[inline]
     pub fn query<T: FromRedisValue>(&self, con: &mut ClusterConnection) -> RedisResult<T> {
         for cmd in &self.commands {
-            let cmd_name = std::str::from_utf8(cmd.arg_idx(0).unwrap_or(b""))
-                .unwrap_or("")
-                .trim()
-                .to_ascii_uppercase();
+            cmd.check_args()?;
+            let word = |idx| {
+                std::str::from_utf8(cmd.arg_idx(idx).unwrap_or(b""))
+                    .unwrap_or("")
+                    .trim()
+                    .to_ascii_uppercase()
+            };
+            // Name the subcommand as well if it is the part that is illegal.
+            let mut cmd_name = word(0);
+            let with_subcommand = format!("{} {}", cmd_name, word(1));
+            if is_illegal_cmd(&with_subcommand) {
+                cmd_name = with_subcommand;
+            }
 
             if is_illegal_cmd(&cmd_name) {
                 fail!((
@@ -50,16 +50,19 @@ impl RoutingInfo {
     }
 
     pub fn for_key(key: &[u8]) -> Option<RoutingInfo> {
-        let key = match get_hashtag(key) {
-            Some(tag) => tag,
-            None => key,
-        };
-        Some(RoutingInfo::Slot(
-            crc16::State::<crc16::XMODEM>::calculate(key) % SLOT_SIZE as u16,
-        ))
+        Some(RoutingInfo::Slot(slot_for_key(key)))
     }
 }
 
+/// Returns the hash slot of `key`, honouring hash tags.
+pub(crate) fn slot_for_key(key: &[u8]) -> u16 {
+    let key = match get_hashtag(key) {
+        Some(tag) => tag,
+        None => key,
+    };
+    crc16::State::<crc16::XMODEM>::calculate(key) % SLOT_SIZE as u16
+}
+
 pub(crate) trait Routable {
     // Convenience function to return ascii uppercase version of the
     // the first argument (i.e., the command).
@@ -145,7 +148,7 @@ impl Slot {
     }
 }
 
-fn get_hashtag(key: &[u8]) -> Option<&[u8]> {
+pub(crate) fn get_hashtag(key: &[u8]) -> Option<&[u8]> {
     let open = key.iter().position(|v| *v == b'{');
     let open = match open {
         Some(open) => open,
@@ -5,11 +5,16 @@ use futures_util::{
 };
 #[cfg(feature = "aio")]
 use std::pin::Pin;
-use std::{fmt, io};
+use std::{fmt, io, marker::PhantomData};
+#[cfg(feature = "aio")]
+use tokio::io::{AsyncRead, AsyncWrite};
 
-use crate::connection::ConnectionLike;
+use crate::connection::{Connection, ConnectionLike};
+use crate::parser::Frame;
 use crate::pipeline::Pipeline;
-use crate::types::{from_redis_value, FromRedisValue, RedisResult, RedisWrite, ToRedisArgs};
+use crate::types::{
+    from_redis_value, ErrorKind, FromRedisValue, RedisResult, RedisWrite, ToRedisArgs, Value,
+};
 
 /// An argument to a redis command
 #[derive(Clone)]
@@ -20,19 +25,68 @@ pub enum Arg<D> {
     Cursor,
 }
 
+// How an argument is stored in a `Cmd`.
+#[derive(Clone)]
+enum StoredArg {
+    // The offset into `data` that marks the end of the argument
+    Simple(usize),
+    Cursor,
+    // An argument written with `write_arg_shared`, which is not copied
+    // into `data`
+    #[cfg(feature = "bytes")]
+    Shared(bytes::Bytes),
+}
+
+/// A part of a packed command, see `Cmd::write_packed_segments`.
+#[cfg(feature = "aio")]
+pub(crate) enum PackedSegment<'a> {
+    /// A range of the buffer the command was packed into.
+    Buffered(std::ops::Range<usize>),
+    /// An argument held in a shared buffer.
+    Shared(&'a [u8]),
+}
+
 /// Represents redis commands.
 #[derive(Clone)]
 pub struct Cmd {
     data: Vec<u8>,
-    // Arg::Simple contains the offset that marks the end of the argument
-    args: Vec<Arg<usize>>,
+    args: Vec<StoredArg>,
     cursor: Option<u64>,
+    // Set if an argument the server requires to be non-empty was empty.
+    empty_arg: bool,
+    // Set to the reason if options were combined that the server rejects.
+    invalid_options: Option<&'static str>,
+    // Set if `cmd` split a name with several words into arguments.
+    split_name: bool,
+}
+
+/// How commands named with several words, like `cmd("CONFIG GET")`, are
+/// handled when they are sent.
+///
+/// The server expects every word as its own argument, so [`cmd`] always
+/// splits such names.  The policy of a connection is set with
+/// [`RedisConnectionInfo::command_name_policy`](crate::RedisConnectionInfo::command_name_policy).
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum CommandNamePolicy {
+    /// Sends the words of the name as separate arguments.  This is the
+    /// default.
+    Split,
+    /// Fails with [`ErrorKind::ClientError`] instead of sending the command,
+    /// pointing at the subcommand to pass with [`Cmd::arg`].
+    Reject,
+}
+
+// `#[default]` on enum variants needs a newer Rust than 1.51.
+#[allow(clippy::derivable_impls)]
+impl Default for CommandNamePolicy {
+    fn default() -> Self {
+        CommandNamePolicy::Split
+    }
 }
 
 /// Represents a redis iterator.
 pub struct Iter<'a, T: FromRedisValue> {
     batch: std::vec::IntoIter<T>,
-    cursor: u64,
     con: &'a mut (dyn ConnectionLike + 'a),
     cmd: Cmd,
 }
@@ -50,18 +104,14 @@ impl<'a, T: FromRedisValue> Iterator for Iter<'a, T> {
             if let Some(v) = self.batch.next() {
                 return Some(v);
             };
-            if self.cursor == 0 {
+            if matches!(self.cmd.cursor(), None | Some(0)) {
                 return None;
             }
 
-            let pcmd = unwrap_or!(
-                self.cmd.get_packed_command_with_cursor(self.cursor),
-                return None
-            );
-            let rv = unwrap_or!(self.con.req_packed_command(&pcmd).ok(), return None);
+            let rv = unwrap_or!(self.con.req_command(&self.cmd).ok(), return None);
             let (cur, batch): (u64, Vec<T>) = unwrap_or!(from_redis_value(&rv).ok(), return None);
 
-            self.cursor = cur;
+            self.cmd.set_cursor(cur);
             self.batch = batch.into_iter();
         }
     }
@@ -104,11 +154,7 @@ impl<'a, T: FromRedisValue + 'a> AsyncIter<'a, T> {
             if let Some(v) = self.batch.next() {
                 return Some(v);
             };
-            if let Some(cursor) = self.cmd.cursor {
-                if cursor == 0 {
-                    return None;
-                }
-            } else {
+            if matches!(self.cmd.cursor(), None | Some(0)) {
                 return None;
             }
 
@@ -118,7 +164,7 @@ impl<'a, T: FromRedisValue + 'a> AsyncIter<'a, T> {
             );
             let (cur, batch): (u64, Vec<T>) = unwrap_or!(from_redis_value(&rv).ok(), return None);
 
-            self.cmd.cursor = Some(cur);
+            self.cmd.set_cursor(cur);
             self.batch = batch.into_iter();
         }
     }
@@ -135,6 +181,142 @@ impl<'a, T: FromRedisValue + Unpin + 'a> Stream for AsyncIter<'a, T> {
     }
 }
 
+/// An iterator over the elements of an array reply which are decoded one
+/// at a time while iterating, see `Cmd::query_streaming`.
+///
+/// The connection can not be used for anything else while the iterator is
+/// alive.  When the iterator is dropped before it is exhausted, the elements
+/// that were not read yet are read and discarded so that the connection can
+/// be used again.  If the connection fails while reading the reply, it is
+/// closed, as the position of the next reply is unknown.
+pub struct StreamingIter<'a, T: FromRedisValue> {
+    con: &'a mut Connection,
+    remaining: usize,
+    marker: PhantomData<fn() -> T>,
+}
+
+impl<'a, T: FromRedisValue> Iterator for StreamingIter<'a, T> {
+    type Item = RedisResult<T>;
+
+    fn next(&mut self) -> Option<RedisResult<T>> {
+        if self.remaining == 0 {
+            return None;
+        }
+        match self.con.read_frame(false) {
+            Ok(frame) => {
+                self.remaining -= 1;
+                Some(frame.into_value().and_then(|v| from_redis_value(&v)))
+            }
+            Err(err) => {
+                self.remaining = 0;
+                self.con.shutdown();
+                Some(Err(err))
+            }
+        }
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+impl<'a, T: FromRedisValue> ExactSizeIterator for StreamingIter<'a, T> {}
+
+impl<'a, T: FromRedisValue> Drop for StreamingIter<'a, T> {
+    fn drop(&mut self) {
+        while self.remaining > 0 {
+            if self.con.read_frame(false).is_err() {
+                self.con.shutdown();
+                return;
+            }
+            self.remaining -= 1;
+        }
+    }
+}
+
+/// A stream over the elements of an array reply which are decoded one at a
+/// time, see `Cmd::query_streaming_async`.
+///
+/// The connection can not be used for anything else while the stream is
+/// alive.  When the stream is dropped before it is exhausted, the elements
+/// that were not read yet are read and discarded before the next request is
+/// sent on the connection.  If the connection fails while reading the reply,
+/// the stream ends with the error and the connection should be discarded.
+#[cfg(feature = "aio")]
+pub struct AsyncStreamingIter<'a, T: FromRedisValue, C> {
+    con: &'a mut crate::aio::Connection<C>,
+    remaining: usize,
+    marker: PhantomData<fn() -> T>,
+}
+
+#[cfg(feature = "aio")]
+impl<'a, T, C> AsyncStreamingIter<'a, T, C>
+where
+    T: FromRedisValue,
+    C: Unpin + AsyncRead + AsyncWrite + Send,
+{
+    /// Reads and converts the next element of the reply.
+    pub async fn next_item(&mut self) -> Option<RedisResult<T>> {
+        if self.remaining == 0 {
+            return None;
+        }
+        match self.con.read_frame(false).await {
+            Ok(frame) => {
+                self.remaining -= 1;
+                Some(frame.into_value().and_then(|v| from_redis_value(&v)))
+            }
+            Err(err) => {
+                self.remaining = 0;
+                Some(Err(err))
+            }
+        }
+    }
+}
+
+// The decoder of the connection keeps a partially read element, so a read
+// which is dropped while pending continues with the next poll.
+#[cfg(feature = "aio")]
+impl<'a, T, C> Stream for AsyncStreamingIter<'a, T, C>
+where
+    T: FromRedisValue,
+    C: Unpin + AsyncRead + AsyncWrite + Send,
+{
+    type Item = RedisResult<T>;
+
+    fn poll_next(self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<Option<RedisResult<T>>> {
+        let this = self.get_mut();
+        let mut future = Box::pin(this.next_item());
+        future.poll_unpin(cx)
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.remaining, Some(self.remaining))
+    }
+}
+
+#[cfg(feature = "aio")]
+impl<'a, T: FromRedisValue, C> Drop for AsyncStreamingIter<'a, T, C> {
+    fn drop(&mut self) {
+        self.con.leave_unread(self.remaining);
+    }
+}
+
+/// Returns the number of elements of a reply whose header was read with
+/// `read_frame`, treating nil like an empty array.
+fn streamed_len(frame: Frame) -> RedisResult<usize> {
+    match frame {
+        Frame::Array(len) => Ok(len),
+        Frame::Value(Ok(Value::Nil)) => Ok(0),
+        Frame::Value(Ok(v)) => Err((
+            ErrorKind::TypeError,
+            "Response was of incompatible type",
+            format!("{:?} (response was {:?})", "Response is not an array", v),
+        )
+            .into()),
+        Frame::Value(Err(err)) => Err(err),
+    }
+}
+
 fn countdigits(mut v: usize) -> usize {
     let mut result = 1;
     loop {
@@ -202,6 +384,22 @@ where
 fn write_command<'a, I>(cmd: &mut (impl ?Sized + io::Write), args: I, cursor: u64) -> io::Result<()>
 where
     I: IntoIterator<Item = Arg<&'a [u8]>> + Clone + ExactSizeIterator,
+{
+    write_command_with(cmd, args, cursor, |cmd, _, bytes| cmd.write_all(bytes))
+}
+
+// Writes the command like `write_command`, except that the bytes of each
+// argument are written by `write_arg`, which gets the index of the argument.
+fn write_command_with<'a, W, I, F>(
+    cmd: &mut W,
+    args: I,
+    cursor: u64,
+    mut write_arg: F,
+) -> io::Result<()>
+where
+    W: ?Sized + io::Write,
+    I: IntoIterator<Item = Arg<&'a [u8]>> + ExactSizeIterator,
+    F: FnMut(&mut W, usize, &[u8]) -> io::Result<()>,
 {
     let mut buf = ::itoa::Buffer::new();
 
@@ -211,7 +409,7 @@ where
     cmd.write_all(b"\r\n")?;
 
     let mut cursor_bytes = itoa::Buffer::new();
-    for item in args {
+    for (idx, item) in args.into_iter().enumerate() {
         let bytes = match item {
             Arg::Cursor => cursor_bytes.format(cursor).as_bytes(),
             Arg::Simple(val) => val,
@@ -222,7 +420,7 @@ where
         cmd.write_all(s.as_bytes())?;
         cmd.write_all(b"\r\n")?;
 
-        cmd.write_all(bytes)?;
+        write_arg(cmd, idx, bytes)?;
         cmd.write_all(b"\r\n")?;
     }
     Ok(())
@@ -231,13 +429,23 @@ where
 impl RedisWrite for Cmd {
     fn write_arg(&mut self, arg: &[u8]) {
         self.data.extend_from_slice(arg);
-        self.args.push(Arg::Simple(self.data.len()));
+        self.args.push(StoredArg::Simple(self.data.len()));
     }
 
     fn write_arg_fmt(&mut self, arg: impl fmt::Display) {
         use std::io::Write;
         write!(self.data, "{}", arg).unwrap();
-        self.args.push(Arg::Simple(self.data.len()));
+        self.args.push(StoredArg::Simple(self.data.len()));
+    }
+
+    fn reserve(&mut self, bytes: usize, args: usize) {
+        self.data.reserve(bytes);
+        self.args.reserve(args);
+    }
+
+    #[cfg(feature = "bytes")]
+    fn write_arg_shared(&mut self, arg: bytes::Bytes) {
+        self.args.push(StoredArg::Shared(arg));
     }
 }
 
@@ -281,6 +489,9 @@ impl Cmd {
             data: vec![],
             args: vec![],
             cursor: None,
+            empty_arg: false,
+            invalid_options: None,
+            split_name: false,
         }
     }
 
@@ -303,6 +514,88 @@ impl Cmd {
         self
     }
 
+    /// Like `arg` but remembers if `arg` did not expand to any argument at
+    /// all, in which case the command fails before it is sent.  Used for the
+    /// arguments the server requires at least one value for, like the
+    /// elements of `LPUSH`.
+    #[inline]
+    pub(crate) fn arg_nonempty<T: ToRedisArgs>(&mut self, arg: T) -> &mut Cmd {
+        let len = self.args.len();
+        arg.write_redis_args(self);
+        self.empty_arg |= self.args.len() == len;
+        self
+    }
+
+    /// Sends the command even if a list of values the server requires to be
+    /// non-empty, like the elements of [`lpush`](Cmd::lpush), is empty.
+    ///
+    /// By default such commands fail with [`ErrorKind::ClientError`]
+    /// without a round trip to the server.
+    #[inline]
+    pub fn allow_empty_args(&mut self) -> &mut Cmd {
+        self.empty_arg = false;
+        self
+    }
+
+    // Returns the database a `SELECT` command switches to, so that
+    // connections can keep track of it.
+    pub(crate) fn selected_db(&self) -> Option<i64> {
+        let mut args = self.args_iter();
+        match (args.next(), args.next(), args.next()) {
+            (Some(Arg::Simple(name)), Some(Arg::Simple(db)), None)
+                if name.eq_ignore_ascii_case(b"SELECT") =>
+            {
+                std::str::from_utf8(db).ok()?.parse().ok()
+            }
+            _ => None,
+        }
+    }
+
+    // Makes `check_args` fail with `problem`, if any, before the command is
+    // sent.  Used for options the server refuses to combine.
+    pub(crate) fn reject_options(&mut self, problem: Option<&'static str>) -> &mut Cmd {
+        self.invalid_options = self.invalid_options.or(problem);
+        self
+    }
+
+    pub(crate) fn check_args(&self) -> RedisResult<()> {
+        let name = || match self.args_iter().next() {
+            Some(Arg::Simple(name)) => String::from_utf8_lossy(name),
+            _ => "command".into(),
+        };
+        if self.empty_arg {
+            fail!((
+                ErrorKind::ClientError,
+                "Empty argument list",
+                format!("{} requires at least one value", name())
+            ));
+        }
+        if let Some(problem) = self.invalid_options {
+            fail!((
+                ErrorKind::ClientError,
+                "Incompatible options",
+                format!("{}: {}", name(), problem)
+            ));
+        }
+        Ok(())
+    }
+
+    // Fails if the name was split by `cmd` and `policy` rejects that.
+    pub(crate) fn check_name(&self, policy: CommandNamePolicy) -> RedisResult<()> {
+        if self.split_name && policy == CommandNamePolicy::Reject {
+            let word = |idx| match self.args_iter().nth(idx) {
+                Some(Arg::Simple(word)) => String::from_utf8_lossy(word).into_owned(),
+                _ => String::new(),
+            };
+            fail!((
+                ErrorKind::ClientError,
+                "Command name with several words",
+                format!("use cmd(\"{}\").arg(\"{}\") instead", word(0), word(1))
+            ));
+        }
+        Ok(())
+    }
+
     /// Works similar to `arg` but adds a cursor argument.  This is always
     /// an integer and also flips the command implementation to support a
     /// different mode for the iterators where the iterator will ask for
@@ -322,7 +615,7 @@ impl Cmd {
     pub fn cursor_arg(&mut self, cursor: u64) -> &mut Cmd {
         assert!(!self.in_scan_mode());
         self.cursor = Some(cursor);
-        self.args.push(Arg::Cursor);
+        self.args.push(StoredArg::Cursor);
         self
     }
 
@@ -342,15 +635,38 @@ impl Cmd {
         write_command(cmd, self.args_iter(), self.cursor.unwrap_or(0)).unwrap()
     }
 
-    /// Like `get_packed_command` but replaces the cursor with the
-    /// provided value.  If the command is not in scan mode, `None`
-    /// is returned.
-    #[inline]
-    fn get_packed_command_with_cursor(&self, cursor: u64) -> Option<Vec<u8>> {
-        if !self.in_scan_mode() {
-            None
-        } else {
-            Some(encode_command(self.args_iter(), cursor))
+    /// Packs the command into `buf` like `write_packed_command`, except for
+    /// the arguments held in shared buffers, which are left where they are.
+    /// Returns the segments which make up the packed command, in order, for
+    /// a vectored write.
+    #[cfg(feature = "aio")]
+    pub(crate) fn write_packed_segments(&self, buf: &mut Vec<u8>) -> Vec<PackedSegment<'_>> {
+        let mut segments = vec![];
+        let mut start = buf.len();
+        let args = self.args_iter();
+        write_command_with(buf, args, self.cursor.unwrap_or(0), |buf, idx, bytes| {
+            match self.shared_arg(idx) {
+                Some(shared) => {
+                    segments.push(PackedSegment::Buffered(start..buf.len()));
+                    segments.push(PackedSegment::Shared(shared));
+                    start = buf.len();
+                }
+                None => buf.extend_from_slice(bytes),
+            }
+            Ok(())
+        })
+        .unwrap();
+        segments.push(PackedSegment::Buffered(start..buf.len()));
+        segments
+    }
+
+    // Returns the argument at `idx` if it is held in a shared buffer.
+    #[cfg(feature = "aio")]
+    fn shared_arg(&self, idx: usize) -> Option<&[u8]> {
+        match self.args[idx] {
+            StoredArg::Simple(_) | StoredArg::Cursor => None,
+            #[cfg(feature = "bytes")]
+            StoredArg::Shared(ref bytes) => Some(bytes),
         }
     }
 
@@ -360,11 +676,44 @@ impl Cmd {
         self.cursor.is_some()
     }
 
+    /// Returns the value of the cursor argument, or `None` if the command
+    /// is not in scan mode.
+    #[inline]
+    pub fn cursor(&self) -> Option<u64> {
+        self.cursor
+    }
+
+    /// Sets the value of the cursor argument, e.g. to continue a `SCAN`
+    /// from the cursor of a previous reply.  The cursor is kept when the
+    /// command is cloned.
+    ///
+    /// ```rust,no_run
+    /// # let client = redis::Client::open("redis://127.0.0.1/").unwrap();
+    /// # let mut con = client.get_connection().unwrap();
+    /// let mut cmd = redis::cmd("SSCAN");
+    /// cmd.arg("my_set").cursor_arg(0);
+    /// let (cursor, first): (u64, Vec<isize>) = cmd.query(&mut con).unwrap();
+    /// cmd.set_cursor(cursor);
+    /// let rest: Vec<isize> = cmd.iter(&mut con).unwrap().collect();
+    /// ```
+    ///
+    /// # Panics
+    ///
+    /// Panics if the command is not in scan mode, see
+    /// [`cursor_arg`](Self::cursor_arg).
+    #[inline]
+    pub fn set_cursor(&mut self, cursor: u64) {
+        assert!(self.in_scan_mode());
+        self.cursor = Some(cursor);
+    }
+
     /// Sends the command as query to the connection and converts the
     /// result to the target redis value.  This is the general way how
     /// you can retrieve data.
     #[inline]
     pub fn query<T: FromRedisValue>(&self, con: &mut dyn ConnectionLike) -> RedisResult<T> {
+        self.check_args()?;
+        self.check_name(con.command_name_policy())?;
         match con.req_command(self) {
             Ok(val) => from_redis_value(&val),
             Err(e) => Err(e),
@@ -378,10 +727,67 @@ impl Cmd {
     where
         C: crate::aio::ConnectionLike,
     {
+        self.check_args()?;
+        self.check_name(con.command_name_policy())?;
         let val = con.req_packed_command(self).await?;
         from_redis_value(&val)
     }
 
+    /// Similar to `query()` but decodes the elements of an array reply one at
+    /// a time while they are iterated, instead of decoding the whole reply
+    /// before converting it.  This keeps the memory used for huge replies,
+    /// such as `SMEMBERS` of a set with millions of members, to a single
+    /// element.  A nil reply yields no elements.
+    ///
+    /// See [`StreamingIter`] for what happens to the connection when the
+    /// iterator is dropped early.
+    ///
+    /// ```rust,no_run
+    /// # let client = redis::Client::open("redis://127.0.0.1/").unwrap();
+    /// # let mut con = client.get_connection().unwrap();
+    /// let mut total = 0;
+    /// for member in redis::cmd("SMEMBERS").arg("my_set").query_streaming::<u64>(&mut con).unwrap() {
+    ///     total += member.unwrap();
+    /// }
+    /// ```
+    pub fn query_streaming<'a, T: FromRedisValue>(
+        &self,
+        con: &'a mut Connection,
+    ) -> RedisResult<StreamingIter<'a, T>> {
+        self.check_args()?;
+        self.check_name(con.command_name_policy())?;
+        let remaining = streamed_len(con.req_streaming(&self.get_packed_command())?)?;
+        Ok(StreamingIter {
+            con,
+            remaining,
+            marker: PhantomData,
+        })
+    }
+
+    /// Async version of `query_streaming`, returning a stream over the
+    /// elements of the reply.
+    ///
+    /// See [`AsyncStreamingIter`] for what happens to the connection when the
+    /// stream is dropped early.
+    #[cfg(feature = "aio")]
+    pub async fn query_streaming_async<'a, T, C>(
+        &self,
+        con: &'a mut crate::aio::Connection<C>,
+    ) -> RedisResult<AsyncStreamingIter<'a, T, C>>
+    where
+        T: FromRedisValue,
+        C: Unpin + AsyncRead + AsyncWrite + Send,
+    {
+        self.check_args()?;
+        self.check_name(con.command_name_policy())?;
+        let remaining = streamed_len(con.req_streaming(self).await?)?;
+        Ok(AsyncStreamingIter {
+            con,
+            remaining,
+            marker: PhantomData,
+        })
+    }
+
     /// Similar to `query()` but returns an iterator over the items of the
     /// bulk result or iterator.  In normal mode this is not in any way more
     /// efficient than just querying into a `Vec<T>` as it's internally
@@ -397,7 +803,12 @@ impl Cmd {
     /// format of `KEYS` (just a list) as well as `SSCAN` (which returns a
     /// tuple of cursor and list).
     #[inline]
-    pub fn iter<T: FromRedisValue>(self, con: &mut dyn ConnectionLike) -> RedisResult<Iter<'_, T>> {
+    pub fn iter<T: FromRedisValue>(
+        mut self,
+        con: &mut dyn ConnectionLike,
+    ) -> RedisResult<Iter<'_, T>> {
+        self.check_args()?;
+        self.check_name(con.command_name_policy())?;
         let rv = con.req_command(&self)?;
 
         let (cursor, batch) = if rv.looks_like_cursor() {
@@ -405,10 +816,12 @@ impl Cmd {
         } else {
             (0, from_redis_value(&rv)?)
         };
+        if self.in_scan_mode() {
+            self.set_cursor(cursor);
+        }
 
         Ok(Iter {
             batch: batch.into_iter(),
-            cursor,
             con,
             cmd: self,
         })
@@ -435,6 +848,8 @@ impl Cmd {
         mut self,
         con: &'a mut (dyn AsyncConnection + Send),
     ) -> RedisResult<AsyncIter<'a, T>> {
+        self.check_args()?;
+        self.check_name(con.command_name_policy())?;
         let rv = con.req_packed_command(&self).await?;
 
         let (cursor, batch) = if rv.looks_like_cursor() {
@@ -442,10 +857,8 @@ impl Cmd {
         } else {
             (0, from_redis_value(&rv)?)
         };
-        if cursor == 0 {
-            self.cursor = None;
-        } else {
-            self.cursor = Some(cursor);
+        if self.in_scan_mode() {
+            self.set_cursor(cursor);
         }
 
         Ok(AsyncIter {
@@ -476,39 +889,26 @@ impl Cmd {
     pub fn args_iter(&self) -> impl Iterator<Item = Arg<&[u8]>> + Clone + ExactSizeIterator {
         let mut prev = 0;
         self.args.iter().map(move |arg| match *arg {
-            Arg::Simple(i) => {
+            StoredArg::Simple(i) => {
                 let arg = Arg::Simple(&self.data[prev..i]);
                 prev = i;
                 arg
             }
 
-            Arg::Cursor => Arg::Cursor,
+            StoredArg::Cursor => Arg::Cursor,
+
+            #[cfg(feature = "bytes")]
+            StoredArg::Shared(ref bytes) => Arg::Simple(&bytes[..]),
         })
     }
 
     // Get a reference to the argument at `idx`
     #[cfg(feature = "cluster")]
     pub(crate) fn arg_idx(&self, idx: usize) -> Option<&[u8]> {
-        if idx >= self.args.len() {
-            return None;
-        }
-
-        let start = if idx == 0 {
-            0
-        } else {
-            match self.args[idx - 1] {
-                Arg::Simple(n) => n,
-                _ => 0,
-            }
-        };
-        let end = match self.args[idx] {
-            Arg::Simple(n) => n,
-            _ => 0,
-        };
-        if start == 0 && end == 0 {
-            return None;
+        match self.args_iter().nth(idx) {
+            Some(Arg::Simple(arg)) => Some(arg),
+            _ => None,
         }
-        Some(&self.data[start..end])
     }
 }
 
@@ -521,9 +921,33 @@ impl Cmd {
 /// ```rust
 /// redis::cmd("PING");
 /// ```
+///
+/// Commands with subcommands may be named with both words.  The name is
+/// split at ASCII whitespace, as the server expects every word as its own
+/// argument and would otherwise fail with an unknown command error:
+///
+/// ```rust
+/// assert_eq!(
+///     redis::cmd("CONFIG GET").arg("maxmemory").get_packed_command(),
+///     redis::cmd("CONFIG").arg("GET").arg("maxmemory").get_packed_command(),
+/// );
+/// ```
+///
+/// Connections with the [`CommandNamePolicy::Reject`] policy refuse to send
+/// such commands instead.
 pub fn cmd(name: &str) -> Cmd {
     let mut rv = Cmd::new();
-    rv.arg(name);
+    if name.contains(|c: char| c.is_ascii_whitespace()) {
+        let words = name
+            .split(|c: char| c.is_ascii_whitespace())
+            .filter(|word| !word.is_empty());
+        for word in words {
+            rv.arg(word);
+        }
+        rv.split_name = rv.args.len() > 1;
+    } else {
+        rv.arg(name);
+    }
     rv
 }
 
@@ -553,10 +977,243 @@ pub fn pipe() -> Pipeline {
 }
 
 #[cfg(test)]
-#[cfg(feature = "cluster")]
 mod tests {
-    use super::Cmd;
+    use super::{Cmd, CommandNamePolicy};
+    use crate::types::ErrorKind;
+    use crate::ConnectionLike;
+
+    #[cfg(feature = "aio")]
+    #[test]
+    fn test_shared_args_are_not_copied() {
+        use super::PackedSegment;
+
+        let value = bytes::Bytes::from(vec![b'x'; 1024]);
+        let mut shared = Cmd::new();
+        shared.arg("SET").arg("key").arg(value.clone()).arg("NX");
+        let mut copied = Cmd::new();
+        copied.arg("SET").arg("key").arg(&value[..]).arg("NX");
+        assert_eq!(shared.get_packed_command(), copied.get_packed_command());
+
+        let mut buf = vec![];
+        let segments = shared.write_packed_segments(&mut buf);
+        assert_eq!(segments.len(), 3);
+        match &segments[1] {
+            PackedSegment::Shared(bytes) => assert_eq!(bytes.as_ptr(), value.as_ptr()),
+            PackedSegment::Buffered(_) => panic!("the value was copied"),
+        }
+        let mut packed = vec![];
+        for segment in &segments {
+            match segment {
+                PackedSegment::Buffered(range) => packed.extend_from_slice(&buf[range.clone()]),
+                PackedSegment::Shared(bytes) => packed.extend_from_slice(bytes),
+            }
+        }
+        assert_eq!(packed, copied.get_packed_command());
+        assert!(buf.len() < value.len());
+    }
+
+    #[test]
+    fn test_two_word_command_names_are_split() {
+        let split = crate::cmd("CONFIG")
+            .arg("GET")
+            .arg("maxmemory")
+            .get_packed_command();
+        assert_eq!(
+            crate::cmd("CONFIG GET")
+                .arg("maxmemory")
+                .get_packed_command(),
+            split
+        );
+        assert_eq!(
+            crate::cmd(" CONFIG  GET ")
+                .arg("maxmemory")
+                .get_packed_command(),
+            split
+        );
+        assert_eq!(
+            crate::cmd("CONFIG\tGET")
+                .arg("maxmemory")
+                .get_packed_command(),
+            split
+        );
+        assert_eq!(Cmd::config_get("maxmemory").get_packed_command(), split);
+        // Only the name is split, arguments are sent as they are.
+        assert_eq!(
+            crate::cmd("SET")
+                .arg("a key")
+                .arg("a value")
+                .args_iter()
+                .count(),
+            3
+        );
+    }
+
+    #[test]
+    fn test_reject_policy_refuses_split_names() {
+        let err = crate::cmd("CONFIG GET")
+            .arg("maxmemory")
+            .check_name(CommandNamePolicy::Reject)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ClientError);
+        assert!(crate::cmd("CONFIG GET")
+            .check_name(CommandNamePolicy::Split)
+            .is_ok());
+        assert!(Cmd::config_get("maxmemory")
+            .check_name(CommandNamePolicy::Reject)
+            .is_ok());
+        assert!(crate::cmd("GET")
+            .check_name(CommandNamePolicy::Reject)
+            .is_ok());
+    }
+
+    #[test]
+    fn test_cursor_survives_clone() {
+        let mut cmd = crate::cmd("SCAN");
+        cmd.cursor_arg(0).arg("COUNT").arg(10);
+        assert_eq!(cmd.cursor(), Some(0));
+        cmd.set_cursor(42);
+
+        let copy = cmd.clone();
+        assert_eq!(copy.cursor(), Some(42));
+        assert_eq!(
+            copy.get_packed_command(),
+            crate::cmd("SCAN")
+                .arg(42)
+                .arg("COUNT")
+                .arg(10)
+                .get_packed_command()
+        );
+        assert_eq!(crate::cmd("GET").cursor(), None);
+    }
+
+    /// Accepts a single connection, answers the first command with `reply`
+    /// and every further one with `+PONG`.  With `close` set the socket is
+    /// closed right after the reply instead.
+    fn streaming_server(reply: &'static [u8], close: bool) -> crate::Connection {
+        use std::io::{Read, Write};
 
+        let listener = std::net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        std::thread::spawn(move || {
+            let (mut socket, _) = listener.accept().unwrap();
+            let mut reply = reply;
+            let mut buf = [0; 512];
+            while socket.read(&mut buf).unwrap_or(0) > 0 {
+                socket.write_all(reply).unwrap();
+                if close {
+                    return;
+                }
+                reply = b"+PONG\r\n";
+            }
+        });
+        crate::Client::open(format!("redis://127.0.0.1:{}/", port))
+            .unwrap()
+            .get_connection()
+            .unwrap()
+    }
+
+    #[test]
+    fn test_query_streaming() {
+        let mut con = streaming_server(b"*3\r\n$1\r\n1\r\n$1\r\nx\r\n:3\r\n", false);
+        let items: Vec<_> = crate::cmd("SMEMBERS")
+            .arg("set")
+            .query_streaming::<i64>(&mut con)
+            .unwrap()
+            .map(|item| item.ok())
+            .collect();
+        assert_eq!(items, vec![Some(1), None, Some(3)]);
+        assert_eq!(crate::cmd("PING").query(&mut con), Ok("PONG".to_string()));
+
+        let mut con = streaming_server(b"*-1\r\n", false);
+        let mut iter = crate::cmd("LRANGE")
+            .query_streaming::<i64>(&mut con)
+            .unwrap();
+        assert!(iter.next().is_none());
+
+        let mut con = streaming_server(b"-WRONGTYPE not a set\r\n", false);
+        let err = crate::cmd("SMEMBERS")
+            .query_streaming::<i64>(&mut con)
+            .err()
+            .unwrap();
+        assert_eq!(err.code(), Some("WRONGTYPE"));
+        assert_eq!(crate::cmd("PING").query(&mut con), Ok("PONG".to_string()));
+    }
+
+    #[test]
+    fn test_query_streaming_drop_drains() {
+        let mut con = streaming_server(b"*3\r\n:1\r\n*2\r\n:2\r\n:3\r\n:4\r\n", false);
+        let mut iter = crate::cmd("SMEMBERS")
+            .query_streaming::<i64>(&mut con)
+            .unwrap();
+        assert_eq!(iter.size_hint(), (3, Some(3)));
+        assert_eq!(iter.next(), Some(Ok(1)));
+        drop(iter);
+        assert!(con.is_open());
+        assert_eq!(crate::cmd("PING").query(&mut con), Ok("PONG".to_string()));
+    }
+
+    #[test]
+    fn test_query_streaming_drop_kills() {
+        let mut con = streaming_server(b"*3\r\n:1\r\n:2\r\n", true);
+        let mut iter = crate::cmd("SMEMBERS")
+            .query_streaming::<i64>(&mut con)
+            .unwrap();
+        assert_eq!(iter.next(), Some(Ok(1)));
+        drop(iter);
+        assert!(!con.is_open());
+
+        let mut con = streaming_server(b"*2\r\n:1\r\n", true);
+        let items: Vec<_> = crate::cmd("SMEMBERS")
+            .query_streaming::<i64>(&mut con)
+            .unwrap()
+            .collect();
+        assert_eq!(items.len(), 2);
+        assert!(items[1].is_err());
+        assert!(!con.is_open());
+    }
+
+    #[cfg(feature = "tokio-comp")]
+    #[test]
+    fn test_query_streaming_async() {
+        use futures_util::StreamExt;
+
+        let runtime = tokio::runtime::Builder::new_current_thread()
+            .enable_io()
+            .build()
+            .unwrap();
+        runtime.block_on(async {
+            let listener = tokio::net::TcpListener::bind("127.0.0.1:0").await.unwrap();
+            let port = listener.local_addr().unwrap().port();
+            let server = tokio::spawn(async move {
+                use tokio::io::{AsyncReadExt, AsyncWriteExt};
+
+                let (mut socket, _) = listener.accept().await.unwrap();
+                let mut buf = [0; 512];
+                let mut reply = &b"*3\r\n:1\r\n*2\r\n:2\r\n:3\r\n:4\r\n"[..];
+                while socket.read(&mut buf).await.unwrap_or(0) > 0 {
+                    socket.write_all(reply).await.unwrap();
+                    reply = b"+PONG\r\n";
+                }
+            });
+            let client = crate::Client::open(format!("redis://127.0.0.1:{}/", port)).unwrap();
+            let mut con = client.get_async_connection().await.unwrap();
+
+            let mut stream = crate::cmd("SMEMBERS")
+                .query_streaming_async::<i64, _>(&mut con)
+                .await
+                .unwrap();
+            assert_eq!(stream.next().await, Some(Ok(1)));
+            assert!(stream.next().await.unwrap().is_err());
+            drop(stream);
+            let pong: String = crate::cmd("PING").query_async(&mut con).await.unwrap();
+            assert_eq!(pong, "PONG");
+
+            drop(con);
+            server.await.unwrap();
+        });
+    }
+
+    #[cfg(feature = "cluster")]
     #[test]
     fn test_cmd_arg_idx() {
         let mut c = Cmd::new();
@@ -3,11 +3,16 @@
 use crate::cmd::{cmd, Cmd, Iter};
 use crate::connection::{Connection, ConnectionLike, Msg};
 use crate::pipeline::Pipeline;
-use crate::types::{FromRedisValue, NumericBehavior, RedisResult, ToRedisArgs, RedisWrite, Expiry};
+use crate::types::{FromRedisValue, NumericBehavior, RedisResult, ToRedisArgs, RedisWrite, Expiry, Expiration, DumpPayload, LatencyEvent, ReplicationOutcome, Value, from_redis_value};
+use crate::types::ErrorKind;
+use std::collections::HashMap;
+use std::time::Duration;
 
 #[cfg(feature = "cluster")]
 use crate::cluster_pipeline::ClusterPipeline;
 
+use crate::config;
+
 #[cfg(feature = "geospatial")]
 use crate::geo;
 
@@ -17,6 +22,9 @@ use crate::streams;
 #[cfg(feature = "acl")]
 use crate::acl;
 
+#[cfg(feature = "safe-encoding")]
+use crate::types::checked_utf8_key;
+
 macro_rules! implement_commands {
     (
         $lifetime: lifetime
@@ -55,6 +63,12 @@ macro_rules! implement_commands {
         /// assert_eq!(con.get("my_key"), Ok(42));
         /// # Ok(()) }
         /// ```
+        ///
+        /// Commands which the server rejects if a list of values is empty, like
+        /// `lpush` with an empty slice of elements, fail with
+        /// [`ErrorKind::ClientError`](crate::ErrorKind::ClientError) before they
+        /// are sent.  Build the command with [`Cmd`] and call
+        /// [`Cmd::allow_empty_args`] to send it anyway.
         pub trait Commands : ConnectionLike+Sized {
             $(
                 $(#[$attr])*
@@ -132,6 +146,102 @@ macro_rules! implement_commands {
                 c.arg(key).cursor_arg(0).arg("MATCH").arg(pattern);
                 c.iter(self)
             }
+
+            /// Checks which of the given commands the server supports, using
+            /// `COMMAND INFO`.  The returned map holds an entry for every name.
+            #[inline]
+            fn supported_commands(&mut self, commands: &[&str]) -> RedisResult<HashMap<String, bool>> {
+                if commands.is_empty() {
+                    return Ok(HashMap::new());
+                }
+                let reply = Cmd::command_info(commands).query(self)?;
+                command_support(commands, reply)
+            }
+
+            /// Sends `write` followed by `WAIT` on the same connection and
+            /// checks that at least `min_replicas` replicas acknowledged it
+            /// within `timeout`.  Returns the reply to the write together with
+            /// the [`ReplicationOutcome`].
+            ///
+            /// On a cluster both commands are sent to the node serving the key
+            /// of the write.  A zero `timeout` blocks until enough replicas
+            /// acknowledged the write, possibly forever.  Timeouts below a
+            /// millisecond are rounded up to one.
+            #[inline]
+            fn write_with_replication<RV: FromRedisValue>(&mut self, write: &Cmd, min_replicas: usize, timeout: Duration) -> RedisResult<(RV, ReplicationOutcome)> {
+                let pipe = replication_pipeline(write, min_replicas, timeout)?;
+                let replies = self.req_packed_commands(&pipe.get_packed_pipeline(), 0, 2)?;
+                replication_result(replies, min_replicas)
+            }
+
+            /// Runs a command that stores its result in `dest`, like
+            /// `SINTERSTORE` or `ZUNIONSTORE`, and sets the TTL of `dest` to
+            /// `ttl` atomically.  Returns the number of elements stored.
+            ///
+            /// Storing a result replaces `dest` including its TTL, so a
+            /// separate `PEXPIRE` would leave a window in which the key does
+            /// not expire.  The two commands are sent as a transaction, or as
+            /// a Lua script on connections without transactions like cluster
+            /// connections.  On a cluster all keys of `store` must be in the
+            /// same slot as `dest`.
+            ///
+            /// A `ttl` below a millisecond is rounded up to one, a zero `ttl`
+            /// fails with [`ClientError`](ErrorKind::ClientError) without
+            /// sending anything.
+            ///
+            /// ```rust,no_run
+            /// # fn do_something(con: &mut redis::Connection) -> redis::RedisResult<()> {
+            /// use redis::{Cmd, Commands};
+            /// use std::time::Duration;
+            ///
+            /// let store = Cmd::sinterstore(&["{u}:common"][..], &["{u}:a", "{u}:b"]);
+            /// let count = con.store_with_ttl(&store, "{u}:common", Duration::from_secs(60))?;
+            /// # Ok(()) }
+            /// ```
+            #[inline]
+            fn store_with_ttl<K: ToRedisArgs>(&mut self, store: &Cmd, dest: K, ttl: Duration) -> RedisResult<u64> {
+                if self.supports_pipelining() {
+                    let (count,) = store_with_ttl_pipeline(store, dest, ttl)?.query(self)?;
+                    Ok(count)
+                } else {
+                    store_with_ttl_script(store, dest, ttl)?.query(self)
+                }
+            }
+
+            /// Gets the value of a key, refusing keys that are not valid UTF-8.
+            ///
+            /// Fails with a `TypeError` instead of sending the command if `key`
+            /// is not valid UTF-8.
+            #[cfg(feature = "safe-encoding")]
+            #[cfg_attr(docsrs, doc(cfg(feature = "safe-encoding")))]
+            #[inline]
+            fn get_str<RV: FromRedisValue>(&mut self, key: &[u8]) -> RedisResult<RV> {
+                self.get(checked_utf8_key(key)?)
+            }
+
+            /// Sets the string value of a key, refusing keys that are not valid UTF-8.
+            #[cfg(feature = "safe-encoding")]
+            #[cfg_attr(docsrs, doc(cfg(feature = "safe-encoding")))]
+            #[inline]
+            fn set_str<V: ToRedisArgs, RV: FromRedisValue>(&mut self, key: &[u8], value: V) -> RedisResult<RV> {
+                self.set(checked_utf8_key(key)?, value)
+            }
+
+            /// Deletes a key, refusing keys that are not valid UTF-8.
+            #[cfg(feature = "safe-encoding")]
+            #[cfg_attr(docsrs, doc(cfg(feature = "safe-encoding")))]
+            #[inline]
+            fn del_str<RV: FromRedisValue>(&mut self, key: &[u8]) -> RedisResult<RV> {
+                self.del(checked_utf8_key(key)?)
+            }
+
+            /// Determines if a key exists, refusing keys that are not valid UTF-8.
+            #[cfg(feature = "safe-encoding")]
+            #[cfg_attr(docsrs, doc(cfg(feature = "safe-encoding")))]
+            #[inline]
+            fn exists_str<RV: FromRedisValue>(&mut self, key: &[u8]) -> RedisResult<RV> {
+                self.exists(checked_utf8_key(key)?)
+            }
         }
 
         impl Cmd {
@@ -172,20 +282,40 @@ macro_rules! implement_commands {
         /// assert_eq!(con.get("my_key").await, Ok(42i32));
         /// # Ok(()) }
         /// ```
+        ///
+        /// # Cancel safety
+        ///
+        /// The futures returned by these methods are not cancel safe.  A
+        /// future dropped before it completes, e.g. by losing a `select!`,
+        /// may or may not have sent the command, so the command may still
+        /// be executed by the server.  What happens to the connection
+        /// depends on its type:
+        ///
+        /// * On a [`MultiplexedConnection`](crate::aio::MultiplexedConnection)
+        ///   or a [`ConnectionManager`](crate::aio::ConnectionManager) the
+        ///   reply is discarded when it arrives, and the connection stays
+        ///   usable.
+        /// * On a single [`Connection`](crate::aio::Connection) the reply is
+        ///   left unread, so later commands would read the replies of
+        ///   earlier ones.  Such a connection must be dropped after one of
+        ///   its futures was cancelled.
         #[cfg(feature = "aio")]
         pub trait AsyncCommands : crate::aio::ConnectionLike + Send + Sized {
             $(
                 $(#[$attr])*
                 #[inline]
                 #[allow(clippy::extra_unused_lifetimes, clippy::needless_lifetimes)]
-                fn $name<$lifetime, $($tyargs: $ty + Send + Sync + $lifetime,)* RV>(
+                fn $name<$lifetime, $($tyargs: $ty + Send,)* RV>(
                     & $lifetime mut self
                     $(, $argname: $argty)*
-                ) -> crate::types::RedisFuture<'a, RV>
+                ) -> crate::types::RedisFuture<$lifetime, RV>
                 where
                     RV: FromRedisValue,
                 {
-                    Box::pin(async move { ($body).query_async(self).await })
+                    // Encode the arguments up front so the future only holds
+                    // the packed command and never borrows them.
+                    let c = std::mem::take($body);
+                    Box::pin(async move { c.query_async(self).await })
                 }
             )*
 
@@ -256,6 +386,88 @@ macro_rules! implement_commands {
                 c.arg(key).cursor_arg(0).arg("MATCH").arg(pattern);
                 Box::pin(async move {c.iter_async(self).await })
             }
+
+            /// Checks which of the given commands the server supports, using
+            /// `COMMAND INFO`.  The returned map holds an entry for every name.
+            #[inline]
+            fn supported_commands<'a>(&'a mut self, commands: &'a [&'a str]) -> crate::types::RedisFuture<'a, HashMap<String, bool>> {
+                Box::pin(async move {
+                    if commands.is_empty() {
+                        return Ok(HashMap::new());
+                    }
+                    let reply = Cmd::command_info(commands).query_async(self).await?;
+                    command_support(commands, reply)
+                })
+            }
+
+            /// Sends `write` followed by `WAIT` on the same connection and
+            /// checks that at least `min_replicas` replicas acknowledged it
+            /// within `timeout`.  Returns the reply to the write together with
+            /// the [`ReplicationOutcome`].
+            ///
+            /// A zero `timeout` blocks until enough replicas acknowledged the
+            /// write, possibly forever.  Timeouts below a millisecond are
+            /// rounded up to one.
+            #[inline]
+            fn write_with_replication<'a, RV: FromRedisValue>(&'a mut self, write: &Cmd, min_replicas: usize, timeout: Duration) -> crate::types::RedisFuture<'a, (RV, ReplicationOutcome)> {
+                let pipe = replication_pipeline(write, min_replicas, timeout);
+                Box::pin(async move {
+                    let replies = self.req_packed_commands(&pipe?, 0, 2).await?;
+                    replication_result(replies, min_replicas)
+                })
+            }
+
+            /// Runs a command that stores its result in `dest`, like
+            /// `SINTERSTORE` or `ZUNIONSTORE`, and sets the TTL of `dest` to
+            /// `ttl` in the same transaction.  Returns the number of elements
+            /// stored.
+            ///
+            /// A `ttl` below a millisecond is rounded up to one, a zero `ttl`
+            /// fails with [`ClientError`](ErrorKind::ClientError) without
+            /// sending anything.
+            #[inline]
+            fn store_with_ttl<'a, K: ToRedisArgs>(&'a mut self, store: &Cmd, dest: K, ttl: Duration) -> crate::types::RedisFuture<'a, u64> {
+                let pipe = store_with_ttl_pipeline(store, dest, ttl);
+                Box::pin(async move {
+                    let (count,) = pipe?.query_async(self).await?;
+                    Ok(count)
+                })
+            }
+
+            /// Gets the value of a key, refusing keys that are not valid UTF-8.
+            ///
+            /// Fails with a `TypeError` instead of sending the command if `key`
+            /// is not valid UTF-8.
+            #[cfg(feature = "safe-encoding")]
+            #[cfg_attr(docsrs, doc(cfg(feature = "safe-encoding")))]
+            #[inline]
+            fn get_str<'a, RV: FromRedisValue>(&'a mut self, key: &'a [u8]) -> crate::types::RedisFuture<'a, RV> {
+                Box::pin(async move { self.get(checked_utf8_key(key)?).await })
+            }
+
+            /// Sets the string value of a key, refusing keys that are not valid UTF-8.
+            #[cfg(feature = "safe-encoding")]
+            #[cfg_attr(docsrs, doc(cfg(feature = "safe-encoding")))]
+            #[inline]
+            fn set_str<'a, V: ToRedisArgs + Send + 'a, RV: FromRedisValue>(&'a mut self, key: &'a [u8], value: V) -> crate::types::RedisFuture<'a, RV> {
+                Box::pin(async move { self.set(checked_utf8_key(key)?, value).await })
+            }
+
+            /// Deletes a key, refusing keys that are not valid UTF-8.
+            #[cfg(feature = "safe-encoding")]
+            #[cfg_attr(docsrs, doc(cfg(feature = "safe-encoding")))]
+            #[inline]
+            fn del_str<'a, RV: FromRedisValue>(&'a mut self, key: &'a [u8]) -> crate::types::RedisFuture<'a, RV> {
+                Box::pin(async move { self.del(checked_utf8_key(key)?).await })
+            }
+
+            /// Determines if a key exists, refusing keys that are not valid UTF-8.
+            #[cfg(feature = "safe-encoding")]
+            #[cfg_attr(docsrs, doc(cfg(feature = "safe-encoding")))]
+            #[inline]
+            fn exists_str<'a, RV: FromRedisValue>(&'a mut self, key: &'a [u8]) -> crate::types::RedisFuture<'a, RV> {
+                Box::pin(async move { self.exists(checked_utf8_key(key)?).await })
+            }
         }
 
         /// Implements common redis commands for pipelines.  Unlike the regular
@@ -314,7 +526,7 @@ implement_commands! {
 
     /// Sets multiple keys to their values.
     fn set_multiple<K: ToRedisArgs, V: ToRedisArgs>(items: &'a [(K, V)]) {
-        cmd("MSET").arg(items)
+        cmd("MSET").arg_nonempty(items)
     }
 
     /// Set the value and expiration of a key.
@@ -322,6 +534,14 @@ implement_commands! {
         cmd("SETEX").arg(key).arg(seconds).arg(value)
     }
 
+    /// Set the value of a key together with its expiration, see [`Expiration`](crate::Expiration).
+    /// `Expiration::Persist` fails with
+    /// [`ErrorKind::ClientError`](crate::ErrorKind::ClientError) without
+    /// being sent.
+    fn set_with_expiration<K: ToRedisArgs, V: ToRedisArgs>(key: K, value: V, expiration: Expiration) {
+        cmd("SET").arg(key).arg(value).arg(expiration).reject_options(unsupported_expiration("SET", expiration))
+    }
+
     /// Set the value and expiration in milliseconds of a key.
     fn pset_ex<K: ToRedisArgs, V: ToRedisArgs>(key: K, value: V, milliseconds: usize) {
         cmd("PSETEX").arg(key).arg(milliseconds).arg(value)
@@ -334,7 +554,7 @@ implement_commands! {
 
     /// Sets multiple keys to their values failing if at least one already exists.
     fn mset_nx<K: ToRedisArgs, V: ToRedisArgs>(items: &'a [(K, V)]) {
-        cmd("MSETNX").arg(items)
+        cmd("MSETNX").arg_nonempty(items)
     }
 
     /// Set the string value of a key and return its old value.
@@ -348,18 +568,18 @@ implement_commands! {
     }
 
     /// Overwrite the part of the value stored in key at the specified offset.
-    fn setrange<K: ToRedisArgs, V: ToRedisArgs>(key: K, offset: isize, value: V) {
+    fn setrange<K: ToRedisArgs, V: ToRedisArgs>(key: K, offset: usize, value: V) {
         cmd("SETRANGE").arg(key).arg(offset).arg(value)
     }
 
     /// Delete one or more keys.
     fn del<K: ToRedisArgs>(key: K) {
-        cmd("DEL").arg(key)
+        cmd("DEL").arg_nonempty(key)
     }
 
     /// Determine if a key exists.
     fn exists<K: ToRedisArgs>(key: K) {
-        cmd("EXISTS").arg(key)
+        cmd("EXISTS").arg_nonempty(key)
     }
 
     /// Set a key's time to live in seconds.
@@ -410,6 +630,14 @@ implement_commands! {
         cmd("GETEX").arg(key).arg(option).arg(time_arg)
     }
 
+    /// Get the value of a key and set its expiration, see [`Expiration`](crate::Expiration).
+    /// `Expiration::Keep` fails with
+    /// [`ErrorKind::ClientError`](crate::ErrorKind::ClientError) without
+    /// being sent.
+    fn get_with_expiration<K: ToRedisArgs>(key: K, expiration: Expiration) {
+        cmd("GETEX").arg(key).arg(expiration).reject_options(unsupported_expiration("GETEX", expiration))
+    }
+
     /// Get the value of a key and delete it
     fn get_del<K: ToRedisArgs>(key: K) {
         cmd("GETDEL").arg(key)
@@ -425,9 +653,29 @@ implement_commands! {
         cmd("RENAMENX").arg(key).arg(new_key)
     }
 
+    /// Serialize the value stored at a key.
+    ///
+    /// The reply is a [`DumpPayload`](crate::DumpPayload), or nil if the key
+    /// does not exist.
+    fn dump<K: ToRedisArgs>(key: K) {
+        cmd("DUMP").arg(key)
+    }
+
+    /// Create a key from a serialized value obtained with `DUMP`, expiring
+    /// after `ttl` milliseconds or never if `ttl` is 0.  Fails if the key
+    /// already exists.
+    fn restore<K: ToRedisArgs>(key: K, ttl: usize, payload: &'a DumpPayload) {
+        cmd("RESTORE").arg(key).arg(ttl).arg(payload)
+    }
+
     /// Unlink one or more keys.
     fn unlink<K: ToRedisArgs>(key: K) {
-        cmd("UNLINK").arg(key)
+        cmd("UNLINK").arg_nonempty(key)
+    }
+
+    /// Sort the elements of a list, set or sorted set, see [`SortOptions`].
+    fn sort<K: ToRedisArgs>(key: K, options: SortOptions) {
+        cmd("SORT").arg(key).arg(options)
     }
 
     // common string operations
@@ -510,7 +758,7 @@ implement_commands! {
 
     /// Deletes a single (or multiple) fields from a hash.
     fn hdel<K: ToRedisArgs, F: ToRedisArgs>(key: K, field: F) {
-        cmd("HDEL").arg(key).arg(field)
+        cmd("HDEL").arg(key).arg_nonempty(field)
     }
 
     /// Sets a single field in a hash.
@@ -525,7 +773,13 @@ implement_commands! {
 
     /// Sets a multiple fields in a hash.
     fn hset_multiple<K: ToRedisArgs, F: ToRedisArgs, V: ToRedisArgs>(key: K, items: &'a [(F, V)]) {
-        cmd("HMSET").arg(key).arg(items)
+        cmd("HMSET").arg(key).arg_nonempty(items)
+    }
+
+    /// Sets multiple fields in a hash from a map of fields to values, such
+    /// as a `HashMap` or a `BTreeMap`.
+    fn hset_map<K: ToRedisArgs, M: ToRedisArgs>(key: K, items: M) {
+        cmd("HSET").arg(key).arg_nonempty(items)
     }
 
     /// Increments a value.
@@ -609,6 +863,12 @@ implement_commands! {
         cmd("LINSERT").arg(key).arg("AFTER").arg(pivot).arg(value)
     }
 
+    /// Insert an element before or after another element in a list.
+    fn linsert<K: ToRedisArgs, P: ToRedisArgs, V: ToRedisArgs>(
+            key: K, position: Where, pivot: P, value: V) {
+        cmd("LINSERT").arg(key).arg(position).arg(pivot).arg(value)
+    }
+
     /// Returns the length of the list stored at key.
     fn llen<K: ToRedisArgs>(key: K) {
         cmd("LLEN").arg(key)
@@ -639,13 +899,13 @@ implement_commands! {
 
     /// Insert all the specified values at the head of the list stored at key.
     fn lpush<K: ToRedisArgs, V: ToRedisArgs>(key: K, value: V) {
-        cmd("LPUSH").arg(key).arg(value)
+        cmd("LPUSH").arg(key).arg_nonempty(value)
     }
 
     /// Inserts a value at the head of the list stored at key, only if key
     /// already exists and holds a list.
     fn lpush_exists<K: ToRedisArgs, V: ToRedisArgs>(key: K, value: V) {
-        cmd("LPUSHX").arg(key).arg(value)
+        cmd("LPUSHX").arg(key).arg_nonempty(value)
     }
 
     /// Returns the specified elements of the list stored at key.
@@ -684,20 +944,20 @@ implement_commands! {
 
     /// Insert all the specified values at the tail of the list stored at key.
     fn rpush<K: ToRedisArgs, V: ToRedisArgs>(key: K, value: V) {
-        cmd("RPUSH").arg(key).arg(value)
+        cmd("RPUSH").arg(key).arg_nonempty(value)
     }
 
     /// Inserts value at the tail of the list stored at key, only if key
     /// already exists and holds a list.
     fn rpush_exists<K: ToRedisArgs, V: ToRedisArgs>(key: K, value: V) {
-        cmd("RPUSHX").arg(key).arg(value)
+        cmd("RPUSHX").arg(key).arg_nonempty(value)
     }
 
     // set commands
 
     /// Add one or more members to a set.
     fn sadd<K: ToRedisArgs, M: ToRedisArgs>(key: K, member: M) {
-        cmd("SADD").arg(key).arg(member)
+        cmd("SADD").arg(key).arg_nonempty(member)
     }
 
     /// Get the number of members in a set.
@@ -707,7 +967,7 @@ implement_commands! {
 
     /// Subtract multiple sets.
     fn sdiff<K: ToRedisArgs>(keys: K) {
-        cmd("SDIFF").arg(keys)
+        cmd("SDIFF").arg_nonempty(keys)
     }
 
     /// Subtract multiple sets and store the resulting set in a key.
@@ -717,7 +977,7 @@ implement_commands! {
 
     /// Intersect multiple sets.
     fn sinter<K: ToRedisArgs>(keys: K) {
-        cmd("SINTER").arg(keys)
+        cmd("SINTER").arg_nonempty(keys)
     }
 
     /// Intersect multiple sets and store the resulting set in a key.
@@ -755,14 +1015,20 @@ implement_commands! {
         cmd("SRANDMEMBER").arg(key).arg(count)
     }
 
+    /// Get random members from a set, which may repeat if the count
+    /// was created with [`Count::with_duplicates`].
+    fn srandmember_count<K: ToRedisArgs>(key: K, count: Count) {
+        cmd("SRANDMEMBER").arg(key).arg(count)
+    }
+
     /// Remove one or more members from a set.
     fn srem<K: ToRedisArgs, M: ToRedisArgs>(key: K, member: M) {
-        cmd("SREM").arg(key).arg(member)
+        cmd("SREM").arg(key).arg_nonempty(member)
     }
 
     /// Add multiple sets.
     fn sunion<K: ToRedisArgs>(keys: K) {
-        cmd("SUNION").arg(keys)
+        cmd("SUNION").arg_nonempty(keys)
     }
 
     /// Add multiple sets and store the resulting set in a key.
@@ -779,7 +1045,29 @@ implement_commands! {
 
     /// Add multiple members to a sorted set, or update its score if it already exists.
     fn zadd_multiple<K: ToRedisArgs, S: ToRedisArgs, M: ToRedisArgs>(key: K, items: &'a [(S, M)]) {
-        cmd("ZADD").arg(key).arg(items)
+        cmd("ZADD").arg(key).arg_nonempty(items)
+    }
+
+    /// Add one member to a sorted set, or update its score, subject to the
+    /// conditions in `options`.
+    fn zadd_options<K: ToRedisArgs, S: ToRedisArgs, M: ToRedisArgs>(key: K, member: M, score: S, options: ZAddOptions) {
+        cmd("ZADD").arg(key).arg(options).reject_options(options.conflict()).arg(score).arg(member)
+    }
+
+    /// Add multiple members to a sorted set, or update their scores, subject
+    /// to the conditions in `options`.
+    fn zadd_multiple_options<K: ToRedisArgs, S: ToRedisArgs, M: ToRedisArgs>(key: K, items: &'a [(S, M)], options: ZAddOptions) {
+        cmd("ZADD").arg(key).arg(options).reject_options(options.conflict()).arg_nonempty(items)
+    }
+
+    /// Increments the score of a member like [`zincr`](Commands::zincr),
+    /// subject to the conditions in `options`.  The server only allows a
+    /// single member with `INCR`, so this is separate from the other forms.
+    ///
+    /// Usually decoded as `Option<f64>`: the new score, or `None` if the
+    /// conditions prevented the update.
+    fn zadd_incr<K: ToRedisArgs, M: ToRedisArgs, D: ToRedisArgs>(key: K, member: M, delta: D, options: ZAddOptions) {
+        cmd("ZADD").arg(key).arg(options).reject_options(options.conflict()).arg("INCR").arg(delta).arg(member)
     }
 
     /// Get the number of members in a sorted set.
@@ -816,30 +1104,38 @@ implement_commands! {
         cmd("ZINTERSTORE").arg(dstkey).arg(keys.len()).arg(keys).arg("AGGREGATE").arg("MAX")
     }
 
+    /// Intersect multiple sorted sets, multiplying the scores of each set
+    /// by its weight, and store the resulting sorted set in a new key using
+    /// SUM as aggregation function.
+    fn zinterstore_weights<K: ToRedisArgs, W: ToRedisArgs>(dstkey: K, keys: &'a [(K, W)]) {
+        let (keys, weights): (Vec<&K>, Vec<&W>) = keys.iter().map(|(key, weight)| (key, weight)).unzip();
+        cmd("ZINTERSTORE").arg(dstkey).arg(keys.len()).arg(keys).arg(Prefixed("WEIGHTS", &weights))
+    }
+
     /// Count the number of members in a sorted set between a given lexicographical range.
     fn zlexcount<K: ToRedisArgs, L: ToRedisArgs>(key: K, min: L, max: L) {
         cmd("ZLEXCOUNT").arg(key).arg(min).arg(max)
     }
 
     /// Removes and returns up to count members with the highest scores in a sorted set
-    fn zpopmax<K: ToRedisArgs>(key: K, count: isize) {
+    fn zpopmax<K: ToRedisArgs>(key: K, count: usize) {
         cmd("ZPOPMAX").arg(key).arg(count)
     }
 
     /// Removes and returns up to count members with the lowest scores in a sorted set
-    fn zpopmin<K: ToRedisArgs>(key: K, count: isize) {
+    fn zpopmin<K: ToRedisArgs>(key: K, count: usize) {
         cmd("ZPOPMIN").arg(key).arg(count)
     }
 
     /// Removes and returns up to count members with the highest scores, 
     /// from the first non-empty sorted set in the provided list of key names.
-    fn zmpop_max<K: ToRedisArgs>(keys: &'a [K], count: isize) {
+    fn zmpop_max<K: ToRedisArgs>(keys: &'a [K], count: usize) {
         cmd("ZMPOP").arg(keys.len()).arg(keys).arg("MAX").arg("COUNT").arg(count)
     }
 
     /// Removes and returns up to count members with the lowest scores, 
     /// from the first non-empty sorted set in the provided list of key names.
-    fn zmpop_min<K: ToRedisArgs>(keys: &'a [K], count: isize) {
+    fn zmpop_min<K: ToRedisArgs>(keys: &'a [K], count: usize) {
         cmd("ZMPOP").arg(keys.len()).arg(keys).arg("MIN").arg("COUNT").arg(count)
     }
 
@@ -917,7 +1213,7 @@ implement_commands! {
 
     /// Remove one or more members from a sorted set.
     fn zrem<K: ToRedisArgs, M: ToRedisArgs>(key: K, members: M) {
-        cmd("ZREM").arg(key).arg(members)
+        cmd("ZREM").arg(key).arg_nonempty(members)
     }
 
     /// Remove all members in a sorted set between the given lexicographical range.
@@ -1003,6 +1299,14 @@ implement_commands! {
         cmd("ZUNIONSTORE").arg(dstkey).arg(keys.len()).arg(keys).arg("AGGREGATE").arg("MAX")
     }
 
+    /// Add multiple sorted sets, multiplying the scores of each set by its
+    /// weight, and store the resulting sorted set in a new key using SUM as
+    /// aggregation function.
+    fn zunionstore_weights<K: ToRedisArgs, W: ToRedisArgs>(dstkey: K, keys: &'a [(K, W)]) {
+        let (keys, weights): (Vec<&K>, Vec<&W>) = keys.iter().map(|(key, weight)| (key, weight)).unzip();
+        cmd("ZUNIONSTORE").arg(dstkey).arg(keys.len()).arg(keys).arg(Prefixed("WEIGHTS", &weights))
+    }
+
     // hyperloglog commands
 
     /// Adds the specified elements to the specified HyperLogLog.
@@ -1013,7 +1317,7 @@ implement_commands! {
     /// Return the approximated cardinality of the set(s) observed by the
     /// HyperLogLog at key(s).
     fn pfcount<K: ToRedisArgs>(key: K) {
-        cmd("PFCOUNT").arg(key)
+        cmd("PFCOUNT").arg_nonempty(key)
     }
 
     /// Merge N different HyperLogLogs into a single one.
@@ -1026,9 +1330,50 @@ implement_commands! {
         cmd("PUBLISH").arg(channel).arg(message)
     }
 
+    /// Lists the currently active channels, usually decoded as `Vec<String>`.
+    fn pubsub_channels<>() {
+        cmd("PUBSUB").arg("CHANNELS")
+    }
+
+    /// Lists the currently active channels matching a pattern, usually decoded
+    /// as `Vec<String>`.
+    fn pubsub_channels_match<P: ToRedisArgs>(pattern: P) {
+        cmd("PUBSUB").arg("CHANNELS").arg(pattern)
+    }
+
+    /// Returns the number of subscribers of the given channels, usually
+    /// decoded as `Vec<(String, u64)>` in the order the channels were given.
+    fn pubsub_numsub<C: ToRedisArgs>(channels: C) {
+        cmd("PUBSUB").arg("NUMSUB").arg(channels)
+    }
+
+    /// Returns the number of unique patterns subscribed to by all clients.
+    fn pubsub_numpat<>() {
+        cmd("PUBSUB").arg("NUMPAT")
+    }
+
+    /// Lists the currently active shard channels, usually decoded as
+    /// `Vec<String>`.
+    fn pubsub_shardchannels<>() {
+        cmd("PUBSUB").arg("SHARDCHANNELS")
+    }
+
+    /// Lists the currently active shard channels matching a pattern, usually
+    /// decoded as `Vec<String>`.
+    fn pubsub_shardchannels_match<P: ToRedisArgs>(pattern: P) {
+        cmd("PUBSUB").arg("SHARDCHANNELS").arg(pattern)
+    }
+
+    /// Returns the number of subscribers of the given shard channels, usually
+    /// decoded as `Vec<(String, u64)>` in the order the channels were given.
+    fn pubsub_shardnumsub<C: ToRedisArgs>(channels: C) {
+        cmd("PUBSUB").arg("SHARDNUMSUB").arg(channels)
+    }
+
     // Object commands
 
-    /// Returns the encoding of a key.
+    /// Returns the encoding of a key, usually decoded as
+    /// [`Encoding`](crate::Encoding).
     fn object_encoding<K: ToRedisArgs>(key: K) {
         cmd("OBJECT").arg("ENCODING").arg(key)
     }
@@ -1048,6 +1393,175 @@ implement_commands! {
         cmd("OBJECT").arg("REFCOUNT").arg(key)
     }
 
+    // Server commands
+
+    /// Returns the current server time, usually decoded as
+    /// [`SystemTime`](std::time::SystemTime) or as a `(u64, u32)` pair of
+    /// seconds and microseconds since the Unix epoch.
+    fn time<>() {
+        &mut cmd("TIME")
+    }
+
+    /// Returns details about the server, usually decoded as
+    /// [`HelloInfo`](crate::HelloInfo).  Needs Redis 6.0.
+    ///
+    /// Only protocol version 2 is supported, as the connection cannot read
+    /// RESP3 replies; any other `protover` fails with
+    /// [`ErrorKind::ClientError`](crate::ErrorKind::ClientError) without
+    /// being sent.
+    fn hello<>(protover: u8) {
+        cmd("HELLO").arg(protover).reject_options(unsupported_protover(protover))
+    }
+
+    /// Returns the id of the current connection.  Needs Redis 5.0.
+    fn client_id<>() {
+        cmd("CLIENT").arg("ID")
+    }
+
+    /// Unblocks the connection with the given id if it is blocked in a
+    /// blocking command like `BLPOP`, returning whether it was blocked.  See
+    /// [`UnblockKind`] for how the blocked command returns.  Needs Redis 5.0.
+    fn client_unblock<>(client_id: i64, kind: UnblockKind) {
+        cmd("CLIENT").arg("UNBLOCK").arg(client_id).arg(kind)
+    }
+
+    /// Returns details about the given commands, with nil for each command
+    /// the server does not know.
+    fn command_info<C: ToRedisArgs>(commands: C) {
+        cmd("COMMAND").arg("INFO").arg_nonempty(commands)
+    }
+
+    /// Returns the configuration parameters matching a glob-style pattern as
+    /// name/value pairs, which can be decoded as
+    /// [`ServerConfig`](crate::config::ServerConfig).
+    fn config_get<K: ToRedisArgs>(parameter: K) {
+        cmd("CONFIG").arg("GET").arg(parameter)
+    }
+
+    /// Sets a configuration parameter.  The types of the
+    /// [`config`](crate::config) module render the values of common
+    /// parameters.
+    fn config_set<K: ToRedisArgs, V: ToRedisArgs>(parameter: K, value: V) {
+        cmd("CONFIG").arg("SET").arg(parameter).arg(value)
+    }
+
+    /// Sets all parameters of a [`ServerConfig`](crate::config::ServerConfig)
+    /// atomically.  Setting more than one parameter at once needs Redis 7.0.
+    fn config_set_multiple<>(config: &'a config::ServerConfig) {
+        cmd("CONFIG").arg("SET").arg_nonempty(config)
+    }
+
+    // Latency commands
+
+    /// Returns the latency distribution of every command that was called,
+    /// usually decoded as [`LatencyHistogram`](crate::LatencyHistogram).
+    fn latency_histogram<>() {
+        cmd("LATENCY").arg("HISTOGRAM")
+    }
+
+    /// Returns the latency distribution of the given commands, usually
+    /// decoded as [`LatencyHistogram`](crate::LatencyHistogram).
+    fn latency_histogram_commands<C: ToRedisArgs>(commands: C) {
+        cmd("LATENCY").arg("HISTOGRAM").arg(commands)
+    }
+
+    /// Returns the latest latency spike of every event, usually decoded as
+    /// a `Vec` of [`LatencyLatestEntry`](crate::LatencyLatestEntry).
+    fn latency_latest<>() {
+        cmd("LATENCY").arg("LATEST")
+    }
+
+    /// Returns the recorded latency spikes of an event as pairs of a unix
+    /// timestamp and a latency in milliseconds.
+    fn latency_history<>(event: LatencyEvent) {
+        cmd("LATENCY").arg("HISTORY").arg(event)
+    }
+
+    /// Resets the latency data of all events and returns the number of
+    /// reset events.
+    fn latency_reset<>() {
+        cmd("LATENCY").arg("RESET")
+    }
+
+    /// Resets the latency data of the given events and returns the number of
+    /// reset events.
+    fn latency_reset_events<>(events: &'a [LatencyEvent]) {
+        cmd("LATENCY").arg("RESET").arg(events)
+    }
+
+    // Replication commands
+
+    /// Returns the replication role of the server, see [`Role`](crate::Role).
+    fn role<>() {
+        &mut cmd("ROLE")
+    }
+
+    /// Blocks until at least `numreplicas` replicas acknowledged the writes
+    /// sent before on this connection, or until `timeout` milliseconds
+    /// passed.  Returns the number of replicas that acknowledged them.  A
+    /// timeout of `0` blocks forever.
+    fn wait<>(numreplicas: usize, timeout: usize) {
+        cmd("WAIT").arg(numreplicas).arg(timeout)
+    }
+
+    // Cluster commands
+
+    /// Returns the node id of the server.
+    fn cluster_myid<>() {
+        cmd("CLUSTER").arg("MYID")
+    }
+
+    /// Assigns the given hash slots to the server.
+    fn cluster_addslots<S: ToRedisArgs>(slots: S) {
+        cmd("CLUSTER").arg("ADDSLOTS").arg(slots)
+    }
+
+    /// Assigns the given inclusive ranges of hash slots to the server.
+    fn cluster_addslotsrange<>(ranges: &'a [(u16, u16)]) {
+        cmd("CLUSTER").arg("ADDSLOTSRANGE").arg(ranges)
+    }
+
+    /// Removes the given hash slots from the server.
+    fn cluster_delslots<S: ToRedisArgs>(slots: S) {
+        cmd("CLUSTER").arg("DELSLOTS").arg(slots)
+    }
+
+    /// Changes the state of a hash slot on the server, see [`SlotState`].
+    fn cluster_setslot<>(slot: u16, state: SlotState) {
+        cmd("CLUSTER").arg("SETSLOT").arg(slot).arg(state)
+    }
+
+    /// Returns up to `count` keys stored in the given hash slot.
+    fn cluster_getkeysinslot<>(slot: u16, count: usize) {
+        cmd("CLUSTER").arg("GETKEYSINSLOT").arg(slot).arg(count)
+    }
+
+    /// Returns the number of keys stored in the given hash slot.
+    fn cluster_countkeysinslot<>(slot: u16) {
+        cmd("CLUSTER").arg("COUNTKEYSINSLOT").arg(slot)
+    }
+
+    /// Starts a coordinated failover of the master of this replica.
+    fn cluster_failover<>() {
+        cmd("CLUSTER").arg("FAILOVER")
+    }
+
+    /// Starts a failover of the master of this replica without the agreement
+    /// of the master or of the rest of the cluster, see [`FailoverMode`].
+    fn cluster_failover_mode<>(mode: FailoverMode) {
+        cmd("CLUSTER").arg("FAILOVER").arg(mode)
+    }
+
+    // Debug commands
+
+    /// Enables or disables the active expiry cycle of the server. Keys with
+    /// an expiry are then only removed lazily when they are accessed.
+    #[cfg(feature = "debug-commands")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "debug-commands")))]
+    fn debug_set_active_expire<>(enabled: bool) {
+        cmd("DEBUG").arg("SET-ACTIVE-EXPIRE").arg(enabled)
+    }
+
     // ACL commands
 
     /// When Redis is configured to use an ACL file (with the aclfile
@@ -1137,7 +1651,7 @@ implement_commands! {
     /// Generates a 1-to-1024-bits password starting from /dev/urandom if available.
     #[cfg(feature = "acl")]
     #[cfg_attr(docsrs, doc(cfg(feature = "acl")))]
-    fn acl_genpass_bits<>(bits: isize) {
+    fn acl_genpass_bits<>(bits: usize) {
         cmd("ACL").arg("GENPASS").arg(bits)
     }
 
@@ -1151,7 +1665,7 @@ implement_commands! {
     /// Shows a list of recent ACL security events
     #[cfg(feature = "acl")]
     #[cfg_attr(docsrs, doc(cfg(feature = "acl")))]
-    fn acl_log<>(count: isize) {
+    fn acl_log<>(count: usize) {
         cmd("ACL").arg("LOG").arg(count)
 
     }
@@ -1210,7 +1724,7 @@ implement_commands! {
     #[cfg(feature = "geospatial")]
     #[cfg_attr(docsrs, doc(cfg(feature = "geospatial")))]
     fn geo_add<K: ToRedisArgs, M: ToRedisArgs>(key: K, members: M) {
-        cmd("GEOADD").arg(key).arg(members)
+        cmd("GEOADD").arg(key).arg_nonempty(members)
     }
 
     /// Return the distance between two members in the geospatial index
@@ -1388,7 +1902,7 @@ implement_commands! {
         cmd("XACK")
             .arg(key)
             .arg(group)
-            .arg(ids)
+            .arg_nonempty(ids)
     }
 
 
@@ -1557,6 +2071,75 @@ implement_commands! {
     }
 
 
+    /// Transfers ownership of pending messages that have been idle for at
+    /// least `min_idle_time` to `consumer`, scanning the pending entries list
+    /// from `start`.  This is like calling `xpending` and then `xclaim`, but
+    /// in a single round-trip.
+    ///
+    /// ```text
+    /// XAUTOCLAIM <key> <group> <consumer> <min-idle-time> <start>
+    /// ```
+    #[cfg(feature = "streams")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "streams")))]
+    fn xautoclaim<K: ToRedisArgs, G: ToRedisArgs, C: ToRedisArgs, MIT: ToRedisArgs, S: ToRedisArgs>(
+        key: K,
+        group: G,
+        consumer: C,
+        min_idle_time: MIT,
+        start: S
+    ) {
+        cmd("XAUTOCLAIM")
+            .arg(key)
+            .arg(group)
+            .arg(consumer)
+            .arg(min_idle_time)
+            .arg(start)
+    }
+
+    /// This is the optional arguments version of `xautoclaim`.
+    ///
+    /// ```no_run
+    /// use redis::{Commands, RedisResult};
+    /// use redis::streams::{StreamAutoClaimOptions, StreamAutoClaimReply};
+    /// let client = redis::Client::open("redis://127.0.0.1/0").unwrap();
+    /// let mut con = client.get_connection().unwrap();
+    ///
+    /// // Claim up to 25 messages of key "k1" from group "g1"
+    /// // that were idle for at least 10ms.
+    /// let opts = StreamAutoClaimOptions::default().count(25);
+    /// let reply: RedisResult<StreamAutoClaimReply> =
+    ///     con.xautoclaim_options("k1", "g1", "c1", 10, "0-0", opts);
+    /// ```
+    ///
+    /// ```text
+    /// XAUTOCLAIM <key> <group> <consumer> <min-idle-time> <start>
+    ///     [COUNT <count>] [JUSTID]
+    /// ```
+    #[cfg(feature = "streams")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "streams")))]
+    fn xautoclaim_options<
+        K: ToRedisArgs,
+        G: ToRedisArgs,
+        C: ToRedisArgs,
+        MIT: ToRedisArgs,
+        S: ToRedisArgs
+    >(
+        key: K,
+        group: G,
+        consumer: C,
+        min_idle_time: MIT,
+        start: S,
+        options: streams::StreamAutoClaimOptions
+    ) {
+        cmd("XAUTOCLAIM")
+            .arg(key)
+            .arg(group)
+            .arg(consumer)
+            .arg(min_idle_time)
+            .arg(start)
+            .arg(options)
+    }
+
     /// Deletes a list of `id`s for a given stream `key`.
     ///
     /// ```text
@@ -1568,7 +2151,7 @@ implement_commands! {
         key: K,
         ids: &'a [ID]
     ) {
-        cmd("XDEL").arg(key).arg(ids)
+        cmd("XDEL").arg(key).arg_nonempty(ids)
     }
 
 
@@ -2140,6 +2723,191 @@ impl PubSubCommands for Connection {
     }
 }
 
+/// A count argument with the special meanings Redis gives to zero and
+/// negative counts.
+///
+/// `LPOS ... COUNT 0` returns all matches, while a negative count for
+/// `SRANDMEMBER` allows the same member to be returned more than once.
+///
+/// ```rust
+/// use redis::{Count, ToRedisArgs};
+///
+/// assert_eq!(Count::all().to_redis_args(), vec![b"0".to_vec()]);
+/// assert_eq!(Count::with_duplicates(3).to_redis_args(), vec![b"-3".to_vec()]);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Count {
+    n: usize,
+    duplicates: bool,
+}
+
+impl Count {
+    /// No limit, sent as `0`.  Only meaningful for commands that treat a
+    /// zero count as unlimited, like `LPOS`.
+    pub fn all() -> Count {
+        Count::exactly(0)
+    }
+
+    /// At most `n` distinct items.
+    pub fn exactly(n: usize) -> Count {
+        Count {
+            n,
+            duplicates: false,
+        }
+    }
+
+    /// Exactly `n` items which may repeat, sent as `-n`.
+    pub fn with_duplicates(n: usize) -> Count {
+        Count {
+            n,
+            duplicates: true,
+        }
+    }
+}
+
+impl ToRedisArgs for Count {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        if self.duplicates && self.n > 0 {
+            out.write_arg_fmt(format_args!("-{}", self.n));
+        } else {
+            out.write_arg_fmt(self.n);
+        }
+    }
+}
+
+/// Writes `keyword` once followed by all values, like `WEIGHTS 1 2`, or
+/// nothing if there are no values.
+struct Prefixed<'a, T>(&'static str, &'a [T]);
+
+impl<T: ToRedisArgs> ToRedisArgs for Prefixed<'_, T> {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        if !self.1.is_empty() {
+            out.write_arg(self.0.as_bytes());
+            self.1.write_redis_args(out);
+        }
+    }
+
+    fn is_single_arg(&self) -> bool {
+        false
+    }
+}
+
+/// Writes `keyword` before every value, like `GET pat1 GET pat2`.
+struct Repeated<'a, T>(&'static str, &'a [T]);
+
+impl<T: ToRedisArgs> ToRedisArgs for Repeated<'_, T> {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        for value in self.1 {
+            out.write_arg(self.0.as_bytes());
+            value.write_redis_args(out);
+        }
+    }
+
+    fn is_single_arg(&self) -> bool {
+        false
+    }
+}
+
+/// Options for the [SORT](https://redis.io/commands/sort) command
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use redis::{Commands, RedisResult, SortOptions};
+/// fn names_by_weight(con: &mut redis::Connection) -> RedisResult<Vec<(String, String)>> {
+///     // Returns the id and the name of every member of `ids`.
+///     let opts = SortOptions::default()
+///         .by("weight_*")
+///         .get("#")
+///         .get("name_*");
+///     con.sort("ids", opts)
+/// }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct SortOptions {
+    by: Option<Vec<Vec<u8>>>,
+    limit: Option<(usize, usize)>,
+    get: Vec<Vec<u8>>,
+    desc: bool,
+    alpha: bool,
+}
+
+impl SortOptions {
+    /// Sort by the values of the keys matching `pattern`, where `*` is
+    /// replaced with each element.
+    pub fn by<P: ToRedisArgs>(mut self, pattern: P) -> Self {
+        self.by = Some(pattern.to_redis_args());
+        self
+    }
+
+    /// Return only `count` elements, starting at `offset`.
+    pub fn limit(mut self, offset: usize, count: usize) -> Self {
+        self.limit = Some((offset, count));
+        self
+    }
+
+    /// Return the values of the keys matching `pattern` instead of the
+    /// elements, or the element itself for `#`.  Can be given several
+    /// times, each element then returns one value per pattern.
+    pub fn get<P: ToRedisArgs>(mut self, pattern: P) -> Self {
+        self.get.extend(pattern.to_redis_args());
+        self
+    }
+
+    /// Sort in descending order.
+    pub fn desc(mut self) -> Self {
+        self.desc = true;
+        self
+    }
+
+    /// Sort lexicographically instead of numerically.
+    pub fn alpha(mut self) -> Self {
+        self.alpha = true;
+        self
+    }
+}
+
+impl ToRedisArgs for SortOptions {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        if let Some(ref pattern) = self.by {
+            out.write_arg(b"BY");
+            pattern.write_redis_args(out);
+        }
+
+        if let Some((offset, count)) = self.limit {
+            out.write_arg(b"LIMIT");
+            out.write_arg_fmt(offset);
+            out.write_arg_fmt(count);
+        }
+
+        Repeated("GET", &self.get).write_redis_args(out);
+
+        if self.desc {
+            out.write_arg(b"DESC");
+        }
+
+        if self.alpha {
+            out.write_arg(b"ALPHA");
+        }
+    }
+
+    fn is_single_arg(&self) -> bool {
+        false
+    }
+}
+
 /// Options for the [LPOS](https://redis.io/commands/lpos) command
 ///
 /// # Example
@@ -2163,7 +2931,7 @@ impl PubSubCommands for Connection {
 /// ```
 #[derive(Default)]
 pub struct LposOptions {
-    count: Option<usize>,
+    count: Option<Count>,
     maxlen: Option<usize>,
     rank: Option<isize>,
 }
@@ -2171,7 +2939,13 @@ pub struct LposOptions {
 impl LposOptions {
     /// Limit the results to the first N matching items.
     pub fn count(mut self, n: usize) -> Self {
-        self.count = Some(n);
+        self.count = Some(Count::exactly(n));
+        self
+    }
+
+    /// Return all matching items, as `COUNT 0` does.
+    pub fn count_all(mut self) -> Self {
+        self.count = Some(Count::all());
         self
     }
 
@@ -2195,7 +2969,7 @@ impl ToRedisArgs for LposOptions {
     {
         if let Some(n) = self.count {
             out.write_arg(b"COUNT");
-            out.write_arg_fmt(n);
+            n.write_redis_args(out);
         }
 
         if let Some(n) = self.rank {
@@ -2214,6 +2988,243 @@ impl ToRedisArgs for LposOptions {
     }
 }
 
+/// Options for the [ZADD](https://redis.io/commands/zadd) command
+///
+/// The server refuses to combine `NX` with `XX`, `GT` or `LT`, and `GT` with
+/// `LT`.  Commands with such options fail with
+/// [`ClientError`](ErrorKind::ClientError) before they are sent, see
+/// [`validate`](ZAddOptions::validate).
+///
+/// # Example
+///
+/// ```rust,no_run
+/// use redis::{Commands, RedisResult, ZAddOptions};
+/// fn raise_high_score(con: &mut redis::Connection, player: &str, score: f64) -> RedisResult<bool> {
+///     // Only counts the member if its score was actually raised.
+///     let changed: usize = con.zadd_options("high_scores", player, score, ZAddOptions::default().gt().ch())?;
+///     Ok(changed == 1)
+/// }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct ZAddOptions {
+    nx: bool,
+    xx: bool,
+    gt: bool,
+    lt: bool,
+    ch: bool,
+}
+
+impl ZAddOptions {
+    /// Only add new members, never update existing ones.
+    pub fn nx(mut self) -> Self {
+        self.nx = true;
+        self
+    }
+
+    /// Only update existing members, never add new ones.
+    pub fn xx(mut self) -> Self {
+        self.xx = true;
+        self
+    }
+
+    /// Only update existing members if the new score is greater.
+    pub fn gt(mut self) -> Self {
+        self.gt = true;
+        self
+    }
+
+    /// Only update existing members if the new score is less.
+    pub fn lt(mut self) -> Self {
+        self.lt = true;
+        self
+    }
+
+    /// Return the number of changed members, including updated ones,
+    /// instead of the number of added members.
+    pub fn ch(mut self) -> Self {
+        self.ch = true;
+        self
+    }
+
+    /// Fails with [`ClientError`](ErrorKind::ClientError) if the options
+    /// combine flags that the server rejects.
+    pub fn validate(&self) -> RedisResult<()> {
+        match self.conflict() {
+            Some(problem) => fail!((
+                ErrorKind::ClientError,
+                "Incompatible options",
+                format!("ZADD: {}", problem)
+            )),
+            None => Ok(()),
+        }
+    }
+
+    fn conflict(&self) -> Option<&'static str> {
+        if self.nx && self.xx {
+            Some("XX and NX options at the same time are not compatible")
+        } else if (self.gt && self.lt) || (self.nx && (self.gt || self.lt)) {
+            Some("GT, LT, and/or NX options at the same time are not compatible")
+        } else {
+            None
+        }
+    }
+}
+
+impl ToRedisArgs for ZAddOptions {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        let flags: [(bool, &[u8]); 5] = [
+            (self.nx, b"NX"),
+            (self.xx, b"XX"),
+            (self.gt, b"GT"),
+            (self.lt, b"LT"),
+            (self.ch, b"CH"),
+        ];
+        for (set, flag) in flags.iter() {
+            if *set {
+                out.write_arg(flag);
+            }
+        }
+    }
+
+    fn is_single_arg(&self) -> bool {
+        false
+    }
+}
+
+// Maps the names passed to `COMMAND INFO` to whether the server returned
+// details for them, which it answers in the same order.
+fn command_support(commands: &[&str], reply: Value) -> RedisResult<HashMap<String, bool>> {
+    let details: Vec<Value> = from_redis_value(&reply)?;
+    if details.len() != commands.len() {
+        fail!((
+            ErrorKind::TypeError,
+            "Unexpected number of COMMAND INFO entries",
+            format!("expected {}, got {}", commands.len(), details.len())
+        ));
+    }
+    Ok(commands
+        .iter()
+        .zip(details)
+        .map(|(name, details)| (name.to_string(), details != Value::Nil))
+        .collect())
+}
+
+// `HELLO` with any other version than 2 would switch the connection to a
+// protocol its parser cannot read, as `Client::connect_and_probe` notes.
+fn unsupported_protover(protover: u8) -> Option<&'static str> {
+    if protover == 2 {
+        None
+    } else {
+        Some("only protocol version 2 is supported")
+    }
+}
+
+// `SET` has no `PERSIST` option and `GETEX` no `KEEPTTL` option, and the
+// server rejects them as syntax errors.
+fn unsupported_expiration(command: &str, expiration: Expiration) -> Option<&'static str> {
+    match (command, expiration) {
+        ("SET", Expiration::Persist) => Some("PERSIST is only supported by GETEX"),
+        ("GETEX", Expiration::Keep) => Some("KEEPTTL is only supported by SET"),
+        _ => None,
+    }
+}
+
+// Pipelines a write with a `WAIT` for `write_with_replication`.
+fn replication_pipeline(write: &Cmd, min_replicas: usize, timeout: Duration) -> RedisResult<Pipeline> {
+    write.check_args()?;
+    let mut pipe = Pipeline::new();
+    pipe.add_command(write.clone())
+        .add_command(Cmd::wait(min_replicas, ceil_millis(timeout) as usize));
+    Ok(pipe)
+}
+
+// Converts `duration` to whole milliseconds, rounding up so that short
+// durations do not turn into zero, which often means "forever".
+fn ceil_millis(duration: Duration) -> u64 {
+    let ms = duration.as_millis() as u64;
+    if duration > Duration::from_millis(ms) {
+        ms + 1
+    } else {
+        ms
+    }
+}
+
+fn replication_result<RV: FromRedisValue>(replies: Vec<Value>, min_replicas: usize) -> RedisResult<(RV, ReplicationOutcome)> {
+    let (reply, acked): (Value, usize) = from_redis_value(&Value::Bulk(replies))?;
+    Ok((from_redis_value(&reply)?, ReplicationOutcome::new(acked, min_replicas)))
+}
+
+// Runs a *STORE command and sets the TTL of its destination in one
+// transaction for `store_with_ttl`.
+fn store_with_ttl_pipeline<K: ToRedisArgs>(store: &Cmd, dest: K, ttl: Duration) -> RedisResult<Pipeline> {
+    store.check_args()?;
+    let ttl = store_ttl_millis(ttl)?;
+    let mut pipe = Pipeline::new();
+    pipe.atomic()
+        .add_command(store.clone())
+        .pexpire(dest, ttl as usize)
+        .ignore();
+    Ok(pipe)
+}
+
+// Converts the TTL of `store_with_ttl` to milliseconds.  A `PEXPIRE` with
+// zero would delete the stored result right away.
+fn store_ttl_millis(ttl: Duration) -> RedisResult<u64> {
+    if ttl == Duration::from_secs(0) {
+        fail!((ErrorKind::ClientError, "The TTL of a stored result must not be zero"));
+    }
+    Ok(ceil_millis(ttl))
+}
+
+// Calls the command given in ARGV[2..] and sets the TTL of KEYS[1] to
+// ARGV[1] milliseconds.
+const STORE_WITH_TTL_SCRIPT: &str = "\
+local count = redis.call(unpack(ARGV, 2))
+redis.call('PEXPIRE', KEYS[1], ARGV[1])
+return count";
+
+// The script version of `store_with_ttl_pipeline`, for connections that do
+// not support transactions.
+fn store_with_ttl_script<K: ToRedisArgs>(store: &Cmd, dest: K, ttl: Duration) -> RedisResult<Cmd> {
+    store.check_args()?;
+    let ttl = store_ttl_millis(ttl)?;
+    let mut script = cmd("EVAL");
+    script.arg(STORE_WITH_TTL_SCRIPT).arg(1).arg(dest).arg(ttl);
+    for arg in store.args_iter() {
+        if let crate::cmd::Arg::Simple(arg) = arg {
+            script.arg(arg);
+        }
+    }
+    Ok(script)
+}
+
+/// Enum for the BEFORE | AFTER args of [`linsert`][1]
+///
+/// [1]: trait.Commands.html#method.linsert
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Where {
+    /// Insert before the pivot element.
+    Before,
+    /// Insert after the pivot element.
+    After,
+}
+
+impl ToRedisArgs for Where {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        let s: &[u8] = match self {
+            Where::Before => b"BEFORE",
+            Where::After => b"AFTER",
+        };
+        out.write_arg(s);
+    }
+}
+
 /// Enum for the LEFT | RIGHT args used by some commands
 pub enum Direction {
     Left,
@@ -2232,3 +3243,95 @@ impl ToRedisArgs for Direction {
         out.write_arg(s);
     }
 }
+
+/// The new state of a hash slot, as set by [`cluster_setslot`][1].
+///
+/// [1]: trait.Commands.html#method.cluster_setslot
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum SlotState {
+    /// The slot is being imported from the node with the given id.
+    Importing(String),
+    /// The slot is being migrated to the node with the given id.
+    Migrating(String),
+    /// Clears any importing or migrating state of the slot.
+    Stable,
+    /// Assigns the slot to the node with the given id.
+    Node(String),
+}
+
+impl ToRedisArgs for SlotState {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        match self {
+            SlotState::Importing(node_id) => {
+                out.write_arg(b"IMPORTING");
+                out.write_arg(node_id.as_bytes());
+            }
+            SlotState::Migrating(node_id) => {
+                out.write_arg(b"MIGRATING");
+                out.write_arg(node_id.as_bytes());
+            }
+            SlotState::Stable => out.write_arg(b"STABLE"),
+            SlotState::Node(node_id) => {
+                out.write_arg(b"NODE");
+                out.write_arg(node_id.as_bytes());
+            }
+        }
+    }
+
+    fn is_single_arg(&self) -> bool {
+        false
+    }
+}
+
+/// Enum for the TIMEOUT | ERROR args of [`client_unblock`][1].
+///
+/// [1]: trait.Commands.html#method.client_unblock
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum UnblockKind {
+    /// The blocked command returns as if its timeout was reached, usually
+    /// with nil.
+    Timeout,
+    /// The blocked command fails with an `UNBLOCKED` error.
+    Error,
+}
+
+impl ToRedisArgs for UnblockKind {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        let s: &[u8] = match self {
+            UnblockKind::Timeout => b"TIMEOUT",
+            UnblockKind::Error => b"ERROR",
+        };
+        out.write_arg(s);
+    }
+}
+
+/// Enum for the FORCE | TAKEOVER args of [`cluster_failover_mode`][1].
+///
+/// [1]: trait.Commands.html#method.cluster_failover_mode
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum FailoverMode {
+    /// Fails over without the agreement of the unreachable master.
+    Force,
+    /// Fails over without the agreement of the master or of the rest of the
+    /// cluster.
+    Takeover,
+}
+
+impl ToRedisArgs for FailoverMode {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        let s: &[u8] = match self {
+            FailoverMode::Force => b"FORCE",
+            FailoverMode::Takeover => b"TAKEOVER",
+        };
+        out.write_arg(s);
+    }
+}
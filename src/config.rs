@@ -0,0 +1,677 @@
+//! Defines typed values for the `CONFIG SET` and `CONFIG GET` commands.
+//!
+//! [`ServerConfig`] renders common tunables to the exact strings the server
+//! expects, and parses them back from the name/value pairs returned by
+//! `CONFIG GET`.
+//!
+//! ```rust,no_run
+//! # fn do_something() -> redis::RedisResult<()> {
+//! use redis::Commands;
+//! use redis::config::{ByteSize, MaxmemoryPolicy, NotifyFlags, ServerConfig};
+//!
+//! let client = redis::Client::open("redis://127.0.0.1/")?;
+//! let mut con = client.get_connection()?;
+//! let config = ServerConfig::new()
+//!     .maxmemory(ByteSize::mib(100))
+//!     .maxmemory_policy(MaxmemoryPolicy::AllkeysLru)
+//!     .notify_keyspace_events(NotifyFlags::EXPIRED | NotifyFlags::KEYEVENT);
+//! for (parameter, value) in config.iter() {
+//!     let () = con.config_set(parameter, value)?;
+//! }
+//!
+//! let current: ServerConfig = con.config_get("maxmemory-policy")?;
+//! assert_eq!(current.get_maxmemory_policy(), Some(MaxmemoryPolicy::AllkeysLru));
+//! # Ok(()) }
+//! ```
+
+use std::fmt;
+use std::ops::{BitOr, BitOrAssign};
+use std::time::Duration;
+
+use crate::cmd::cmd;
+use crate::connection::ConnectionLike;
+use crate::types::{
+    ErrorKind, FromRedisValue, RedisError, RedisResult, RedisWrite, ToRedisArgs, Value,
+};
+
+macro_rules! not_convertible_error {
+    ($v:expr, $det:expr) => {
+        RedisError::from((
+            ErrorKind::TypeError,
+            "Response type not convertible",
+            format!("{:?} (response was {:?})", $det, $v),
+        ))
+    };
+}
+
+/// A memory size, such as the value of `maxmemory`.
+///
+/// The server reports sizes in bytes, which is also how they are sent.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ByteSize(pub u64);
+
+impl ByteSize {
+    /// A size of `n` bytes.
+    pub const fn bytes(n: u64) -> ByteSize {
+        ByteSize(n)
+    }
+
+    /// A size of `n` kibibytes, which the server calls `kb`.
+    pub const fn kib(n: u64) -> ByteSize {
+        ByteSize(n * 1024)
+    }
+
+    /// A size of `n` mebibytes, which the server calls `mb`.
+    pub const fn mib(n: u64) -> ByteSize {
+        ByteSize(n * 1024 * 1024)
+    }
+
+    /// A size of `n` gibibytes, which the server calls `gb`.
+    pub const fn gib(n: u64) -> ByteSize {
+        ByteSize(n * 1024 * 1024 * 1024)
+    }
+
+    /// Returns the size in bytes.
+    pub const fn as_u64(self) -> u64 {
+        self.0
+    }
+
+    // Accepts the units of the redis.conf format, where `k` is 1000 bytes
+    // and `kb` is 1024 bytes.
+    fn parse(s: &str) -> Option<ByteSize> {
+        let s = s.trim().to_ascii_lowercase();
+        let digits = s.bytes().take_while(u8::is_ascii_digit).count();
+        let (number, unit) = s.split_at(digits);
+        let multiplier = match unit {
+            "" | "b" => 1,
+            "k" => 1000,
+            "kb" => 1024,
+            "m" => 1000 * 1000,
+            "mb" => 1024 * 1024,
+            "g" => 1000 * 1000 * 1000,
+            "gb" => 1024 * 1024 * 1024,
+            _ => return None,
+        };
+        number
+            .parse::<u64>()
+            .ok()
+            .and_then(|n| n.checked_mul(multiplier))
+            .map(ByteSize)
+    }
+}
+
+impl fmt::Display for ByteSize {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}", self.0)
+    }
+}
+
+impl ToRedisArgs for ByteSize {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        self.0.write_redis_args(out)
+    }
+}
+
+impl FromRedisValue for ByteSize {
+    fn from_redis_value(v: &Value) -> RedisResult<Self> {
+        if let Value::Int(n) = *v {
+            if n >= 0 {
+                return Ok(ByteSize(n as u64));
+            }
+        }
+        let s: String = FromRedisValue::from_redis_value(v)?;
+        ByteSize::parse(&s).ok_or_else(|| not_convertible_error!(v, "Invalid byte size"))
+    }
+}
+
+/// The eviction policy set with `maxmemory-policy`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum MaxmemoryPolicy {
+    /// Evict the least recently used keys with an expire set.
+    VolatileLru,
+    /// Evict the least recently used keys.
+    AllkeysLru,
+    /// Evict the least frequently used keys with an expire set.
+    VolatileLfu,
+    /// Evict the least frequently used keys.
+    AllkeysLfu,
+    /// Evict random keys with an expire set.
+    VolatileRandom,
+    /// Evict random keys.
+    AllkeysRandom,
+    /// Evict the keys with an expire set that are closest to expiring.
+    VolatileTtl,
+    /// Evict nothing and refuse writes once the limit is reached.
+    Noeviction,
+}
+
+impl MaxmemoryPolicy {
+    /// Returns the name of the policy as the server spells it.
+    pub fn as_str(self) -> &'static str {
+        match self {
+            MaxmemoryPolicy::VolatileLru => "volatile-lru",
+            MaxmemoryPolicy::AllkeysLru => "allkeys-lru",
+            MaxmemoryPolicy::VolatileLfu => "volatile-lfu",
+            MaxmemoryPolicy::AllkeysLfu => "allkeys-lfu",
+            MaxmemoryPolicy::VolatileRandom => "volatile-random",
+            MaxmemoryPolicy::AllkeysRandom => "allkeys-random",
+            MaxmemoryPolicy::VolatileTtl => "volatile-ttl",
+            MaxmemoryPolicy::Noeviction => "noeviction",
+        }
+    }
+
+    /// Returns whether the policy evicts by access frequency, in which case
+    /// the server tracks `OBJECT FREQ` instead of `OBJECT IDLETIME`.
+    pub fn is_lfu(self) -> bool {
+        matches!(
+            self,
+            MaxmemoryPolicy::VolatileLfu | MaxmemoryPolicy::AllkeysLfu
+        )
+    }
+
+    fn parse(s: &str) -> Option<MaxmemoryPolicy> {
+        Some(match &s.to_ascii_lowercase()[..] {
+            "volatile-lru" => MaxmemoryPolicy::VolatileLru,
+            "allkeys-lru" => MaxmemoryPolicy::AllkeysLru,
+            "volatile-lfu" => MaxmemoryPolicy::VolatileLfu,
+            "allkeys-lfu" => MaxmemoryPolicy::AllkeysLfu,
+            "volatile-random" => MaxmemoryPolicy::VolatileRandom,
+            "allkeys-random" => MaxmemoryPolicy::AllkeysRandom,
+            "volatile-ttl" => MaxmemoryPolicy::VolatileTtl,
+            "noeviction" => MaxmemoryPolicy::Noeviction,
+            _ => return None,
+        })
+    }
+}
+
+impl ToRedisArgs for MaxmemoryPolicy {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        out.write_arg(self.as_str().as_bytes())
+    }
+}
+
+impl FromRedisValue for MaxmemoryPolicy {
+    fn from_redis_value(v: &Value) -> RedisResult<Self> {
+        let s: String = FromRedisValue::from_redis_value(v)?;
+        MaxmemoryPolicy::parse(&s)
+            .ok_or_else(|| not_convertible_error!(v, "Unknown maxmemory policy"))
+    }
+}
+
+/// The classes of keyspace notifications set with `notify-keyspace-events`.
+///
+/// Notifications are only sent if `KEYSPACE` or `KEYEVENT` is set together
+/// with at least one class of events.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct NotifyFlags(u16);
+
+// The flag letters in the order the server prints them.
+const NOTIFY_LETTERS: [(NotifyFlags, char); 14] = [
+    (NotifyFlags::GENERIC, 'g'),
+    (NotifyFlags::STRING, '$'),
+    (NotifyFlags::LIST, 'l'),
+    (NotifyFlags::SET, 's'),
+    (NotifyFlags::HASH, 'h'),
+    (NotifyFlags::ZSET, 'z'),
+    (NotifyFlags::EXPIRED, 'x'),
+    (NotifyFlags::EVICTED, 'e'),
+    (NotifyFlags::STREAM, 't'),
+    (NotifyFlags::MODULE, 'd'),
+    (NotifyFlags::KEYSPACE, 'K'),
+    (NotifyFlags::KEYEVENT, 'E'),
+    (NotifyFlags::KEY_MISS, 'm'),
+    (NotifyFlags::NEW, 'n'),
+];
+
+impl NotifyFlags {
+    /// No notifications, which disables them.
+    pub const NONE: NotifyFlags = NotifyFlags(0);
+    /// Keyspace events, published in `__keyspace@<db>__` (`K`).
+    pub const KEYSPACE: NotifyFlags = NotifyFlags(1);
+    /// Keyevent events, published in `__keyevent@<db>__` (`E`).
+    pub const KEYEVENT: NotifyFlags = NotifyFlags(1 << 1);
+    /// Generic commands like `DEL`, `EXPIRE` and `RENAME` (`g`).
+    pub const GENERIC: NotifyFlags = NotifyFlags(1 << 2);
+    /// String commands (`$`).
+    pub const STRING: NotifyFlags = NotifyFlags(1 << 3);
+    /// List commands (`l`).
+    pub const LIST: NotifyFlags = NotifyFlags(1 << 4);
+    /// Set commands (`s`).
+    pub const SET: NotifyFlags = NotifyFlags(1 << 5);
+    /// Hash commands (`h`).
+    pub const HASH: NotifyFlags = NotifyFlags(1 << 6);
+    /// Sorted set commands (`z`).
+    pub const ZSET: NotifyFlags = NotifyFlags(1 << 7);
+    /// Expired events, sent every time a key expires (`x`).
+    pub const EXPIRED: NotifyFlags = NotifyFlags(1 << 8);
+    /// Evicted events, sent when a key is evicted for `maxmemory` (`e`).
+    pub const EVICTED: NotifyFlags = NotifyFlags(1 << 9);
+    /// Stream commands (`t`).
+    pub const STREAM: NotifyFlags = NotifyFlags(1 << 10);
+    /// Module key type events (`d`).
+    pub const MODULE: NotifyFlags = NotifyFlags(1 << 11);
+    /// Key miss events, sent when a key that does not exist is accessed
+    /// (`m`).  Not included in `ALL`.
+    pub const KEY_MISS: NotifyFlags = NotifyFlags(1 << 12);
+    /// New key events (`n`).  Not included in `ALL`.
+    pub const NEW: NotifyFlags = NotifyFlags(1 << 13);
+    /// All classes of events except `KEY_MISS` and `NEW` (`A`).
+    pub const ALL: NotifyFlags = NotifyFlags(0b1111_1111_1100);
+
+    /// Returns true if all flags of `other` are set.
+    pub const fn contains(self, other: NotifyFlags) -> bool {
+        self.0 & other.0 == other.0
+    }
+
+    /// Returns true if no flag is set.
+    pub const fn is_empty(self) -> bool {
+        self.0 == 0
+    }
+
+    fn parse(s: &str) -> Option<NotifyFlags> {
+        let mut flags = NotifyFlags::NONE;
+        for letter in s.chars() {
+            flags |= if letter == 'A' {
+                NotifyFlags::ALL
+            } else {
+                NOTIFY_LETTERS.iter().find(|(_, l)| *l == letter)?.0
+            };
+        }
+        Some(flags)
+    }
+}
+
+impl BitOr for NotifyFlags {
+    type Output = NotifyFlags;
+
+    fn bitor(self, rhs: NotifyFlags) -> NotifyFlags {
+        NotifyFlags(self.0 | rhs.0)
+    }
+}
+
+impl BitOrAssign for NotifyFlags {
+    fn bitor_assign(&mut self, rhs: NotifyFlags) {
+        self.0 |= rhs.0;
+    }
+}
+
+impl fmt::Display for NotifyFlags {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        for (flag, letter) in NOTIFY_LETTERS.iter() {
+            if self.contains(*flag) {
+                write!(f, "{}", letter)?;
+            }
+        }
+        Ok(())
+    }
+}
+
+impl ToRedisArgs for NotifyFlags {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        out.write_arg(self.to_string().as_bytes())
+    }
+}
+
+impl FromRedisValue for NotifyFlags {
+    fn from_redis_value(v: &Value) -> RedisResult<Self> {
+        let s: String = FromRedisValue::from_redis_value(v)?;
+        NotifyFlags::parse(&s)
+            .ok_or_else(|| not_convertible_error!(v, "Unknown keyspace notification flag"))
+    }
+}
+
+/// The result of [`notify_config_check`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct NotifyConfigReport {
+    /// The flags currently set in `notify-keyspace-events`.
+    pub configured: NotifyFlags,
+    /// The required flags that are not set.
+    pub missing: NotifyFlags,
+}
+
+impl NotifyConfigReport {
+    fn new(configured: NotifyFlags, required: NotifyFlags) -> NotifyConfigReport {
+        NotifyConfigReport {
+            configured,
+            missing: NotifyFlags(required.0 & !configured.0),
+        }
+    }
+
+    /// Returns true if all required flags are set.
+    pub fn is_ok(&self) -> bool {
+        self.missing.is_empty()
+    }
+}
+
+/// Checks that `notify-keyspace-events` includes the `required` flags.
+///
+/// The server sends keyspace notifications only for the classes of events
+/// that are configured, and by default none are.  Subscriptions to events
+/// that are not configured silently receive nothing, so this is worth
+/// checking before subscribing.  `required` should include `KEYSPACE` or
+/// `KEYEVENT` depending on the channels subscribed to.
+///
+/// ```rust,no_run
+/// # fn do_something() -> redis::RedisResult<()> {
+/// use redis::config::{notify_config_check, NotifyFlags};
+///
+/// let client = redis::Client::open("redis://127.0.0.1/")?;
+/// let mut con = client.get_connection()?;
+/// let report = notify_config_check(&mut con, NotifyFlags::KEYEVENT | NotifyFlags::EXPIRED)?;
+/// if !report.is_ok() {
+///     eprintln!("notify-keyspace-events lacks {}", report.missing);
+/// }
+/// # Ok(()) }
+/// ```
+pub fn notify_config_check<C: ConnectionLike>(
+    con: &mut C,
+    required: NotifyFlags,
+) -> RedisResult<NotifyConfigReport> {
+    let config: ServerConfig = cmd("CONFIG")
+        .arg("GET")
+        .arg("notify-keyspace-events")
+        .query(con)?;
+    let configured = config.get_notify_keyspace_events().unwrap_or_default();
+    Ok(NotifyConfigReport::new(configured, required))
+}
+
+/// A set of configuration parameters for `CONFIG SET`.
+///
+/// The parameters are kept in the order they were set.  All of them can be
+/// sent at once with [`config_set_multiple`](crate::Commands::config_set_multiple),
+/// which needs Redis 7.0, or one by one by iterating over them.
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct ServerConfig {
+    parameters: Vec<(&'static str, String)>,
+}
+
+impl ServerConfig {
+    /// Creates an empty set of parameters.
+    pub fn new() -> ServerConfig {
+        ServerConfig::default()
+    }
+
+    /// Sets the memory limit (`maxmemory`), `0` means no limit.
+    pub fn maxmemory(self, size: ByteSize) -> Self {
+        self.set("maxmemory", size.to_string())
+    }
+
+    /// Sets the eviction policy (`maxmemory-policy`).
+    pub fn maxmemory_policy(self, policy: MaxmemoryPolicy) -> Self {
+        self.set("maxmemory-policy", policy.as_str().to_string())
+    }
+
+    /// Sets the keyspace notifications to publish (`notify-keyspace-events`).
+    pub fn notify_keyspace_events(self, flags: NotifyFlags) -> Self {
+        self.set("notify-keyspace-events", flags.to_string())
+    }
+
+    /// Enables or disables the append only file (`appendonly`).
+    pub fn appendonly(self, enabled: bool) -> Self {
+        self.set("appendonly", if enabled { "yes" } else { "no" }.to_string())
+    }
+
+    /// Sets the time after which idle clients are disconnected (`timeout`),
+    /// with a precision of seconds.  A zero duration disables it.
+    pub fn timeout(self, timeout: Duration) -> Self {
+        self.set("timeout", timeout.as_secs().to_string())
+    }
+
+    /// Sets the interval of TCP keepalive probes (`tcp-keepalive`), with a
+    /// precision of seconds.  A zero duration disables them.
+    pub fn tcp_keepalive(self, interval: Duration) -> Self {
+        self.set("tcp-keepalive", interval.as_secs().to_string())
+    }
+
+    /// Returns the memory limit, if it is part of the set.
+    pub fn get_maxmemory(&self) -> Option<ByteSize> {
+        self.get("maxmemory").and_then(ByteSize::parse)
+    }
+
+    /// Returns the eviction policy, if it is part of the set.
+    pub fn get_maxmemory_policy(&self) -> Option<MaxmemoryPolicy> {
+        self.get("maxmemory-policy")
+            .and_then(MaxmemoryPolicy::parse)
+    }
+
+    /// Returns the keyspace notifications, if they are part of the set.
+    pub fn get_notify_keyspace_events(&self) -> Option<NotifyFlags> {
+        self.get("notify-keyspace-events")
+            .and_then(NotifyFlags::parse)
+    }
+
+    /// Returns whether the append only file is enabled, if it is part of the
+    /// set.
+    pub fn get_appendonly(&self) -> Option<bool> {
+        self.get("appendonly").and_then(parse_yes_no)
+    }
+
+    /// Returns the idle client timeout, if it is part of the set.
+    pub fn get_timeout(&self) -> Option<Duration> {
+        self.get("timeout").and_then(parse_seconds)
+    }
+
+    /// Returns the interval of TCP keepalive probes, if it is part of the
+    /// set.
+    pub fn get_tcp_keepalive(&self) -> Option<Duration> {
+        self.get("tcp-keepalive").and_then(parse_seconds)
+    }
+
+    /// Returns the parameters and their values as they are sent.
+    pub fn iter(&self) -> impl Iterator<Item = (&'static str, &str)> + '_ {
+        self.parameters
+            .iter()
+            .map(|(parameter, value)| (*parameter, &value[..]))
+    }
+
+    fn get(&self, parameter: &str) -> Option<&str> {
+        self.iter()
+            .find(|(p, _)| *p == parameter)
+            .map(|(_, value)| value)
+    }
+
+    // Setting a parameter twice keeps the last value.
+    fn set(mut self, parameter: &'static str, value: String) -> Self {
+        self.parameters.retain(|(p, _)| *p != parameter);
+        self.parameters.push((parameter, value));
+        self
+    }
+}
+
+// Checks whether a value of a parameter can be parsed.
+type Validator = fn(&str) -> bool;
+
+// The parameters covered by `ServerConfig` and how their values are checked.
+const PARAMETERS: [(&str, Validator); 6] = [
+    ("maxmemory", |v| ByteSize::parse(v).is_some()),
+    ("maxmemory-policy", |v| MaxmemoryPolicy::parse(v).is_some()),
+    ("notify-keyspace-events", |v| {
+        NotifyFlags::parse(v).is_some()
+    }),
+    ("appendonly", |v| parse_yes_no(v).is_some()),
+    ("timeout", |v| parse_seconds(v).is_some()),
+    ("tcp-keepalive", |v| parse_seconds(v).is_some()),
+];
+
+fn parse_yes_no(s: &str) -> Option<bool> {
+    match &s.to_ascii_lowercase()[..] {
+        "yes" => Some(true),
+        "no" => Some(false),
+        _ => None,
+    }
+}
+
+fn parse_seconds(s: &str) -> Option<Duration> {
+    s.trim().parse().ok().map(Duration::from_secs)
+}
+
+/// Parses the name/value pairs returned by `CONFIG GET`, keeping the
+/// parameters that can be read with the getters of `ServerConfig`.
+impl FromRedisValue for ServerConfig {
+    fn from_redis_value(v: &Value) -> RedisResult<Self> {
+        let pairs: Vec<(String, String)> = FromRedisValue::from_redis_value(v)?;
+        let mut parameters = Vec::new();
+        for (name, value) in pairs {
+            let known = PARAMETERS
+                .iter()
+                .find(|(parameter, _)| name.eq_ignore_ascii_case(parameter));
+            if let Some((parameter, valid)) = known {
+                if !valid(&value) {
+                    return Err(not_convertible_error!(
+                        v,
+                        format!("Invalid value of {}", parameter)
+                    ));
+                }
+                parameters.push((*parameter, value));
+            }
+        }
+        Ok(ServerConfig { parameters })
+    }
+}
+
+impl ToRedisArgs for ServerConfig {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        for (parameter, value) in self.iter() {
+            out.write_arg(parameter.as_bytes());
+            out.write_arg(value.as_bytes());
+        }
+    }
+
+    fn is_single_arg(&self) -> bool {
+        false
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_byte_size() {
+        assert_eq!(ByteSize::mib(100).to_string(), "104857600");
+        for (s, size) in &[
+            ("0", 0),
+            ("104857600", 104_857_600),
+            ("1k", 1000),
+            ("1kb", 1024),
+            ("2MB", 2 * 1024 * 1024),
+            ("3g", 3_000_000_000),
+        ] {
+            let v = Value::Data(s.as_bytes().to_vec());
+            assert_eq!(ByteSize::from_redis_value(&v), Ok(ByteSize(*size)));
+        }
+        assert!(ByteSize::from_redis_value(&Value::Data(b"1tb".to_vec())).is_err());
+        assert!(ByteSize::from_redis_value(&Value::Data(b"-1".to_vec())).is_err());
+    }
+
+    #[test]
+    fn test_maxmemory_policy() {
+        for policy in &[
+            MaxmemoryPolicy::VolatileLru,
+            MaxmemoryPolicy::AllkeysLru,
+            MaxmemoryPolicy::VolatileLfu,
+            MaxmemoryPolicy::AllkeysLfu,
+            MaxmemoryPolicy::VolatileRandom,
+            MaxmemoryPolicy::AllkeysRandom,
+            MaxmemoryPolicy::VolatileTtl,
+            MaxmemoryPolicy::Noeviction,
+        ] {
+            let v = Value::Data(policy.to_redis_args().remove(0));
+            assert_eq!(MaxmemoryPolicy::from_redis_value(&v), Ok(*policy));
+        }
+        assert!(MaxmemoryPolicy::from_redis_value(&Value::Data(b"lru".to_vec())).is_err());
+    }
+
+    #[test]
+    fn test_notify_flags() {
+        let flags = NotifyFlags::EXPIRED | NotifyFlags::KEYEVENT;
+        assert_eq!(flags.to_string(), "xE");
+        assert_eq!(NotifyFlags::ALL.to_string(), "g$lshzxetd");
+        assert_eq!(NotifyFlags::NONE.to_string(), "");
+
+        let parse = |s: &[u8]| NotifyFlags::from_redis_value(&Value::Data(s.to_vec()));
+        assert_eq!(parse(b"xE"), Ok(flags));
+        assert_eq!(
+            parse(b"AKE"),
+            Ok(NotifyFlags::ALL | NotifyFlags::KEYSPACE | NotifyFlags::KEYEVENT)
+        );
+        assert_eq!(parse(b""), Ok(NotifyFlags::NONE));
+        assert!(parse(b"xq").is_err());
+    }
+
+    #[test]
+    fn test_notify_config_report() {
+        let required = NotifyFlags::KEYEVENT | NotifyFlags::EXPIRED;
+        let report = NotifyConfigReport::new(NotifyFlags::NONE, required);
+        assert!(!report.is_ok());
+        assert_eq!(report.missing, required);
+
+        let report = NotifyConfigReport::new(NotifyFlags::ALL | NotifyFlags::KEYSPACE, required);
+        assert_eq!(report.missing, NotifyFlags::KEYEVENT);
+
+        let configured = NotifyFlags::ALL | NotifyFlags::KEYEVENT;
+        let report = NotifyConfigReport::new(configured, required);
+        assert!(report.is_ok());
+        assert_eq!(report.configured, configured);
+    }
+
+    #[test]
+    fn test_server_config() {
+        let config = ServerConfig::new()
+            .maxmemory(ByteSize::kib(1))
+            .appendonly(true)
+            .timeout(Duration::from_millis(2500))
+            .maxmemory(ByteSize::kib(2));
+        assert_eq!(
+            config.to_redis_args(),
+            vec![
+                b"appendonly".to_vec(),
+                b"yes".to_vec(),
+                b"timeout".to_vec(),
+                b"2".to_vec(),
+                b"maxmemory".to_vec(),
+                b"2048".to_vec(),
+            ]
+        );
+
+        let reply = Value::Bulk(
+            [
+                "appendonly",
+                "yes",
+                "timeout",
+                "2",
+                "hz",
+                "10",
+                "maxmemory",
+                "2048",
+            ]
+            .iter()
+            .map(|s| Value::Data(s.as_bytes().to_vec()))
+            .collect(),
+        );
+        let parsed = ServerConfig::from_redis_value(&reply).unwrap();
+        assert_eq!(parsed, config);
+        assert_eq!(parsed.get_maxmemory(), Some(ByteSize::kib(2)));
+        assert_eq!(parsed.get_appendonly(), Some(true));
+        assert_eq!(parsed.get_timeout(), Some(Duration::from_secs(2)));
+        assert_eq!(parsed.get_maxmemory_policy(), None);
+
+        let reply = Value::Bulk(vec![
+            Value::Data(b"appendonly".to_vec()),
+            Value::Data(b"maybe".to_vec()),
+        ]);
+        assert!(ServerConfig::from_redis_value(&reply).is_err());
+    }
+}
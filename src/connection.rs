@@ -6,8 +6,9 @@ use std::path::PathBuf;
 use std::str::{from_utf8, FromStr};
 use std::time::Duration;
 
-use crate::cmd::{cmd, pipe, Cmd};
-use crate::parser::Parser;
+use crate::cmd::{cmd, pipe, Cmd, CommandNamePolicy};
+use crate::hook::DebugHook;
+use crate::parser::{Frame, Parser};
 use crate::pipeline::Pipeline;
 use crate::types::{
     from_redis_value, ErrorKind, FromRedisValue, RedisError, RedisResult, ToRedisArgs, Value,
@@ -16,6 +17,8 @@ use crate::types::{
 #[cfg(unix)]
 use std::os::unix::net::UnixStream;
 
+use socket2::{SockRef, TcpKeepalive};
+
 #[cfg(feature = "tls")]
 use native_tls::{TlsConnector, TlsStream};
 
@@ -98,6 +101,11 @@ pub struct ConnectionInfo {
 
     /// A boxed connection address for where to connect to.
     pub redis: RedisConnectionInfo,
+
+    /// Idle time after which the OS starts sending TCP keepalive probes.
+    ///
+    /// `None` leaves `SO_KEEPALIVE` untouched.  This is ignored for unix sockets.
+    pub tcp_keepalive: Option<Duration>,
 }
 
 /// Redis specific/connection independent information used to establish a connection to redis.
@@ -109,6 +117,48 @@ pub struct RedisConnectionInfo {
     pub username: Option<String>,
     /// Optionally a password that should be used for connection.
     pub password: Option<String>,
+    /// Interval of inactivity after which async multiplexed connections send an
+    /// out-of-band `PING` to the server.
+    ///
+    /// If the `PING` fails or does not complete within the same interval, the
+    /// connection is considered broken and closed.  This is ignored by the
+    /// synchronous connection.
+    pub ping_interval: Option<Duration>,
+    /// Whether to ask the server for the id of every new connection with
+    /// `CLIENT ID` while connecting, which makes it available as
+    /// [`Connection::client_id`].  Needs Redis 5.0.
+    pub fetch_client_id: bool,
+    /// How commands named with several words, like `cmd("CONFIG GET")`, are
+    /// handled by connections.  Cluster connections always split them.
+    pub command_name_policy: CommandNamePolicy,
+}
+
+impl ConnectionInfo {
+    /// Sets [`tcp_keepalive`](Self::tcp_keepalive).
+    pub fn with_tcp_keepalive(mut self, idle: Option<Duration>) -> Self {
+        self.tcp_keepalive = idle;
+        self
+    }
+}
+
+impl RedisConnectionInfo {
+    /// Sets [`ping_interval`](Self::ping_interval).
+    pub fn with_ping_interval(mut self, interval: Option<Duration>) -> Self {
+        self.ping_interval = interval;
+        self
+    }
+
+    /// Sets [`fetch_client_id`](Self::fetch_client_id).
+    pub fn with_fetch_client_id(mut self, fetch: bool) -> Self {
+        self.fetch_client_id = fetch;
+        self
+    }
+
+    /// Sets [`command_name_policy`](Self::command_name_policy).
+    pub fn with_command_name_policy(mut self, policy: CommandNamePolicy) -> Self {
+        self.command_name_policy = policy;
+        self
+    }
 }
 
 impl FromStr for ConnectionInfo {
@@ -150,6 +200,7 @@ where
         Ok(ConnectionInfo {
             addr: ConnectionAddr::Tcp(self.0.into(), self.1),
             redis: RedisConnectionInfo::default(),
+            tcp_keepalive: None,
         })
     }
 }
@@ -165,6 +216,8 @@ impl IntoConnectionInfo for String {
 
 fn url_to_tcp_connection_info(url: url::Url) -> RedisResult<ConnectionInfo> {
     let host = match url.host() {
+        // Unlike `to_string`, this strips the brackets around IPv6 addresses.
+        Some(url::Host::Ipv6(addr)) => addr.to_string(),
         Some(host) => host.to_string(),
         None => fail!((ErrorKind::InvalidClientConfig, "Missing hostname")),
     };
@@ -229,7 +282,11 @@ fn url_to_tcp_connection_info(url: url::Url) -> RedisResult<ConnectionInfo> {
                 },
                 None => None,
             },
+            ping_interval: None,
+            fetch_client_id: false,
+            command_name_policy: CommandNamePolicy::Split,
         },
+        tcp_keepalive: None,
     })
 }
 
@@ -251,7 +308,11 @@ fn url_to_unix_connection_info(url: url::Url) -> RedisResult<ConnectionInfo> {
             },
             username: query.get("user").map(|username| username.to_string()),
             password: query.get("pass").map(|password| password.to_string()),
+            ping_interval: None,
+            fetch_client_id: false,
+            command_name_policy: CommandNamePolicy::Split,
         },
+        tcp_keepalive: None,
     })
 }
 
@@ -312,6 +373,12 @@ pub struct Connection {
     /// This flag is checked when attempting to send a command, and if it's raised, we attempt to
     /// exit the pubsub state before executing the new request.
     pubsub: bool,
+
+    debug_hook: Option<DebugHook>,
+
+    client_id: Option<i64>,
+
+    command_name_policy: CommandNamePolicy,
 }
 
 /// Represents a pubsub connection.
@@ -319,16 +386,79 @@ pub struct PubSub<'a> {
     con: &'a mut Connection,
 }
 
-/// Represents a pubsub message.
-#[derive(Debug)]
-pub struct Msg {
-    payload: Value,
-    channel: Value,
-    pattern: Option<Value>,
+/// Represents a frame received on a pubsub connection.
+///
+/// Besides published messages this includes the confirmations the server
+/// sends for every channel that was subscribed to or unsubscribed from,
+/// which are only returned by [`PubSub::get_frame`] and the `on_frame`
+/// streams of the async `PubSub`.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Msg {
+    /// A message published to a subscribed channel.
+    Message {
+        /// The channel the message was published to.
+        channel: Value,
+        /// The message itself.
+        payload: Value,
+    },
+    /// A message published to a channel matching a subscribed pattern.
+    PMessage {
+        /// The pattern the channel matched.
+        pattern: Value,
+        /// The channel the message was published to.
+        channel: Value,
+        /// The message itself.
+        payload: Value,
+    },
+    /// A message published to a subscribed shard channel.
+    SMessage {
+        /// The shard channel the message was published to.
+        channel: Value,
+        /// The message itself.
+        payload: Value,
+    },
+    /// Confirms a subscription.
+    Subscribed {
+        /// Whether a channel, pattern or shard channel was subscribed to.
+        kind: SubscriptionKind,
+        /// The channel or pattern that was subscribed to.
+        channel: Value,
+        /// The number of subscriptions of the connection afterwards.
+        count: usize,
+    },
+    /// Confirms an unsubscription.
+    Unsubscribed {
+        /// Whether a channel, pattern or shard channel was unsubscribed from.
+        kind: SubscriptionKind,
+        /// The channel or pattern that was unsubscribed from.  This is `nil`
+        /// if the connection had no subscriptions to remove.
+        channel: Value,
+        /// The number of subscriptions of the connection afterwards.
+        count: usize,
+    },
+    /// Any other frame, e.g. the reply to a `PING` sent while subscribed.
+    Other(Value),
+}
+
+/// The kind of subscription a [`Msg::Subscribed`] or [`Msg::Unsubscribed`]
+/// confirmation refers to.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum SubscriptionKind {
+    /// A channel (`SUBSCRIBE`).
+    Channel,
+    /// A pattern (`PSUBSCRIBE`).
+    Pattern,
+    /// A shard channel (`SSUBSCRIBE`).
+    Shard,
 }
 
 impl ActualConnection {
-    pub fn new(addr: &ConnectionAddr, timeout: Option<Duration>) -> RedisResult<ActualConnection> {
+    pub fn new(
+        addr: &ConnectionAddr,
+        timeout: Option<Duration>,
+        tcp_keepalive: Option<Duration>,
+    ) -> RedisResult<ActualConnection> {
         Ok(match *addr {
             ConnectionAddr::Tcp(ref host, ref port) => {
                 let host: &str = &*host;
@@ -362,6 +492,7 @@ impl ActualConnection {
                         }
                     }
                 };
+                set_tcp_keepalive(SockRef::from(&tcp), tcp_keepalive)?;
                 ActualConnection::Tcp(TcpConnection {
                     reader: tcp,
                     open: true,
@@ -386,6 +517,7 @@ impl ActualConnection {
                 let tls = match timeout {
                     None => {
                         let tcp = TcpStream::connect((host, port))?;
+                        set_tcp_keepalive(SockRef::from(&tcp), tcp_keepalive)?;
                         match tls_connector.connect(host, tcp) {
                             Ok(res) => res,
                             Err(e) => {
@@ -408,7 +540,10 @@ impl ActualConnection {
                             };
                         }
                         match (tcp, last_error) {
-                            (Some(tcp), _) => tls_connector.connect(host, tcp).unwrap(),
+                            (Some(tcp), _) => {
+                                set_tcp_keepalive(SockRef::from(&tcp), tcp_keepalive)?;
+                                tls_connector.connect(host, tcp).unwrap()
+                            }
                             (None, Some(e)) => {
                                 fail!(e);
                             }
@@ -450,44 +585,28 @@ impl ActualConnection {
     }
 
     pub fn send_bytes(&mut self, bytes: &[u8]) -> RedisResult<Value> {
-        match *self {
-            ActualConnection::Tcp(ref mut connection) => {
-                let res = connection.reader.write_all(bytes).map_err(RedisError::from);
-                match res {
-                    Err(e) => {
-                        if e.is_connection_dropped() {
-                            connection.open = false;
-                        }
-                        Err(e)
-                    }
-                    Ok(_) => Ok(Value::Okay),
-                }
-            }
+        let result = match *self {
+            ActualConnection::Tcp(ref mut connection) => write_fully(&mut connection.reader, bytes),
+            // The TLS stream may already have encrypted and sent a part of
+            // the buffer even if it reports that nothing was written.
             #[cfg(feature = "tls")]
             ActualConnection::TcpTls(ref mut connection) => {
-                let res = connection.reader.write_all(bytes).map_err(RedisError::from);
-                match res {
-                    Err(e) => {
-                        if e.is_connection_dropped() {
-                            connection.open = false;
-                        }
-                        Err(e)
-                    }
-                    Ok(_) => Ok(Value::Okay),
-                }
+                write_fully(&mut connection.reader, bytes).map_err(|(_, err)| (true, err))
             }
             #[cfg(unix)]
-            ActualConnection::Unix(ref mut connection) => {
-                let result = connection.sock.write_all(bytes).map_err(RedisError::from);
-                match result {
-                    Err(e) => {
-                        if e.is_connection_dropped() {
-                            connection.open = false;
-                        }
-                        Err(e)
-                    }
-                    Ok(_) => Ok(Value::Okay),
+            ActualConnection::Unix(ref mut connection) => write_fully(&mut connection.sock, bytes),
+        };
+        match result {
+            Ok(()) => Ok(Value::Okay),
+            Err((partial, err)) => {
+                let err = RedisError::from(err);
+                // The server would take whatever is sent next for the rest of
+                // a partially written command, so the connection cannot be
+                // used anymore.
+                if partial || err.is_connection_dropped() {
+                    self.shutdown();
                 }
+                Err(err)
             }
         }
     }
@@ -526,6 +645,25 @@ impl ActualConnection {
         Ok(())
     }
 
+    pub fn shutdown(&mut self) {
+        match *self {
+            ActualConnection::Tcp(ref mut connection) => {
+                let _ = connection.reader.shutdown(net::Shutdown::Both);
+                connection.open = false;
+            }
+            #[cfg(feature = "tls")]
+            ActualConnection::TcpTls(ref mut connection) => {
+                let _ = connection.reader.shutdown();
+                connection.open = false;
+            }
+            #[cfg(unix)]
+            ActualConnection::Unix(ref mut connection) => {
+                let _ = connection.sock.shutdown(net::Shutdown::Both);
+                connection.open = false;
+            }
+        }
+    }
+
     pub fn is_open(&self) -> bool {
         match *self {
             ActualConnection::Tcp(TcpConnection { open, .. }) => open,
@@ -537,49 +675,144 @@ impl ActualConnection {
     }
 }
 
-fn connect_auth(con: &mut Connection, connection_info: &RedisConnectionInfo) -> RedisResult<()> {
-    let mut command = cmd("AUTH");
-    if let Some(username) = &connection_info.username {
-        command.arg(username);
+/// A command sent while setting up a connection.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum HandshakeStep {
+    Auth,
+    Select,
+    ClientId,
+}
+
+/// The outcome of [`check_handshake`].
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub(crate) enum Handshake {
+    /// The connection is set up, with the id reported by `CLIENT ID` if it
+    /// was requested.
+    Done(Option<i64>),
+    /// The server does not understand `AUTH <username> <password>` and the
+    /// handshake has to be repeated with `legacy_auth`.
+    RetryWithLegacyAuth,
+}
+
+/// Returns the commands which set up a new connection.  They are sent in one
+/// write, so that the whole handshake takes a single round trip.
+///
+/// With `legacy_auth` the username is left out, for servers older than 6.0
+/// which only know `AUTH <password>`.
+pub(crate) fn handshake_commands(
+    connection_info: &RedisConnectionInfo,
+    legacy_auth: bool,
+) -> Vec<(HandshakeStep, Cmd)> {
+    let mut commands = vec![];
+    if let Some(password) = &connection_info.password {
+        let mut command = cmd("AUTH");
+        if let (Some(username), false) = (&connection_info.username, legacy_auth) {
+            command.arg(username);
+        }
+        command.arg(password);
+        commands.push((HandshakeStep::Auth, command));
+    }
+    if connection_info.db != 0 {
+        let mut command = cmd("SELECT");
+        command.arg(connection_info.db);
+        commands.push((HandshakeStep::Select, command));
+    }
+    if connection_info.fetch_client_id {
+        let mut command = cmd("CLIENT");
+        command.arg("ID");
+        commands.push((HandshakeStep::ClientId, command));
     }
-    let password = connection_info.password.as_ref().unwrap();
-    let err = match command.arg(password).query::<Value>(con) {
-        Ok(Value::Okay) => return Ok(()),
-        Ok(_) => {
-            fail!((
+    commands
+}
+
+/// Checks the replies to [`handshake_commands`] in order and maps the first
+/// failed step into an error.
+pub(crate) fn check_handshake<I>(replies: I, legacy_auth: bool) -> RedisResult<Handshake>
+where
+    I: IntoIterator<Item = (HandshakeStep, RedisResult<Value>)>,
+{
+    let mut client_id = None;
+    for (step, reply) in replies {
+        match (step, reply) {
+            (HandshakeStep::ClientId, Ok(Value::Int(id))) => client_id = Some(id),
+            (_, Ok(Value::Okay)) => {}
+            (_, Err(err)) if err.is_io_error() => return Err(err),
+            (HandshakeStep::Auth, Err(err))
+                if !legacy_auth
+                    && matches!(err.detail(), Some(detail)
+                        if detail.contains("wrong number of arguments for 'auth' command")) =>
+            {
+                return Ok(Handshake::RetryWithLegacyAuth);
+            }
+            (HandshakeStep::Auth, _) => fail!((
+                ErrorKind::AuthenticationFailed,
+                "Password authentication failed",
+            )),
+            (HandshakeStep::Select, Err(err)) => match err.detail() {
+                Some(detail) => fail!((
+                    ErrorKind::ResponseError,
+                    "Redis server refused to switch database",
+                    detail.to_string()
+                )),
+                None => fail!((
+                    ErrorKind::ResponseError,
+                    "Redis server refused to switch database"
+                )),
+            },
+            (HandshakeStep::Select, Ok(_)) => fail!((
+                ErrorKind::ResponseError,
+                "Redis server refused to switch database"
+            )),
+            (HandshakeStep::ClientId, _) => fail!((
                 ErrorKind::ResponseError,
-                "Redis server refused to authenticate, returns Ok() != Value::Okay"
-            ));
+                "Redis server did not report the client id"
+            )),
         }
-        Err(e) => e,
-    };
-    let err_msg = err.detail().ok_or((
-        ErrorKind::AuthenticationFailed,
-        "Password authentication failed",
-    ))?;
-    if !err_msg.contains("wrong number of arguments for 'auth' command") {
-        fail!((
-            ErrorKind::AuthenticationFailed,
-            "Password authentication failed",
-        ));
     }
+    Ok(Handshake::Done(client_id))
+}
 
-    // fallback to AUTH version <= 5
-    let mut command = cmd("AUTH");
-    match command.arg(password).query::<Value>(con) {
-        Ok(Value::Okay) => Ok(()),
-        _ => fail!((
-            ErrorKind::AuthenticationFailed,
-            "Password authentication failed",
-        )),
+/// Enables TCP keepalive probes on `socket` after `time` of idleness.
+///
+/// Does nothing if `time` is `None`.
+pub(crate) fn set_tcp_keepalive(socket: SockRef<'_>, time: Option<Duration>) -> io::Result<()> {
+    if let Some(time) = time {
+        socket.set_tcp_keepalive(&TcpKeepalive::new().with_time(time))?;
+    }
+    Ok(())
+}
+
+/// Writes all of `bytes`, retrying interrupted and short writes.
+///
+/// On failure the error is returned together with whether a part of `bytes`
+/// was already written.
+fn write_fully<W: Write>(writer: &mut W, bytes: &[u8]) -> Result<(), (bool, io::Error)> {
+    let mut written = 0;
+    while written < bytes.len() {
+        match writer.write(&bytes[written..]) {
+            Ok(0) => {
+                return Err((
+                    written > 0,
+                    io::Error::new(io::ErrorKind::WriteZero, "failed to write whole command"),
+                ))
+            }
+            Ok(n) => written += n,
+            Err(err) if err.kind() == io::ErrorKind::Interrupted => {}
+            Err(err) => return Err((written > 0, err)),
+        }
     }
+    writer.flush().map_err(|err| (written > 0, err))
 }
 
 pub fn connect(
     connection_info: &ConnectionInfo,
     timeout: Option<Duration>,
 ) -> RedisResult<Connection> {
-    let con = ActualConnection::new(&connection_info.addr, timeout)?;
+    let con = ActualConnection::new(
+        &connection_info.addr,
+        timeout,
+        connection_info.tcp_keepalive,
+    )?;
     setup_connection(con, &connection_info.redis)
 }
 
@@ -592,22 +825,34 @@ fn setup_connection(
         parser: Parser::new(),
         db: connection_info.db,
         pubsub: false,
+        debug_hook: None,
+        client_id: None,
+        command_name_policy: connection_info.command_name_policy,
     };
 
-    if connection_info.password.is_some() {
-        connect_auth(&mut rv, connection_info)?;
-    }
-
-    if connection_info.db != 0 {
-        match cmd("SELECT")
-            .arg(connection_info.db)
-            .query::<Value>(&mut rv)
-        {
-            Ok(Value::Okay) => {}
-            _ => fail!((
-                ErrorKind::ResponseError,
-                "Redis server refused to switch database"
-            )),
+    let mut legacy_auth = false;
+    loop {
+        let commands = handshake_commands(connection_info, legacy_auth);
+        if commands.is_empty() {
+            break;
+        }
+        let mut packed = vec![];
+        for (_, command) in &commands {
+            command.write_packed_command(&mut packed);
+        }
+        rv.send_packed_command(&packed)?;
+        // All replies are read before checking them, to leave the connection
+        // in a clean state.
+        let replies: Vec<_> = commands
+            .iter()
+            .map(|(step, _)| (*step, rv.recv_response()))
+            .collect();
+        match check_handshake(replies, legacy_auth)? {
+            Handshake::Done(client_id) => {
+                rv.client_id = client_id;
+                break;
+            }
+            Handshake::RetryWithLegacyAuth => legacy_auth = true,
         }
     }
 
@@ -668,6 +913,12 @@ pub trait ConnectionLike {
     /// sockets the connection is open until writing a command failed with a
     /// `BrokenPipe` error.
     fn is_open(&self) -> bool;
+
+    /// Returns how commands named with several words are handled, see
+    /// [`CommandNamePolicy`].
+    fn command_name_policy(&self) -> CommandNamePolicy {
+        CommandNamePolicy::Split
+    }
 }
 
 /// A connection is an object that represents a single redis connection.  It
@@ -711,6 +962,26 @@ impl Connection {
         self.con.set_read_timeout(dur)
     }
 
+    /// Returns the id the server assigned to this connection, if
+    /// [`RedisConnectionInfo::fetch_client_id`] was set when connecting.
+    ///
+    /// The id identifies the connection in `CLIENT` commands sent from other
+    /// connections, e.g. to cancel a blocking call with
+    /// [`Client::unblock`](crate::Client::unblock).  Unlike
+    /// [`Commands::client_id`](crate::Commands::client_id) this does not ask
+    /// the server.
+    pub fn client_id(&self) -> Option<i64> {
+        self.client_id
+    }
+
+    /// Sets the [`DebugHook`] that sees all commands and replies of
+    /// [`req_packed_command`](ConnectionLike::req_packed_command) and
+    /// [`req_packed_commands`](ConnectionLike::req_packed_commands), or
+    /// removes it with `None`.
+    pub fn set_debug_hook(&mut self, hook: Option<DebugHook>) {
+        self.debug_hook = hook;
+    }
+
     /// Creates a [`PubSub`] instance for this connection.
     pub fn as_pubsub(&mut self) -> PubSub<'_> {
         // NOTE: The pubsub flag is intentionally not raised at this time since
@@ -783,17 +1054,23 @@ impl Connection {
 
     /// Fetches a single response from the connection.
     fn read_response(&mut self) -> RedisResult<Value> {
+        self.read_frame(false)?.into_value()
+    }
+
+    /// Fetches a single response from the connection, or only its header if
+    /// it is an array and `array_header` is set.
+    pub(crate) fn read_frame(&mut self, array_header: bool) -> RedisResult<Frame> {
         let result = match self.con {
             ActualConnection::Tcp(TcpConnection { ref mut reader, .. }) => {
-                self.parser.parse_value(reader)
+                self.parser.parse_frame(reader, array_header)
             }
             #[cfg(feature = "tls")]
             ActualConnection::TcpTls(TcpTlsConnection { ref mut reader, .. }) => {
-                self.parser.parse_value(reader)
+                self.parser.parse_frame(reader, array_header)
             }
             #[cfg(unix)]
             ActualConnection::Unix(UnixConnection { ref mut sock, .. }) => {
-                self.parser.parse_value(sock)
+                self.parser.parse_frame(sock, array_header)
             }
         };
         // shutdown connection on protocol error
@@ -803,26 +1080,27 @@ impl Connection {
                 None => false,
             };
             if shutdown {
-                match self.con {
-                    ActualConnection::Tcp(ref mut connection) => {
-                        let _ = connection.reader.shutdown(net::Shutdown::Both);
-                        connection.open = false;
-                    }
-                    #[cfg(feature = "tls")]
-                    ActualConnection::TcpTls(ref mut connection) => {
-                        let _ = connection.reader.shutdown();
-                        connection.open = false;
-                    }
-                    #[cfg(unix)]
-                    ActualConnection::Unix(ref mut connection) => {
-                        let _ = connection.sock.shutdown(net::Shutdown::Both);
-                        connection.open = false;
-                    }
-                }
+                self.shutdown();
             }
         }
         result
     }
+
+    /// Sends an already encoded (packed) command and reads the header of its
+    /// reply, leaving the elements of an array reply to be read with
+    /// `read_frame`.
+    pub(crate) fn req_streaming(&mut self, cmd: &[u8]) -> RedisResult<Frame> {
+        if self.pubsub {
+            self.exit_pubsub()?;
+        }
+        self.con.send_bytes(cmd)?;
+        self.read_frame(true)
+    }
+
+    /// Closes the connection, which can no longer be used afterwards.
+    pub(crate) fn shutdown(&mut self) {
+        self.con.shutdown();
+    }
 }
 
 impl ConnectionLike for Connection {
@@ -831,8 +1109,13 @@ impl ConnectionLike for Connection {
             self.exit_pubsub()?;
         }
 
+        let names = self.debug_hook.as_ref().map(|hook| hook.sent(cmd));
         self.con.send_bytes(cmd)?;
-        self.read_response()
+        let response = self.read_response();
+        if let (Some(hook), Some(names)) = (&self.debug_hook, &names) {
+            hook.received(names, 0, response.as_ref());
+        }
+        response
     }
 
     fn req_packed_commands(
@@ -844,6 +1127,7 @@ impl ConnectionLike for Connection {
         if self.pubsub {
             self.exit_pubsub()?;
         }
+        let names = self.debug_hook.as_ref().map(|hook| hook.sent(cmd));
         self.con.send_bytes(cmd)?;
         let mut rv = vec![];
         let mut first_err = None;
@@ -853,6 +1137,9 @@ impl ConnectionLike for Connection {
             // so bailing early with `?` would not be correct.
             // See: https://github.com/redis-rs/redis-rs/issues/436
             let response = self.read_response();
+            if let (Some(hook), Some(names)) = (&self.debug_hook, &names) {
+                hook.received(names, idx, response.as_ref());
+            }
             match response {
                 Ok(item) => {
                     if idx >= offset {
@@ -870,6 +1157,14 @@ impl ConnectionLike for Connection {
         first_err.map_or(Ok(rv), Err)
     }
 
+    fn req_command(&mut self, cmd: &Cmd) -> RedisResult<Value> {
+        let response = self.req_packed_command(&cmd.get_packed_command())?;
+        if let Some(db) = cmd.selected_db() {
+            self.db = db;
+        }
+        Ok(response)
+    }
+
     fn get_db(&self) -> i64 {
         self.db
     }
@@ -881,6 +1176,10 @@ impl ConnectionLike for Connection {
     fn check_connection(&mut self) -> bool {
         cmd("PING").query::<String>(self).is_ok()
     }
+
+    fn command_name_policy(&self) -> CommandNamePolicy {
+        self.command_name_policy
+    }
 }
 
 impl<C, T> ConnectionLike for T
@@ -920,6 +1219,10 @@ where
     fn is_open(&self) -> bool {
         self.deref().is_open()
     }
+
+    fn command_name_policy(&self) -> CommandNamePolicy {
+        self.deref().command_name_policy()
+    }
 }
 
 /// The pubsub object provides convenient access to the redis pubsub
@@ -958,6 +1261,35 @@ impl<'a> PubSub<'a> {
         cmd("PSUBSCRIBE").arg(pchannel).query(self.con)
     }
 
+    /// Subscribes to the keyspace notifications of the keys matching
+    /// `key_pattern` in database `db`, i.e. to the pattern
+    /// `__keyspace@<db>__:<key_pattern>`.
+    ///
+    /// Subscriptions are not bound to a database, but notifications are
+    /// published for the database the keys live in.  `db` defaults to the
+    /// database selected on the connection.  As subscribing to another one
+    /// is a common mistake, this logs a warning with the `log` feature when
+    /// they differ.  See also [`notify_config_check`](crate::config::notify_config_check).
+    ///
+    /// The selected database is the one from the connection info, changed
+    /// only by `SELECT` commands sent on their own, e.g. with [`Cmd::query`].
+    /// A `SELECT` sent in a pipeline or with
+    /// [`req_packed_command`](ConnectionLike::req_packed_command) is not
+    /// noticed, so pass `db` explicitly after those.
+    pub fn psubscribe_keyspace(&mut self, db: Option<i64>, key_pattern: &str) -> RedisResult<()> {
+        let channel = notification_channel("keyspace", self.con.db, db, key_pattern);
+        self.psubscribe(channel)
+    }
+
+    /// Subscribes to the keyevent notifications of the events matching
+    /// `event_pattern` in database `db`, i.e. to the pattern
+    /// `__keyevent@<db>__:<event_pattern>`.  `db` is handled like in
+    /// [`psubscribe_keyspace`](Self::psubscribe_keyspace).
+    pub fn psubscribe_keyevent(&mut self, db: Option<i64>, event_pattern: &str) -> RedisResult<()> {
+        let channel = notification_channel("keyevent", self.con.db, db, event_pattern);
+        self.psubscribe(channel)
+    }
+
     /// Unsubscribes from a channel.
     pub fn unsubscribe<T: ToRedisArgs>(&mut self, channel: T) -> RedisResult<()> {
         cmd("UNSUBSCRIBE").arg(channel).query(self.con)
@@ -972,8 +1304,11 @@ impl<'a> PubSub<'a> {
     /// a message becomes available.  This currently does not provide a
     /// wait not to block :(
     ///
-    /// The message itself is still generic and can be converted into an
-    /// appropriate type through the helper methods on it.
+    /// Only published messages are returned, other frames like
+    /// subscription confirmations are skipped, see
+    /// [`get_frame`](Self::get_frame).  The message itself is still
+    /// generic and can be converted into an appropriate type through the
+    /// helper methods on it.
     pub fn get_message(&mut self) -> RedisResult<Msg> {
         loop {
             if let Some(msg) = Msg::from_value(&self.con.recv_response()?) {
@@ -984,6 +1319,17 @@ impl<'a> PubSub<'a> {
         }
     }
 
+    /// Fetches the next frame from the pubsub connection.  Blocks until
+    /// one becomes available.
+    ///
+    /// Unlike [`get_message`](Self::get_message) this also returns the
+    /// confirmations of subscriptions that were not read by
+    /// [`subscribe`](Self::subscribe), e.g. for the second channel when
+    /// subscribing to two at once, and any other frame.
+    pub fn get_frame(&mut self) -> RedisResult<Msg> {
+        Ok(Msg::from(self.con.recv_response()?))
+    }
+
     /// Sets the read timeout for the connection.
     ///
     /// If the provided value is `None`, then `get_message` call will
@@ -1000,39 +1346,120 @@ impl<'a> Drop for PubSub<'a> {
     }
 }
 
-/// This holds the data that comes from listening to a pubsub
-/// connection.  It only contains actual message data.
+// Returns the channel pattern for keyspace or keyevent notifications of
+// `db`, which defaults to the `selected` database of the connection.
+pub(crate) fn notification_channel(
+    kind: &str,
+    selected: i64,
+    db: Option<i64>,
+    pattern: &str,
+) -> String {
+    let db = db.unwrap_or(selected);
+    #[cfg(feature = "log")]
+    {
+        if db != selected {
+            log::warn!(
+                target: "redis",
+                "subscribing to {} notifications of database {} on a connection that selected database {}",
+                kind,
+                db,
+                selected
+            );
+        }
+    }
+    format!("__{}@{}__:{}", kind, db, pattern)
+}
+
 impl Msg {
-    /// Tries to convert provided [`Value`] into [`Msg`].
+    /// Tries to convert provided [`Value`] into a published message.
+    ///
+    /// Returns `None` for subscription confirmations and any other frame,
+    /// use `Msg::from` to convert those as well.
     pub fn from_value(value: &Value) -> Option<Self> {
-        let raw_msg: Vec<Value> = from_redis_value(value).ok()?;
-        let mut iter = raw_msg.into_iter();
-        let msg_type: String = from_redis_value(&iter.next()?).ok()?;
-        let mut pattern = None;
-        let payload;
-        let channel;
-
-        if msg_type == "message" {
-            channel = iter.next()?;
-            payload = iter.next()?;
-        } else if msg_type == "pmessage" {
-            pattern = Some(iter.next()?);
-            channel = iter.next()?;
-            payload = iter.next()?;
-        } else {
-            return None;
+        match Self::parse(value)? {
+            msg @ Msg::Message { .. } | msg @ Msg::PMessage { .. } | msg @ Msg::SMessage { .. } => {
+                Some(msg)
+            }
+            _ => None,
         }
+    }
 
-        Some(Msg {
-            payload,
-            channel,
-            pattern,
-        })
+    fn parse(value: &Value) -> Option<Self> {
+        let items = match value {
+            Value::Bulk(items) => items,
+            _ => return None,
+        };
+        let (kind, rest) = items.split_first()?;
+        let kind = match kind {
+            Value::Data(kind) => &kind[..],
+            _ => return None,
+        };
+        let confirmation = |kind| -> Option<(SubscriptionKind, Value, usize)> {
+            match rest {
+                [channel, count] => Some((kind, channel.clone(), from_redis_value(count).ok()?)),
+                _ => None,
+            }
+        };
+        let msg = match (kind, rest) {
+            (b"message", [channel, payload]) => Msg::Message {
+                channel: channel.clone(),
+                payload: payload.clone(),
+            },
+            (b"pmessage", [pattern, channel, payload]) => Msg::PMessage {
+                pattern: pattern.clone(),
+                channel: channel.clone(),
+                payload: payload.clone(),
+            },
+            (b"smessage", [channel, payload]) => Msg::SMessage {
+                channel: channel.clone(),
+                payload: payload.clone(),
+            },
+            (b"subscribe", _) | (b"psubscribe", _) | (b"ssubscribe", _) => {
+                let (kind, channel, count) = confirmation(SubscriptionKind::from_command(kind))?;
+                Msg::Subscribed {
+                    kind,
+                    channel,
+                    count,
+                }
+            }
+            (b"unsubscribe", _) | (b"punsubscribe", _) | (b"sunsubscribe", _) => {
+                let (kind, channel, count) = confirmation(SubscriptionKind::from_command(kind))?;
+                Msg::Unsubscribed {
+                    kind,
+                    channel,
+                    count,
+                }
+            }
+            _ => return None,
+        };
+        Some(msg)
+    }
+
+    fn channel(&self) -> &Value {
+        match self {
+            Msg::Message { channel, .. }
+            | Msg::PMessage { channel, .. }
+            | Msg::SMessage { channel, .. }
+            | Msg::Subscribed { channel, .. }
+            | Msg::Unsubscribed { channel, .. } => channel,
+            Msg::Other(_) => &Value::Nil,
+        }
+    }
+
+    fn payload(&self) -> &Value {
+        match self {
+            Msg::Message { payload, .. }
+            | Msg::PMessage { payload, .. }
+            | Msg::SMessage { payload, .. } => payload,
+            _ => &Value::Nil,
+        }
     }
 
-    /// Returns the channel this message came on.
+    /// Returns the channel this message came on, or the channel a
+    /// confirmation refers to.  Other frames have no channel, which is
+    /// returned as `nil`.
     pub fn get_channel<T: FromRedisValue>(&self) -> RedisResult<T> {
-        from_redis_value(&self.channel)
+        from_redis_value(self.channel())
     }
 
     /// Convenience method to get a string version of the channel.  Unless
@@ -1040,32 +1467,43 @@ impl Msg {
     /// method.  If the channel is not a valid string (which really should
     /// not happen) then the return value is `"?"`.
     pub fn get_channel_name(&self) -> &str {
-        match self.channel {
+        match self.channel() {
             Value::Data(ref bytes) => from_utf8(bytes).unwrap_or("?"),
             _ => "?",
         }
     }
 
-    /// Returns the message's payload in a specific format.
+    /// Returns the message's payload in a specific format.  Frames other
+    /// than published messages have no payload, which is returned as `nil`.
     pub fn get_payload<T: FromRedisValue>(&self) -> RedisResult<T> {
-        from_redis_value(&self.payload)
+        from_redis_value(self.payload())
     }
 
     /// Returns the bytes that are the message's payload.  This can be used
     /// as an alternative to the `get_payload` function if you are interested
-    /// in the raw bytes in it.
+    /// in the raw bytes in it.  This is empty for frames other than
+    /// published messages.
     pub fn get_payload_bytes(&self) -> &[u8] {
-        match self.payload {
+        match self.payload() {
             Value::Data(ref bytes) => bytes,
             _ => b"",
         }
     }
 
+    /// Returns the message's payload as a string slice, or `None` if there
+    /// is no payload or it is not valid utf-8.
+    pub fn get_payload_str(&self) -> Option<&str> {
+        match self.payload() {
+            Value::Data(ref bytes) => from_utf8(bytes).ok(),
+            _ => None,
+        }
+    }
+
     /// Returns true if the message was constructed from a pattern
     /// subscription.
     #[allow(clippy::wrong_self_convention)]
     pub fn from_pattern(&self) -> bool {
-        self.pattern.is_some()
+        matches!(self, Msg::PMessage { .. })
     }
 
     /// If the message was constructed from a message pattern this can be
@@ -1073,9 +1511,26 @@ impl Msg {
     /// an `Option<String>` so that you do not need to use `from_pattern`
     /// to figure out if a pattern was set.
     pub fn get_pattern<T: FromRedisValue>(&self) -> RedisResult<T> {
-        match self.pattern {
-            None => from_redis_value(&Value::Nil),
-            Some(ref x) => from_redis_value(x),
+        match self {
+            Msg::PMessage { pattern, .. } => from_redis_value(pattern),
+            _ => from_redis_value(&Value::Nil),
+        }
+    }
+}
+
+impl From<Value> for Msg {
+    fn from(value: Value) -> Self {
+        Msg::parse(&value).unwrap_or(Msg::Other(value))
+    }
+}
+
+impl SubscriptionKind {
+    // Maps the name of a (un)subscribe command, as echoed by its confirmation.
+    fn from_command(command: &[u8]) -> Self {
+        match command {
+            b"psubscribe" | b"punsubscribe" => SubscriptionKind::Pattern,
+            b"ssubscribe" | b"sunsubscribe" => SubscriptionKind::Shard,
+            _ => SubscriptionKind::Channel,
         }
     }
 }
@@ -1173,6 +1628,7 @@ mod tests {
                 ConnectionInfo {
                     addr: ConnectionAddr::Tcp("127.0.0.1".to_string(), 6379),
                     redis: Default::default(),
+                    tcp_keepalive: None,
                 },
             ),
             (
@@ -1183,7 +1639,9 @@ mod tests {
                         db: 2,
                         username: Some("%johndoe%".to_string()),
                         password: Some("#@<>$".to_string()),
+                        ..Default::default()
                     },
+                    tcp_keepalive: None,
                 },
             ),
         ];
@@ -1254,7 +1712,9 @@ mod tests {
                         db: 0,
                         username: None,
                         password: None,
+                        ..Default::default()
                     },
+                    tcp_keepalive: None,
                 },
             ),
             (
@@ -1265,7 +1725,9 @@ mod tests {
                         db: 1,
                         username: None,
                         password: None,
+                        ..Default::default()
                     },
+                    tcp_keepalive: None,
                 },
             ),
             (
@@ -1279,7 +1741,9 @@ mod tests {
                         db: 2,
                         username: Some("%johndoe%".to_string()),
                         password: Some("#@<>$".to_string()),
+                        ..Default::default()
                     },
+                    tcp_keepalive: None,
                 },
             ),
             (
@@ -1293,7 +1757,9 @@ mod tests {
                         db: 2,
                         username: Some("%johndoe%".to_string()),
                         password: Some("&?= *+".to_string()),
+                        ..Default::default()
                     },
+                    tcp_keepalive: None,
                 },
             ),
         ];
@@ -1323,4 +1789,343 @@ mod tests {
             );
         }
     }
+
+    /// Accepts a single connection and sends `replies` once the whole
+    /// handshake has been received, after a simulated round trip of `rtt`.
+    ///
+    /// A client waiting for each reply before sending the next command never
+    /// completes the handshake, and the server gives up after a second.
+    /// Accepts one connection and writes `replies` once the `SELECT` of the
+    /// handshake arrived, or the client stopped sending.  Returns the port and
+    /// a handle to the bytes received before the replies were written.
+    fn handshake_server(replies: &'static [u8]) -> (u16, std::thread::JoinHandle<Vec<u8>>) {
+        use std::io::Read;
+
+        let listener = net::TcpListener::bind("127.0.0.1:0").unwrap();
+        let port = listener.local_addr().unwrap().port();
+        let server = std::thread::spawn(move || {
+            let (mut socket, _) = listener.accept().unwrap();
+            socket
+                .set_read_timeout(Some(Duration::from_secs(1)))
+                .unwrap();
+            let mut received = vec![];
+            while !received.ends_with(b"SELECT\r\n$1\r\n3\r\n") {
+                let mut buf = [0; 512];
+                match socket.read(&mut buf) {
+                    Ok(0) | Err(_) => break,
+                    Ok(n) => received.extend_from_slice(&buf[..n]),
+                }
+            }
+            socket.write_all(replies).unwrap();
+            // Keep the socket open until the client is done with it.
+            let _ = socket.read(&mut [0; 1]);
+            received
+        });
+        (port, server)
+    }
+
+    fn handshake_connection_info(port: u16) -> ConnectionInfo {
+        ConnectionInfo {
+            addr: ConnectionAddr::Tcp("127.0.0.1".to_string(), port),
+            redis: RedisConnectionInfo {
+                db: 3,
+                username: Some("user".to_string()),
+                password: Some("secret".to_string()),
+                ..Default::default()
+            },
+            tcp_keepalive: None,
+        }
+    }
+
+    #[test]
+    fn test_handshake_is_pipelined() {
+        let (port, server) = handshake_server(b"+OK\r\n+OK\r\n");
+        let info = handshake_connection_info(port);
+        let con = connect(&info, None).unwrap();
+        assert_eq!(con.get_db(), 3);
+        drop(con);
+
+        // All handshake commands arrived before any reply was written.
+        let mut expected = vec![];
+        for (_, command) in handshake_commands(&info.redis, false) {
+            command.write_packed_command(&mut expected);
+        }
+        assert_eq!(server.join().unwrap(), expected);
+    }
+
+    #[test]
+    fn test_handshake_errors() {
+        let (port, _) = handshake_server(
+            b"-WRONGPASS invalid username-password pair\r\n-NOAUTH Authentication required.\r\n",
+        );
+        let err = connect(&handshake_connection_info(port), None)
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), ErrorKind::AuthenticationFailed);
+
+        let (port, _) = handshake_server(b"+OK\r\n-ERR DB index is out of range\r\n");
+        let err = connect(&handshake_connection_info(port), None)
+            .err()
+            .unwrap();
+        assert_eq!(err.kind(), ErrorKind::ResponseError);
+        assert_eq!(err.detail(), Some("DB index is out of range"));
+    }
+
+    #[test]
+    fn test_handshake_fetches_client_id() {
+        let mut info = handshake_connection_info(0).redis;
+        info.fetch_client_id = true;
+        let steps: Vec<_> = handshake_commands(&info, false)
+            .into_iter()
+            .map(|(step, _)| step)
+            .collect();
+        assert_eq!(
+            steps,
+            vec![
+                HandshakeStep::Auth,
+                HandshakeStep::Select,
+                HandshakeStep::ClientId
+            ]
+        );
+
+        let replies = vec![
+            (HandshakeStep::Auth, Ok(Value::Okay)),
+            (HandshakeStep::Select, Ok(Value::Okay)),
+            (HandshakeStep::ClientId, Ok(Value::Int(42))),
+        ];
+        assert_eq!(
+            check_handshake(replies, false),
+            Ok(Handshake::Done(Some(42)))
+        );
+
+        let replies = vec![(
+            HandshakeStep::ClientId,
+            Err((ErrorKind::ResponseError, "unknown subcommand").into()),
+        )];
+        assert!(check_handshake(replies, false).is_err());
+    }
+
+    /// Writes at most `chunk` bytes at a time, is interrupted before every
+    /// write and fails with `WouldBlock` once `limit` bytes were written.
+    struct FlakyWriter {
+        written: Vec<u8>,
+        chunk: usize,
+        limit: usize,
+        interrupted: bool,
+    }
+
+    impl FlakyWriter {
+        fn new(chunk: usize, limit: usize) -> FlakyWriter {
+            FlakyWriter {
+                written: vec![],
+                chunk,
+                limit,
+                interrupted: false,
+            }
+        }
+    }
+
+    impl Write for FlakyWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.interrupted = !self.interrupted;
+            if self.interrupted {
+                return Err(io::ErrorKind::Interrupted.into());
+            }
+            let n = buf
+                .len()
+                .min(self.chunk)
+                .min(self.limit - self.written.len());
+            if n == 0 {
+                return Err(io::ErrorKind::WouldBlock.into());
+            }
+            self.written.extend_from_slice(&buf[..n]);
+            Ok(n)
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_write_fully_retries_short_and_interrupted_writes() {
+        let command = cmd("SET")
+            .arg("key")
+            .arg(&[b'x'; 100][..])
+            .get_packed_command();
+        let mut writer = FlakyWriter::new(7, usize::MAX);
+        write_fully(&mut writer, &command).unwrap();
+        assert_eq!(writer.written, command);
+    }
+
+    #[test]
+    fn test_write_fully_reports_partial_writes() {
+        let command = cmd("SET").arg("key").arg("value").get_packed_command();
+        let (partial, err) = write_fully(&mut FlakyWriter::new(7, 10), &command).unwrap_err();
+        assert!(partial);
+        assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+
+        let (partial, err) = write_fully(&mut FlakyWriter::new(7, 0), &command).unwrap_err();
+        assert!(!partial);
+        assert_eq!(err.kind(), io::ErrorKind::WouldBlock);
+    }
+
+    #[cfg(unix)]
+    fn unix_pair(send_buffer: usize) -> (ActualConnection, UnixStream) {
+        let (sock, peer) = UnixStream::pair().unwrap();
+        SockRef::from(&sock)
+            .set_send_buffer_size(send_buffer)
+            .unwrap();
+        let con = ActualConnection::Unix(UnixConnection { sock, open: true });
+        (con, peer)
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_slow_reader_receives_whole_command() {
+        use std::io::Read;
+
+        let (mut con, mut peer) = unix_pair(1024);
+        let command = cmd("SET")
+            .arg("key")
+            .arg(vec![b'x'; 1 << 20])
+            .get_packed_command();
+        let len = command.len();
+        let reader = std::thread::spawn(move || {
+            let mut received = vec![];
+            let mut buf = [0; 4096];
+            while received.len() < len {
+                let n = peer.read(&mut buf).unwrap();
+                received.extend_from_slice(&buf[..n]);
+            }
+            received
+        });
+        con.send_bytes(&command).unwrap();
+        assert!(con.is_open());
+        assert_eq!(reader.join().unwrap(), command);
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_partial_write_breaks_connection() {
+        let (mut con, _peer) = unix_pair(1024);
+        con.set_write_timeout(Some(Duration::from_millis(50)))
+            .unwrap();
+        let command = cmd("SET")
+            .arg("key")
+            .arg(vec![b'x'; 1 << 20])
+            .get_packed_command();
+        // Nobody reads from the peer, so the command only fits partially.
+        let err = con.send_bytes(&command).unwrap_err();
+        assert_eq!(
+            err.as_io_error().map(io::Error::kind),
+            Some(io::ErrorKind::WouldBlock)
+        );
+        assert!(!con.is_open());
+        assert!(con.send_bytes(b"*1\r\n$4\r\nPING\r\n").is_err());
+    }
+
+    #[cfg(unix)]
+    #[test]
+    fn test_debug_hook_sees_pipeline() {
+        use crate::hook::MessageDirection::{Received, Sent};
+        use std::sync::{Arc, Mutex};
+
+        let (con, mut peer) = unix_pair(1 << 16);
+        peer.write_all(b"+OK\r\n$5\r\nvalue\r\n").unwrap();
+        let events = Arc::new(Mutex::new(vec![]));
+        let sink = events.clone();
+        let mut con = Connection {
+            con,
+            parser: Parser::new(),
+            db: 0,
+            pubsub: false,
+            client_id: None,
+            debug_hook: Some(DebugHook::new(move |direction, command, summary| {
+                sink.lock()
+                    .unwrap()
+                    .push((direction, command.to_string(), summary.to_string()))
+            })),
+
+            command_name_policy: CommandNamePolicy::Split,
+        };
+
+        let (ok, value): (String, String) = pipe()
+            .set("key", "value")
+            .get("key")
+            .query(&mut con)
+            .unwrap();
+        assert_eq!((ok.as_str(), value.as_str()), ("OK", "value"));
+        let expected = vec![
+            (Sent, "SET", "SET key value"),
+            (Sent, "GET", "GET key"),
+            (Received, "SET", "ok"),
+            (Received, "GET", "string-data('\"value\"')"),
+        ];
+        let expected: Vec<_> = expected
+            .into_iter()
+            .map(|(direction, command, summary)| {
+                (direction, command.to_string(), summary.to_string())
+            })
+            .collect();
+        assert_eq!(*events.lock().unwrap(), expected);
+    }
+
+    #[test]
+    #[cfg(feature = "log")]
+    // Const thread local initializers need a newer compiler than the minimum
+    // supported one.
+    #[allow(clippy::missing_const_for_thread_local)]
+    fn test_notification_channel_warns_about_other_db() {
+        use std::cell::RefCell;
+
+        thread_local! {
+            static WARNINGS: RefCell<Vec<String>> = RefCell::new(vec![]);
+        }
+
+        struct Capture;
+
+        impl log::Log for Capture {
+            fn enabled(&self, metadata: &log::Metadata<'_>) -> bool {
+                metadata.level() <= log::Level::Warn
+            }
+
+            fn log(&self, record: &log::Record<'_>) {
+                if self.enabled(record.metadata()) {
+                    let message = record.args().to_string();
+                    WARNINGS.with(|warnings| warnings.borrow_mut().push(message));
+                }
+            }
+
+            fn flush(&self) {}
+        }
+
+        static CAPTURE: Capture = Capture;
+        let _ = log::set_logger(&CAPTURE);
+        log::set_max_level(log::LevelFilter::Warn);
+
+        assert_eq!(
+            notification_channel("keyspace", 2, None, "user:*"),
+            "__keyspace@2__:user:*"
+        );
+        assert_eq!(
+            notification_channel("keyevent", 2, Some(2), "expired"),
+            "__keyevent@2__:expired"
+        );
+        WARNINGS.with(|warnings| assert!(warnings.borrow().is_empty()));
+
+        assert_eq!(
+            notification_channel("keyspace", 2, Some(0), "*"),
+            "__keyspace@0__:*"
+        );
+        WARNINGS.with(|warnings| {
+            assert_eq!(
+                *warnings.borrow(),
+                vec![
+                    "subscribing to keyspace notifications of database 0 \
+                     on a connection that selected database 2"
+                ]
+            )
+        });
+    }
 }
@@ -0,0 +1,262 @@
+use std::fmt;
+use std::sync::Arc;
+
+use crate::types::{RedisError, Value};
+
+/// Arguments longer than this are truncated in summaries.
+const MAX_ARG_LEN: usize = 64;
+
+/// Commands with more arguments than this only show the first ones.
+const MAX_ARGS: usize = 16;
+
+/// Replies longer than this are truncated in summaries.
+const MAX_REPLY_LEN: usize = 256;
+
+/// Commands that can carry credentials, together with the number of
+/// arguments after the command name that are safe to show.
+const REDACTED_COMMANDS: &[(&str, usize)] = &[
+    ("AUTH", 0),
+    ("HELLO", 0),
+    ("MIGRATE", 0),
+    ("ACL", 1),
+    ("CONFIG", 1),
+];
+
+type Callback = dyn Fn(MessageDirection, &str, &str) + Send + Sync;
+
+/// Whether a [`DebugHook`] is called for a command or for a reply.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum MessageDirection {
+    /// A command was sent to the server.
+    Sent,
+    /// A reply was received from the server.
+    Received,
+}
+
+/// A callback that sees every command sent on a connection and every reply
+/// read from it, for debugging.
+///
+/// The callback receives the direction, the name of the command and a short
+/// human readable summary.  Commands are summarized with their arguments,
+/// where long arguments are truncated and the arguments of commands that can
+/// carry credentials, like `AUTH`, are redacted.  Replies are summarized
+/// with their debug representation, truncated.
+///
+/// Hooks are installed with [`Client::set_debug_hook`](crate::Client::set_debug_hook)
+/// and are inherited by all connections the client opens afterwards.  The
+/// summaries are only built when a hook is installed.
+///
+/// ```rust,no_run
+/// # fn do_something() -> redis::RedisResult<()> {
+/// use redis::{DebugHook, MessageDirection};
+///
+/// let mut client = redis::Client::open("redis://127.0.0.1/")?;
+/// client.set_debug_hook(Some(DebugHook::new(|direction, command, summary| {
+///     if direction == MessageDirection::Sent {
+///         eprintln!("-> {}: {}", command, summary);
+///     }
+/// })));
+/// # Ok(()) }
+/// ```
+#[derive(Clone)]
+pub struct DebugHook(Arc<Callback>);
+
+impl DebugHook {
+    /// Creates a hook that calls `callback`.
+    pub fn new<F>(callback: F) -> DebugHook
+    where
+        F: Fn(MessageDirection, &str, &str) + Send + Sync + 'static,
+    {
+        DebugHook(Arc::new(callback))
+    }
+
+    /// Creates a hook that logs every message with `log::debug!` under the
+    /// `redis` target.
+    #[cfg(feature = "log")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "log")))]
+    pub fn log() -> DebugHook {
+        DebugHook::new(|direction, command, summary| match direction {
+            MessageDirection::Sent => log::debug!(target: "redis", "-> {}", summary),
+            MessageDirection::Received => {
+                log::debug!(target: "redis", "<- {}: {}", command, summary)
+            }
+        })
+    }
+
+    /// Reports the commands in `packed` and returns their names, so that
+    /// their replies can be reported with [`received`](Self::received).
+    pub(crate) fn sent(&self, packed: &[u8]) -> Vec<String> {
+        split_packed(packed)
+            .into_iter()
+            .map(|args| {
+                let name = args
+                    .first()
+                    .map(|name| String::from_utf8_lossy(name).to_ascii_uppercase())
+                    .unwrap_or_default();
+                (self.0)(
+                    MessageDirection::Sent,
+                    &name,
+                    &summarize_command(&name, &args),
+                );
+                name
+            })
+            .collect()
+    }
+
+    /// Reports the reply to the `idx`th command of `names`.
+    pub(crate) fn received(
+        &self,
+        names: &[String],
+        idx: usize,
+        reply: Result<&Value, &RedisError>,
+    ) {
+        let name = names.get(idx).map_or("", String::as_str);
+        let summary = match reply {
+            Ok(value) => truncate(format!("{:?}", value), MAX_REPLY_LEN),
+            Err(err) => truncate(format!("error: {}", err), MAX_REPLY_LEN),
+        };
+        (self.0)(MessageDirection::Received, name, &summary);
+    }
+}
+
+impl fmt::Debug for DebugHook {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("DebugHook")
+    }
+}
+
+fn summarize_command(name: &str, args: &[&[u8]]) -> String {
+    let visible = REDACTED_COMMANDS
+        .iter()
+        .find(|(redacted, _)| *redacted == name)
+        .map(|&(_, visible)| visible + 1);
+    let shown = visible.unwrap_or(MAX_ARGS + 1).min(args.len());
+    let mut parts: Vec<String> = args[..shown]
+        .iter()
+        .map(|arg| truncate(String::from_utf8_lossy(arg).into_owned(), MAX_ARG_LEN))
+        .collect();
+    if shown < args.len() {
+        parts.push(match visible {
+            Some(_) => "<redacted>".to_string(),
+            None => format!("... ({} more)", args.len() - shown),
+        });
+    }
+    parts.join(" ")
+}
+
+fn truncate(mut s: String, max: usize) -> String {
+    if s.len() > max {
+        let mut end = max;
+        while !s.is_char_boundary(end) {
+            end -= 1;
+        }
+        s.truncate(end);
+        s.push_str("...");
+    }
+    s
+}
+
+// Splits packed commands back into their arguments.  Packed commands are
+// always arrays of bulk strings, anything else ends the split.
+fn split_packed(mut packed: &[u8]) -> Vec<Vec<&[u8]>> {
+    let mut commands = vec![];
+    while let Some((count, mut rest)) = read_header(packed, b'*') {
+        let mut args = Vec::with_capacity(count);
+        for _ in 0..count {
+            let (len, data) = match read_header(rest, b'$') {
+                Some(header) => header,
+                None => return commands,
+            };
+            match (data.get(..len), data.get(len + 2..)) {
+                (Some(arg), Some(next)) => {
+                    args.push(arg);
+                    rest = next;
+                }
+                _ => return commands,
+            }
+        }
+        commands.push(args);
+        packed = rest;
+    }
+    commands
+}
+
+fn read_header(input: &[u8], prefix: u8) -> Option<(usize, &[u8])> {
+    let rest = input.strip_prefix(&[prefix])?;
+    let end = rest.iter().position(|&b| b == b'\r')?;
+    let len = std::str::from_utf8(&rest[..end]).ok()?.parse().ok()?;
+    Some((len, rest.get(end + 2..)?))
+}
+
+#[cfg(test)]
+mod tests {
+    use std::sync::Mutex;
+
+    use super::*;
+    use crate::{cmd, pipe};
+
+    type Events = Arc<Mutex<Vec<(MessageDirection, String, String)>>>;
+
+    fn collecting_hook() -> (DebugHook, Events) {
+        let events = Arc::new(Mutex::new(vec![]));
+        let sink = events.clone();
+        let hook = DebugHook::new(move |direction, command, summary| {
+            sink.lock()
+                .unwrap()
+                .push((direction, command.to_string(), summary.to_string()))
+        });
+        (hook, events)
+    }
+
+    #[test]
+    fn test_sent_splits_pipeline() {
+        let (hook, events) = collecting_hook();
+        let packed = pipe().set("key", "value").get("key").get_packed_pipeline();
+        let names = hook.sent(&packed);
+        assert_eq!(names, vec!["SET", "GET"]);
+        assert_eq!(
+            *events.lock().unwrap(),
+            vec![
+                (MessageDirection::Sent, "SET".into(), "SET key value".into()),
+                (MessageDirection::Sent, "GET".into(), "GET key".into()),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_sent_redacts_credentials() {
+        let (hook, events) = collecting_hook();
+        hook.sent(&cmd("auth").arg("user").arg("secret").get_packed_command());
+        hook.sent(
+            &cmd("ACL")
+                .arg("SETUSER")
+                .arg("user")
+                .arg(">secret")
+                .get_packed_command(),
+        );
+        let summaries: Vec<_> = events.lock().unwrap().drain(..).map(|e| e.2).collect();
+        assert_eq!(summaries, vec!["auth <redacted>", "ACL SETUSER <redacted>"]);
+    }
+
+    #[test]
+    fn test_summaries_are_truncated() {
+        let (hook, events) = collecting_hook();
+        let long = "x".repeat(1000);
+        hook.sent(
+            &cmd("RPUSH")
+                .arg("list")
+                .arg(&long)
+                .arg(&[1; 20][..])
+                .get_packed_command(),
+        );
+        let names = vec!["GET".to_string()];
+        hook.received(&names, 0, Ok(&Value::Data(long.into_bytes())));
+
+        let events = events.lock().unwrap();
+        let sent = &events[0].2;
+        assert!(sent.starts_with(&format!("RPUSH list {}...", "x".repeat(MAX_ARG_LEN))));
+        assert!(sent.ends_with("... (6 more)"));
+        assert_eq!(events[1].1, "GET");
+        assert_eq!(events[1].2.len(), MAX_REPLY_LEN + 3);
+    }
+}
@@ -60,6 +60,18 @@
 //! * `cluster`: enables redis cluster support (optional)
 //! * `tokio-comp`: enables support for tokio (optional)
 //! * `connection-manager`: enables support for automatic reconnection (optional)
+//! * `safe-encoding`: enables `_str` command variants which reject keys that are
+//!   not valid UTF-8 (optional)
+//! * `debug-commands`: enables `DEBUG` commands and the [`testing`] helpers
+//!   for deterministic expiry in tests (optional)
+//! * `test-server`: enables the [`testing`] helpers that start disposable
+//!   `redis-server` processes for tests (optional)
+//! * `log`: enables [`DebugHook::log`], which logs all traffic with
+//!   `log::debug!` (optional)
+//! * `chrono`: allows passing `chrono::DateTime` as unix time arguments (optional)
+//! * `uuid`: allows passing `uuid::Uuid` as arguments (optional)
+//! * `metrics`: enables exporting [`LatencyHistogram`] replies through the
+//!   `metrics` facade, version 0.17 (optional)
 //!
 //! ## Connection Parameters
 //!
@@ -99,16 +111,13 @@
 //! about the actual return value (other than that it is not a failure)
 //! you can always type annotate it to the unit type `()`.
 //!
-//! Note that commands with a sub-command (like "MEMORY USAGE", "ACL WHOAMI",
-//! "LATENCY HISTORY", etc) must specify the sub-command as a separate `arg`:
+//! Commands with a sub-command (like "MEMORY USAGE", "ACL WHOAMI",
+//! "LATENCY HISTORY", etc) are sent as two arguments.  `cmd` splits a name
+//! with a space for you, so both of these work:
 //!
 //! ```rust,no_run
 //! fn do_something(con: &mut redis::Connection) -> redis::RedisResult<usize> {
-//!     // This will result in a server error: "unknown command `MEMORY USAGE`"
-//!     // because "USAGE" is technically a sub-command of "MEMORY".
-//!     redis::cmd("MEMORY USAGE").arg("my_key").query(con)?;
-//!
-//!     // However, this will work as you'd expect
+//!     let _: usize = redis::cmd("MEMORY USAGE").arg("my_key").query(con)?;
 //!     redis::cmd("MEMORY").arg("USAGE").arg("my_key").query(con)
 //! }
 //! ```
@@ -359,12 +368,16 @@ assert_eq!(result, Ok(("foo".to_string(), b"bar".to_vec())));
 
 // public api
 pub use crate::client::Client;
-pub use crate::cmd::{cmd, pack_command, pipe, Arg, Cmd, Iter};
-pub use crate::commands::{Commands, ControlFlow, LposOptions, PubSubCommands};
+pub use crate::cmd::{cmd, pack_command, pipe, Arg, Cmd, CommandNamePolicy, Iter, StreamingIter};
+pub use crate::commands::{
+    Commands, ControlFlow, Count, FailoverMode, LposOptions, PubSubCommands, SlotState,
+    SortOptions, UnblockKind, Where, ZAddOptions,
+};
 pub use crate::connection::{
     parse_redis_url, transaction, Connection, ConnectionAddr, ConnectionInfo, ConnectionLike,
-    IntoConnectionInfo, Msg, PubSub, RedisConnectionInfo,
+    IntoConnectionInfo, Msg, PubSub, RedisConnectionInfo, SubscriptionKind,
 };
+pub use crate::hook::{DebugHook, MessageDirection};
 pub use crate::parser::{parse_redis_value, Parser};
 pub use crate::pipeline::Pipeline;
 
@@ -387,7 +400,21 @@ pub use crate::types::{
     // utility types
     InfoDict,
     NumericBehavior,
+    CommandLatency,
+    DumpPayload,
+    Encoding,
+    LatencyEvent,
+    LatencyHistogram,
+    LatencyLatestEntry,
+    LossyString,
     Expiry,
+    Expiration,
+    HelloInfo,
+    Pattern,
+    ReplicaInfo,
+    ReplicationOutcome,
+    Role,
+    Status,
 
     // error and result types
     RedisError,
@@ -402,7 +429,10 @@ pub use crate::types::{
 #[cfg(feature = "aio")]
 #[cfg_attr(docsrs, doc(cfg(feature = "aio")))]
 pub use crate::{
-    cmd::AsyncIter, commands::AsyncCommands, parser::parse_redis_value_async, types::RedisFuture,
+    cmd::{AsyncIter, AsyncStreamingIter},
+    commands::AsyncCommands,
+    parser::parse_redis_value_async,
+    types::RedisFuture,
 };
 
 mod macros;
@@ -416,6 +446,8 @@ pub mod acl;
 #[cfg_attr(docsrs, doc(cfg(feature = "aio")))]
 pub mod aio;
 
+pub mod config;
+
 #[cfg(feature = "geospatial")]
 #[cfg_attr(docsrs, doc(cfg(feature = "geospatial")))]
 pub mod geo;
@@ -424,6 +456,9 @@ pub mod geo;
 #[cfg_attr(docsrs, doc(cfg(feature = "cluster")))]
 pub mod cluster;
 
+#[cfg(feature = "cluster")]
+mod cluster_breaker;
+
 #[cfg(feature = "cluster")]
 mod cluster_client;
 
@@ -441,10 +476,20 @@ mod r2d2;
 #[cfg_attr(docsrs, doc(cfg(feature = "streams")))]
 pub mod streams;
 
+#[cfg(any(feature = "debug-commands", feature = "test-server"))]
+#[cfg_attr(
+    docsrs,
+    doc(cfg(any(feature = "debug-commands", feature = "test-server")))
+)]
+pub mod testing;
+
+pub mod util;
+
 mod client;
 mod cmd;
 mod commands;
 mod connection;
+mod hook;
 mod parser;
 mod script;
 mod types;
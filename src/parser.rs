@@ -10,7 +10,7 @@ use combine::{
     error::StreamError,
     opaque,
     parser::{
-        byte::{crlf, take_until_bytes},
+        byte::{byte, crlf, take_until_bytes},
         combinator::{any_send_sync_partial_state, AnySendSyncPartialState},
         range::{recognize, take},
     },
@@ -133,7 +133,16 @@ where
                         code => return make_extension_error(code, pieces.next()),
                     };
                     match pieces.next() {
-                        Some(detail) => RedisError::from((kind, desc, detail.to_string())),
+                        Some(detail) => {
+                            let kind = if kind == ErrorKind::ResponseError
+                                && is_wrong_eviction_policy(detail)
+                            {
+                                ErrorKind::WrongEvictionPolicy
+                            } else {
+                                kind
+                            };
+                            RedisError::from((kind, desc, detail.to_string()))
+                        }
                         None => RedisError::from((kind, desc)),
                     }
                 })
@@ -151,6 +160,65 @@ where
     )))
 }
 
+// `OBJECT FREQ` fails with "An LFU maxmemory policy is not selected, access
+// frequency not tracked. ..." and `OBJECT IDLETIME` with "An LFU maxmemory
+// policy is selected, idle time not tracked. ...".
+fn is_wrong_eviction_policy(detail: &str) -> bool {
+    detail.contains("maxmemory policy is") && detail.contains("not tracked")
+}
+
+/// A reply read from the connection, or the header of an array reply whose
+/// elements are read one at a time afterwards.
+pub(crate) enum Frame {
+    Value(RedisResult<Value>),
+    Array(usize),
+}
+
+impl Frame {
+    pub(crate) fn into_value(self) -> RedisResult<Value> {
+        match self {
+            Frame::Value(value) => value,
+            Frame::Array(_) => unreachable!("array header read as a value"),
+        }
+    }
+}
+
+// Parsing a header and parsing a value have to share the partial state of a
+// decoder, so both are the same parser which only differs in its mode.
+fn frame<'a, I>(
+    array_header: bool,
+) -> impl combine::Parser<I, Output = Frame, PartialState = AnySendSyncPartialState>
+where
+    I: RangeStream<Token = u8, Range = &'a [u8]>,
+    I::Error: combine::ParseError<u8, &'a [u8], I::Position>,
+{
+    opaque!(any_send_sync_partial_state(if array_header {
+        let length = recognize(take_until_bytes(&b"\r\n"[..]).with(take(2).map(|_| ()))).and_then(
+            |line: &[u8]| {
+                str::from_utf8(&line[..line.len() - 2])
+                    .ok()
+                    .and_then(|line| line.trim().parse::<i64>().ok())
+                    .ok_or_else(|| {
+                        StreamErrorFor::<I>::message_static_message("Expected integer, got garbage")
+                    })
+            },
+        );
+        byte(b'*')
+            .with(length)
+            .map(|length| {
+                if length < 0 {
+                    Frame::Value(Ok(Value::Nil))
+                } else {
+                    Frame::Array(length as usize)
+                }
+            })
+            .or(value().map(Frame::Value))
+            .left()
+    } else {
+        value().map(Frame::Value).right()
+    }))
+}
+
 #[cfg(feature = "aio")]
 mod aio_support {
     use super::*;
@@ -248,6 +316,38 @@ mod aio_support {
             Ok(result) => result,
         }
     }
+
+    /// Parses a reply asynchronously, stopping after the header if it is an
+    /// array and `array_header` is set.
+    pub(crate) async fn parse_frame_async<R>(
+        decoder: &mut combine::stream::Decoder<AnySendSyncPartialState, PointerOffset<[u8]>>,
+        read: &mut R,
+        array_header: bool,
+    ) -> RedisResult<Frame>
+    where
+        R: AsyncRead + std::marker::Unpin,
+    {
+        let result = combine::decode_tokio!(*decoder, *read, frame(array_header), |input, _| {
+            combine::stream::easy::Stream::from(input)
+        });
+        match result {
+            Err(err) => Err(match err {
+                combine::stream::decoder::Error::Io { error, .. } => error.into(),
+                combine::stream::decoder::Error::Parse(err) => {
+                    if err.is_unexpected_end_of_input() {
+                        RedisError::from(io::Error::from(io::ErrorKind::UnexpectedEof))
+                    } else {
+                        let err = err
+                            .map_range(|range| format!("{:?}", range))
+                            .map_position(|pos| pos.translate_position(decoder.buffer()))
+                            .to_string();
+                        RedisError::from((ErrorKind::ResponseError, "parse error", err))
+                    }
+                }
+            }),
+            Ok(frame) => Ok(frame),
+        }
+    }
 }
 
 #[cfg(feature = "aio")]
@@ -283,9 +383,21 @@ impl Parser {
     // public api
 
     /// Parses synchronously into a single value from the reader.
-    pub fn parse_value<T: Read>(&mut self, mut reader: T) -> RedisResult<Value> {
+    pub fn parse_value<T: Read>(&mut self, reader: T) -> RedisResult<Value> {
+        self.parse_frame(reader, false)?.into_value()
+    }
+
+    /// Parses a reply from the reader, stopping after the header if it is an
+    /// array and `array_header` is set.  The returned error is only set if
+    /// the reply could not be read, errors sent by the server are part of the
+    /// frame.
+    pub(crate) fn parse_frame<T: Read>(
+        &mut self,
+        mut reader: T,
+        array_header: bool,
+    ) -> RedisResult<Frame> {
         let mut decoder = &mut self.decoder;
-        let result = combine::decode!(decoder, reader, value(), |input, _| {
+        let result = combine::decode!(decoder, reader, frame(array_header), |input, _| {
             combine::stream::easy::Stream::from(input)
         });
         match result {
@@ -303,7 +415,7 @@ impl Parser {
                     }
                 }
             }),
-            Ok(result) => result,
+            Ok(frame) => Ok(frame),
         }
     }
 }
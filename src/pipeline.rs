@@ -1,16 +1,42 @@
 #![macro_use]
 
-use crate::cmd::{cmd, cmd_len, Cmd};
+use crate::cmd::{cmd, cmd_len, Cmd, CommandNamePolicy};
 use crate::connection::ConnectionLike;
 use crate::types::{from_redis_value, ErrorKind, FromRedisValue, RedisResult, ToRedisArgs, Value};
-use std::collections::HashSet;
 
 /// Represents a redis command pipeline.
+///
+/// A pipeline containing a command with an empty list of values the server
+/// requires at least one of, like [`lpush`](Self::lpush) without elements,
+/// fails with [`ErrorKind::ClientError`] when it is queried, without sending
+/// any of its commands.  Adding commands cannot fail, as it returns the
+/// pipeline for chaining, so this is checked when the pipeline is sent
+/// rather than when the command is added.  Commands added with
+/// [`add_command`](Self::add_command) after calling
+/// [`Cmd::allow_empty_args`] on them are sent as they are.
 #[derive(Clone)]
 pub struct Pipeline {
     commands: Vec<Cmd>,
     transaction_mode: bool,
-    ignored_commands: HashSet<usize>,
+    replies: Vec<ReplyRecord>,
+}
+
+// Describes the replies of one entry of a pipeline, which is what `ignore`
+// applies to.  An entry is usually a single command, but it may consist of
+// several commands whose replies are returned as one bulk value, or of none.
+#[derive(Clone, Copy, Debug)]
+pub(crate) struct ReplyRecord {
+    pub(crate) count: usize,
+    pub(crate) ignored: bool,
+}
+
+impl ReplyRecord {
+    pub(crate) fn new(count: usize) -> ReplyRecord {
+        ReplyRecord {
+            count,
+            ignored: false,
+        }
+    }
 }
 
 /// A pipeline allows you to send multiple commands in one go to the
@@ -46,7 +72,7 @@ impl Pipeline {
         Pipeline {
             commands: Vec::with_capacity(capacity),
             transaction_mode: false,
-            ignored_commands: HashSet::new(),
+            replies: vec![],
         }
     }
 
@@ -79,6 +105,17 @@ impl Pipeline {
         write_pipeline(out, &self.commands, self.transaction_mode)
     }
 
+    // Fails if any of the commands was built with an empty argument list or
+    // a name `policy` rejects, so that nothing is sent.  This cannot happen
+    // when the commands are added as the chaining methods have no way to
+    // report errors.
+    fn check_args(&self, policy: CommandNamePolicy) -> RedisResult<()> {
+        self.commands.iter().try_for_each(|cmd| {
+            cmd.check_args()?;
+            cmd.check_name(policy)
+        })
+    }
+
     fn execute_pipelined(&self, con: &mut dyn ConnectionLike) -> RedisResult<Value> {
         Ok(self.make_pipeline_results(con.req_packed_commands(
             &encode_pipeline(&self.commands, false),
@@ -122,6 +159,7 @@ impl Pipeline {
     ///       it is necessary to call the `clear()` before inserting new commands.
     #[inline]
     pub fn query<T: FromRedisValue>(&self, con: &mut dyn ConnectionLike) -> RedisResult<T> {
+        self.check_args(con.command_name_policy())?;
         if !con.supports_pipelining() {
             fail!((
                 ErrorKind::ResponseError,
@@ -176,6 +214,7 @@ impl Pipeline {
     where
         C: crate::aio::ConnectionLike,
     {
+        self.check_args(con.command_name_policy())?;
         let v = if self.commands.is_empty() {
             return from_redis_value(&Value::Bulk(vec![]));
         } else if self.transaction_mode {
@@ -242,6 +281,18 @@ macro_rules! implement_pipeline_commands {
             #[inline]
             pub fn add_command(&mut self, cmd: Cmd) -> &mut Self {
                 self.commands.push(cmd);
+                self.replies.push(ReplyRecord::new(1));
+                self
+            }
+
+            /// Adds several commands as a single entry of the pipeline.  Their
+            /// replies are returned together as one bulk value and `ignore`
+            /// skips all of them.  An empty list does not add a value to the
+            /// result.
+            #[inline]
+            pub fn add_commands(&mut self, cmds: Vec<Cmd>) -> &mut Self {
+                self.replies.push(ReplyRecord::new(cmds.len()));
+                self.commands.extend(cmds);
                 self
             }
 
@@ -264,10 +315,9 @@ macro_rules! implement_pipeline_commands {
             /// you do not care about.
             #[inline]
             pub fn ignore(&mut self) -> &mut Self {
-                match self.commands.len() {
-                    0 => true,
-                    x => self.ignored_commands.insert(x - 1),
-                };
+                if let Some(record) = self.replies.last_mut() {
+                    record.ignored = true;
+                }
                 self
             }
 
@@ -291,7 +341,7 @@ macro_rules! implement_pipeline_commands {
             #[inline]
             pub fn clear(&mut self) {
                 self.commands.clear();
-                self.ignored_commands.clear();
+                self.replies.clear();
             }
 
             #[inline]
@@ -304,10 +354,17 @@ macro_rules! implement_pipeline_commands {
             }
 
             fn make_pipeline_results(&self, resp: Vec<Value>) -> Value {
+                let mut resp = resp.into_iter();
                 let mut rv = vec![];
-                for (idx, result) in resp.into_iter().enumerate() {
-                    if !self.ignored_commands.contains(&idx) {
-                        rv.push(result);
+                for record in &self.replies {
+                    let mut values: Vec<Value> = resp.by_ref().take(record.count).collect();
+                    if record.ignored {
+                        continue;
+                    }
+                    match record.count {
+                        0 => (),
+                        1 => rv.extend(values.pop()),
+                        _ => rv.push(Value::Bulk(values)),
                     }
                 }
                 Value::Bulk(rv)
@@ -323,3 +380,110 @@ macro_rules! implement_pipeline_commands {
 }
 
 implement_pipeline_commands!(Pipeline);
+
+#[cfg(test)]
+mod tests {
+    use super::Pipeline;
+    use crate::cmd::cmd;
+    use crate::connection::ConnectionLike;
+    use crate::types::{RedisResult, Value};
+
+    // Answers a pipeline with canned replies, one per command on the wire.
+    struct FakeConnection {
+        replies: Vec<Value>,
+    }
+
+    impl ConnectionLike for FakeConnection {
+        fn req_packed_command(&mut self, _cmd: &[u8]) -> RedisResult<Value> {
+            unreachable!()
+        }
+
+        fn req_packed_commands(
+            &mut self,
+            _cmd: &[u8],
+            offset: usize,
+            count: usize,
+        ) -> RedisResult<Vec<Value>> {
+            if offset == 0 {
+                assert_eq!(count, self.replies.len());
+                Ok(self.replies.clone())
+            } else {
+                // MULTI and the QUEUED replies are skipped.
+                assert_eq!((offset, count), (self.replies.len() + 1, 1));
+                Ok(vec![Value::Bulk(self.replies.clone())])
+            }
+        }
+
+        fn get_db(&self) -> i64 {
+            0
+        }
+
+        fn check_connection(&mut self) -> bool {
+            true
+        }
+
+        fn is_open(&self) -> bool {
+            true
+        }
+    }
+
+    fn status(s: &str) -> Value {
+        Value::Status(s.to_string())
+    }
+
+    fn data(s: &str) -> Value {
+        Value::Data(s.as_bytes().to_vec())
+    }
+
+    #[test]
+    fn test_ignore_applies_to_whole_entry() {
+        let mut con = FakeConnection {
+            replies: vec![status("OK"), data("1"), Value::Nil, Value::Int(2)],
+        };
+        let mut pipe = Pipeline::new();
+        pipe.set("a", 1)
+            .ignore()
+            .add_commands(vec![
+                cmd("GET").arg("a").clone(),
+                cmd("GET").arg("b").clone(),
+            ])
+            .incr("c", 1)
+            .ignore();
+        let (values,): ((Option<String>, Option<String>),) = pipe.query(&mut con).unwrap();
+        assert_eq!(values, (Some("1".to_string()), None));
+    }
+
+    #[test]
+    fn test_ignore_in_atomic_pipeline() {
+        let mut con = FakeConnection {
+            replies: vec![status("OK"), data("1"), Value::Nil, Value::Int(2)],
+        };
+        let (ok, count): (String, i32) = Pipeline::new()
+            .atomic()
+            .set("a", 1)
+            .add_commands(vec![
+                cmd("GET").arg("a").clone(),
+                cmd("GET").arg("b").clone(),
+            ])
+            .ignore()
+            .incr("c", 1)
+            .query(&mut con)
+            .unwrap();
+        assert_eq!((ok, count), ("OK".to_string(), 2));
+    }
+
+    #[test]
+    fn test_empty_entry_does_not_shift_ignore() {
+        let mut con = FakeConnection {
+            replies: vec![data("1"), Value::Int(2)],
+        };
+        let mut pipe = Pipeline::new();
+        pipe.get("a")
+            .add_commands(vec![])
+            .ignore()
+            .incr("c", 1)
+            .ignore();
+        let (value,): (String,) = pipe.query(&mut con).unwrap();
+        assert_eq!(value, "1");
+    }
+}
@@ -1,6 +1,9 @@
 //! Defines types to use with the streams commands.
 
-use crate::{from_redis_value, FromRedisValue, RedisResult, RedisWrite, ToRedisArgs, Value};
+use crate::{
+    from_redis_value, Commands, ConnectionLike, FromRedisValue, RedisResult, RedisWrite,
+    ToRedisArgs, Value,
+};
 
 use std::collections::HashMap;
 use std::io::{Error, ErrorKind};
@@ -111,6 +114,49 @@ impl ToRedisArgs for StreamClaimOptions {
     }
 }
 
+/// Builder options for [`xautoclaim_options`] command.
+///
+/// [`xautoclaim_options`]: ../trait.Commands.html#method.xautoclaim_options
+///
+#[derive(Default, Debug, Clone, Copy)]
+pub struct StreamAutoClaimOptions {
+    /// Set COUNT <count> cmd arg.
+    count: Option<usize>,
+    /// Set JUSTID cmd arg. Be advised: the claimed messages
+    /// are returned without their payload with this option.
+    justid: bool,
+}
+
+impl StreamAutoClaimOptions {
+    /// Set COUNT <count> cmd arg.
+    pub fn count(mut self, count: usize) -> Self {
+        self.count = Some(count);
+        self
+    }
+
+    /// Set JUSTID cmd arg to true. Be advised: the claimed messages
+    /// are returned without their payload with this option.
+    pub fn with_justid(mut self) -> Self {
+        self.justid = true;
+        self
+    }
+}
+
+impl ToRedisArgs for StreamAutoClaimOptions {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        if let Some(ref count) = self.count {
+            out.write_arg(b"COUNT");
+            out.write_arg(format!("{}", count).as_bytes());
+        }
+        if self.justid {
+            out.write_arg(b"JUSTID");
+        }
+    }
+}
+
 /// Argument to `StreamReadOptions`
 /// Represents the Redis GROUP <groupname> <consumername> cmd arg.
 /// This option will toggle the cmd from XREAD to XREADGROUP
@@ -245,6 +291,22 @@ pub struct StreamClaimReply {
     pub ids: Vec<StreamId>,
 }
 
+/// Reply type used with [`xautoclaim`] command.
+///
+/// [`xautoclaim`]: ../trait.Commands.html#method.xautoclaim
+///
+#[derive(Default, Debug, Clone)]
+pub struct StreamAutoClaimReply {
+    /// The id to pass as `start` to the next call to continue scanning,
+    /// `0-0` once the whole pending entries list was scanned.
+    pub next_stream_id: String,
+    /// The claimed messages.  With JUSTID only their `id` is set.
+    pub claimed: Vec<StreamId>,
+    /// The ids of messages that no longer exist in the stream and were
+    /// removed from the pending entries list (since Redis 7.0).
+    pub deleted_ids: Vec<String>,
+}
+
 /// Reply type used with [`xpending`] command.
 ///
 /// Data returned here were fetched from the stream without
@@ -326,14 +388,24 @@ pub struct StreamInfoStreamReply {
     /// Details about the radix tree representing the stream mostly
     /// useful for optimization and debugging tasks.
     pub radix_tree_keys: usize,
+    /// The number of nodes in the radix tree representing the stream.
+    pub radix_tree_nodes: usize,
     /// The number of consumer groups associated with the stream.
     pub groups: usize,
     /// Number of elements of the stream.
     pub length: usize,
-    /// The very first entry in the stream.
+    /// The very first entry in the stream.  Empty if the stream has no
+    /// entries.
     pub first_entry: StreamId,
-    /// The very last entry in the stream.
+    /// The very last entry in the stream.  Empty if the stream has no
+    /// entries.
     pub last_entry: StreamId,
+    /// The largest ID that was deleted from the stream (Redis 7 and later).
+    pub max_deleted_entry_id: Option<String>,
+    /// The number of entries ever added to the stream (Redis 7 and later).
+    pub entries_added: Option<usize>,
+    /// The first entry ID that was not deleted (Redis 7 and later).
+    pub recorded_first_entry_id: Option<String>,
 }
 
 /// Reply type used with [`xinfo_consumer`] command, an array of every
@@ -418,6 +490,9 @@ pub struct StreamKey {
 }
 
 /// Represents a stream `id` and its field/values as a `HashMap`
+///
+/// The entries returned by `XRANGE` and `XREVRANGE` can be decoded as a
+/// `Vec<StreamId>` as well as a [`StreamRangeReply`].
 #[derive(Default, Debug, Clone)]
 pub struct StreamId {
     /// The stream `id` (entry ID) of this particular message.
@@ -487,6 +562,13 @@ impl FromRedisValue for StreamReadReply {
     }
 }
 
+impl FromRedisValue for StreamId {
+    fn from_redis_value(v: &Value) -> RedisResult<Self> {
+        let (id, map): (String, HashMap<String, Value>) = from_redis_value(v)?;
+        Ok(StreamId { id, map })
+    }
+}
+
 impl FromRedisValue for StreamRangeReply {
     fn from_redis_value(v: &Value) -> RedisResult<Self> {
         let rows: Vec<HashMap<String, HashMap<String, Value>>> = from_redis_value(v)?;
@@ -509,6 +591,105 @@ impl FromRedisValue for StreamClaimReply {
     }
 }
 
+impl FromRedisValue for StreamAutoClaimReply {
+    fn from_redis_value(v: &Value) -> RedisResult<Self> {
+        let items = match v {
+            Value::Bulk(items) => items,
+            _ => fail!((
+                crate::types::ErrorKind::TypeError,
+                "Cannot parse XAUTOCLAIM reply"
+            )),
+        };
+        let (next_stream_id, claimed, deleted_ids) = match &items[..] {
+            [next, claimed] => (next, claimed, None),
+            [next, claimed, deleted] => (next, claimed, Some(deleted)),
+            _ => fail!((
+                crate::types::ErrorKind::TypeError,
+                "Cannot parse XAUTOCLAIM reply"
+            )),
+        };
+        let claimed =
+            match claimed {
+                Value::Bulk(entries) => entries
+                    .iter()
+                    .filter_map(|entry| match entry {
+                        // Redis 6.2 reports deleted messages as nil entries.
+                        Value::Nil => None,
+                        Value::Bulk(_) => Some(from_redis_value(entry).map(
+                            |(id, map): (String, HashMap<String, Value>)| StreamId { id, map },
+                        )),
+                        // JUSTID
+                        _ => Some(from_redis_value(entry).map(|id| StreamId {
+                            id,
+                            map: HashMap::new(),
+                        })),
+                    })
+                    .collect::<RedisResult<_>>()?,
+                _ => fail!((
+                    crate::types::ErrorKind::TypeError,
+                    "Cannot parse XAUTOCLAIM reply"
+                )),
+            };
+        Ok(StreamAutoClaimReply {
+            next_stream_id: from_redis_value(next_stream_id)?,
+            claimed,
+            deleted_ids: match deleted_ids {
+                Some(deleted_ids) => from_redis_value(deleted_ids)?,
+                None => Vec::new(),
+            },
+        })
+    }
+}
+
+/// Claims every message of the pending entries list of `group` that was
+/// idle for at least `min_idle_time` milliseconds for `consumer`.
+///
+/// `XAUTOCLAIM` scans the pending entries list in batches of `COUNT`
+/// entries.  This calls it with the cursor of each reply until the whole list
+/// was scanned, and returns the claimed messages and deleted ids of all
+/// batches in one reply.
+///
+/// ```rust,no_run
+/// # fn do_something() -> redis::RedisResult<()> {
+/// use redis::streams::{xautoclaim_all, StreamAutoClaimOptions};
+///
+/// let client = redis::Client::open("redis://127.0.0.1/")?;
+/// let mut con = client.get_connection()?;
+/// let reply = xautoclaim_all(&mut con, "k1", "g1", "c1", 60_000, StreamAutoClaimOptions::default())?;
+/// for message in reply.claimed {
+///     // process the message, then XACK it
+/// }
+/// # Ok(()) }
+/// ```
+pub fn xautoclaim_all<C, K, G, N>(
+    con: &mut C,
+    key: K,
+    group: G,
+    consumer: N,
+    min_idle_time: usize,
+    options: StreamAutoClaimOptions,
+) -> RedisResult<StreamAutoClaimReply>
+where
+    C: ConnectionLike,
+    K: ToRedisArgs,
+    G: ToRedisArgs,
+    N: ToRedisArgs,
+{
+    let mut all = StreamAutoClaimReply::default();
+    let mut start = "0-0".to_string();
+    loop {
+        let reply: StreamAutoClaimReply =
+            con.xautoclaim_options(&key, &group, &consumer, min_idle_time, &start, options)?;
+        all.claimed.extend(reply.claimed);
+        all.deleted_ids.extend(reply.deleted_ids);
+        if reply.next_stream_id == "0-0" {
+            all.next_stream_id = reply.next_stream_id;
+            return Ok(all);
+        }
+        start = reply.next_stream_id;
+    }
+}
+
 type SPRInner = (
     usize,
     Option<String>,
@@ -606,9 +787,12 @@ impl FromRedisValue for StreamInfoStreamReply {
         if let Some(v) = &map.get("last-generated-id") {
             reply.last_generated_id = from_redis_value(v)?;
         }
-        if let Some(v) = &map.get("radix-tree-nodes") {
+        if let Some(v) = &map.get("radix-tree-keys") {
             reply.radix_tree_keys = from_redis_value(v)?;
         }
+        if let Some(v) = &map.get("radix-tree-nodes") {
+            reply.radix_tree_nodes = from_redis_value(v)?;
+        }
         if let Some(v) = &map.get("groups") {
             reply.groups = from_redis_value(v)?;
         }
@@ -621,6 +805,15 @@ impl FromRedisValue for StreamInfoStreamReply {
         if let Some(v) = &map.get("last-entry") {
             reply.last_entry = StreamId::from_bulk_value(v)?;
         }
+        if let Some(v) = &map.get("max-deleted-entry-id") {
+            reply.max_deleted_entry_id = from_redis_value(v)?;
+        }
+        if let Some(v) = &map.get("entries-added") {
+            reply.entries_added = from_redis_value(v)?;
+        }
+        if let Some(v) = &map.get("recorded-first-entry-id") {
+            reply.recorded_first_entry_id = from_redis_value(v)?;
+        }
         Ok(reply)
     }
 }
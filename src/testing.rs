@@ -0,0 +1,28 @@
+//! Helpers for writing deterministic tests against a redis server.
+//!
+//! With the `test-server` feature, [`TestServer`] and [`TestCluster`] start
+//! disposable `redis-server` processes, so that tests do not depend on a
+//! server running at a known address.
+//!
+//! With the `debug-commands` feature, [`freeze_expiry`] and
+//! [`wait_for_expired`] make expiry deterministic.  Redis removes expired
+//! keys both lazily, when they are accessed, and actively, from a background
+//! cycle that samples keys at unpredictable times.  These helpers switch the
+//! active cycle off for the duration of a test and wait for keys to expire
+//! without relying on fixed sleeps.
+//!
+//! Redis 7 and later refuse `DEBUG` commands unless the server was started
+//! with `enable-debug-command` set to `yes` or `local`.
+
+#[cfg(feature = "debug-commands")]
+mod expiry;
+#[cfg(feature = "test-server")]
+mod server;
+
+#[cfg(feature = "debug-commands")]
+#[cfg_attr(docsrs, doc(cfg(feature = "debug-commands")))]
+pub use self::expiry::{freeze_expiry, wait_for_expired, FreezeExpiryGuard};
+
+#[cfg(feature = "test-server")]
+#[cfg_attr(docsrs, doc(cfg(feature = "test-server")))]
+pub use self::server::{TestCluster, TestServer, TestServerBuilder, TlsFiles};
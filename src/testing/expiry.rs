@@ -0,0 +1,96 @@
+use std::ops::{Deref, DerefMut};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::commands::Commands;
+use crate::connection::ConnectionLike;
+use crate::types::{RedisResult, ToRedisArgs};
+
+/// The longest pause between two polls of [`wait_for_expired`].
+const MAX_POLL_INTERVAL: Duration = Duration::from_millis(100);
+
+/// A guard which keeps the active expiry cycle of the server disabled.
+///
+/// Created by [`freeze_expiry`].  The connection stays usable through the
+/// guard and active expiry is enabled again when the guard is dropped.
+pub struct FreezeExpiryGuard<'a, C: ConnectionLike> {
+    con: &'a mut C,
+}
+
+/// Disables the active expiry cycle of the server until the returned guard
+/// is dropped.
+///
+/// While frozen, keys with an expiry are only removed when they are accessed,
+/// so the moment a key disappears is determined by the test alone.
+///
+/// ```rust,no_run
+/// # fn do_something() -> redis::RedisResult<()> {
+/// use redis::Commands;
+/// use std::time::Duration;
+///
+/// let client = redis::Client::open("redis://127.0.0.1/")?;
+/// let mut con = client.get_connection()?;
+/// let mut con = redis::testing::freeze_expiry(&mut con)?;
+///
+/// let () = con.pset_ex("key", 42, 100)?;
+/// assert!(redis::testing::wait_for_expired(&mut *con, "key", Duration::from_secs(1))?);
+/// # Ok(()) }
+/// ```
+pub fn freeze_expiry<C: ConnectionLike>(con: &mut C) -> RedisResult<FreezeExpiryGuard<'_, C>> {
+    con.debug_set_active_expire::<()>(false)?;
+    Ok(FreezeExpiryGuard { con })
+}
+
+impl<'a, C: ConnectionLike> Deref for FreezeExpiryGuard<'a, C> {
+    type Target = C;
+
+    fn deref(&self) -> &C {
+        self.con
+    }
+}
+
+impl<'a, C: ConnectionLike> DerefMut for FreezeExpiryGuard<'a, C> {
+    fn deref_mut(&mut self) -> &mut C {
+        self.con
+    }
+}
+
+impl<'a, C: ConnectionLike> Drop for FreezeExpiryGuard<'a, C> {
+    fn drop(&mut self) {
+        // There is no way to report an error from drop and the connection
+        // may already be broken, in which case there is nothing to restore.
+        let _: RedisResult<()> = self.con.debug_set_active_expire(true);
+    }
+}
+
+/// Waits until `key` no longer exists, polling `PTTL` with backoff.
+///
+/// Returns `true` once the key is gone and `false` if it still exists after
+/// `max_wait`.  Polling the key also triggers its lazy expiry, so this works
+/// while the active expiry cycle is frozen.
+pub fn wait_for_expired<C: ConnectionLike, K: ToRedisArgs>(
+    con: &mut C,
+    key: K,
+    max_wait: Duration,
+) -> RedisResult<bool> {
+    let deadline = Instant::now() + max_wait;
+    let mut backoff = Duration::from_millis(1);
+    loop {
+        // -2 means the key does not exist, -1 that it has no expiry.
+        let pttl: i64 = con.pttl(&key)?;
+        if pttl == -2 {
+            return Ok(true);
+        }
+        let now = Instant::now();
+        if now >= deadline {
+            return Ok(false);
+        }
+        let pause = if pttl > 0 {
+            Duration::from_millis(pttl as u64)
+        } else {
+            backoff = (backoff * 2).min(MAX_POLL_INTERVAL);
+            backoff
+        };
+        thread::sleep(pause.min(MAX_POLL_INTERVAL).min(deadline - now));
+    }
+}
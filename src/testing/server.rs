@@ -0,0 +1,400 @@
+use std::env;
+use std::fs;
+use std::net::TcpListener;
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command, Stdio};
+use std::thread;
+use std::time::{Duration, Instant};
+
+use crate::client::Client;
+use crate::cmd::cmd;
+use crate::connection::{Connection, ConnectionAddr, ConnectionInfo};
+use crate::types::{ErrorKind, InfoDict, RedisResult};
+
+/// The number of hash slots of a redis cluster.
+const SLOT_COUNT: usize = 16384;
+
+/// How long to wait for a server or cluster to come up by default.
+const DEFAULT_STARTUP_TIMEOUT: Duration = Duration::from_secs(10);
+
+/// The highest port a server is started on.  The cluster bus listens on the
+/// port plus 10000, which has to be a valid port as well.
+const MAX_PORT: u16 = 55535;
+
+/// How often starting a server is tried, each time on another port.
+const START_ATTEMPTS: usize = 3;
+
+/// The certificate files a [`TestServer`] serves TLS connections with.
+#[derive(Clone, Debug)]
+pub struct TlsFiles {
+    /// The certificate of the server.
+    pub cert: PathBuf,
+    /// The private key of the server.
+    pub key: PathBuf,
+    /// The certificate of the authority that signed `cert`.
+    pub ca_cert: PathBuf,
+}
+
+/// Configures a [`TestServer`] before it is started.
+///
+/// Created by [`TestServer::builder`].
+#[derive(Clone, Debug)]
+pub struct TestServerBuilder {
+    executable: PathBuf,
+    config: Vec<(String, String)>,
+    modules: Vec<PathBuf>,
+    cluster: bool,
+    tls: Option<TlsFiles>,
+    startup_timeout: Duration,
+}
+
+impl TestServerBuilder {
+    /// Sets the `redis-server` binary to run.  Defaults to `redis-server`
+    /// from the `PATH`.
+    pub fn executable<P: Into<PathBuf>>(mut self, executable: P) -> Self {
+        self.executable = executable.into();
+        self
+    }
+
+    /// Passes a configuration parameter on the command line of the server,
+    /// e.g. `config("maxmemory", "10mb")`.
+    pub fn config<N: Into<String>, V: Into<String>>(mut self, name: N, value: V) -> Self {
+        self.config.push((name.into(), value.into()));
+        self
+    }
+
+    /// Loads the module at `path` on startup.
+    pub fn module<P: Into<PathBuf>>(mut self, path: P) -> Self {
+        self.modules.push(path.into());
+        self
+    }
+
+    /// Runs the server in cluster mode.  The server does not serve any
+    /// slots until it is joined to a cluster, see
+    /// [`start_cluster`](Self::start_cluster).
+    pub fn cluster(mut self, cluster: bool) -> Self {
+        self.cluster = cluster;
+        self
+    }
+
+    /// Serves TLS connections only, using the given certificates.
+    /// Connecting to the server needs the `tls` feature.
+    pub fn tls(mut self, files: TlsFiles) -> Self {
+        self.tls = Some(files);
+        self
+    }
+
+    /// Sets how long to wait for the server to answer `PING`.  Defaults to
+    /// 10 seconds.
+    pub fn startup_timeout(mut self, timeout: Duration) -> Self {
+        self.startup_timeout = timeout;
+        self
+    }
+
+    /// Starts the server on a random port and waits until it answers `PING`.
+    ///
+    /// The port is only known to be free until the server binds it, so if
+    /// the server does not come up, it is started again on another port a
+    /// few times before giving up.
+    pub fn start(&self) -> RedisResult<TestServer> {
+        let mut attempts = 1;
+        loop {
+            match self.start_on(free_port()?) {
+                Err(_) if attempts < START_ATTEMPTS => attempts += 1,
+                result => return result,
+            }
+        }
+    }
+
+    fn start_on(&self, port: u16) -> RedisResult<TestServer> {
+        let dir = env::temp_dir().join(format!("redis-rs-{}-{}", std::process::id(), port));
+        fs::create_dir_all(&dir)?;
+
+        let mut command = Command::new(&self.executable);
+        command
+            .current_dir(&dir)
+            .stdout(Stdio::null())
+            .stderr(Stdio::null())
+            .arg("--bind")
+            .arg("127.0.0.1")
+            .arg("--dir")
+            .arg(&dir)
+            .arg("--save")
+            .arg("");
+        let addr = match &self.tls {
+            Some(files) => {
+                command
+                    .arg("--port")
+                    .arg("0")
+                    .arg("--tls-port")
+                    .arg(port.to_string())
+                    .arg("--tls-cert-file")
+                    .arg(&files.cert)
+                    .arg("--tls-key-file")
+                    .arg(&files.key)
+                    .arg("--tls-ca-cert-file")
+                    .arg(&files.ca_cert)
+                    .arg("--tls-auth-clients")
+                    .arg("no");
+                if self.cluster {
+                    command.arg("--tls-cluster").arg("yes");
+                }
+                ConnectionAddr::TcpTls {
+                    host: "127.0.0.1".to_string(),
+                    port,
+                    insecure: true,
+                }
+            }
+            None => {
+                command.arg("--port").arg(port.to_string());
+                ConnectionAddr::Tcp("127.0.0.1".to_string(), port)
+            }
+        };
+        if self.cluster {
+            command
+                .arg("--cluster-enabled")
+                .arg("yes")
+                .arg("--cluster-config-file")
+                .arg("nodes.conf");
+        }
+        for module in &self.modules {
+            command.arg("--loadmodule").arg(module);
+        }
+        for (name, value) in &self.config {
+            command.arg(format!("--{}", name)).arg(value);
+        }
+
+        let process = match command.spawn() {
+            Ok(process) => process,
+            Err(err) => {
+                let _ = fs::remove_dir_all(&dir);
+                return Err(err.into());
+            }
+        };
+        // From here on dropping the server cleans up after a failed start.
+        let mut server = TestServer { process, dir, addr };
+        server.wait_until_ready(self.startup_timeout)?;
+        Ok(server)
+    }
+
+    /// Starts `masters` servers in cluster mode, joins them with
+    /// `CLUSTER MEET` and splits the hash slots evenly between them with
+    /// `CLUSTER ADDSLOTS`.  Returns once every node reports the cluster as
+    /// up.
+    pub fn start_cluster(&self, masters: usize) -> RedisResult<TestCluster> {
+        if masters == 0 {
+            fail!((
+                ErrorKind::InvalidClientConfig,
+                "A cluster needs at least one master"
+            ));
+        }
+        let builder = self.clone().cluster(true);
+        let servers = (0..masters)
+            .map(|_| builder.start())
+            .collect::<RedisResult<Vec<_>>>()?;
+        let mut connections = servers
+            .iter()
+            .map(TestServer::connection)
+            .collect::<RedisResult<Vec<_>>>()?;
+
+        let seed_port = match servers[0].port() {
+            Some(port) => port,
+            None => fail!((
+                ErrorKind::InvalidClientConfig,
+                "Cluster nodes must listen on TCP"
+            )),
+        };
+        for con in &mut connections[1..] {
+            cmd("CLUSTER")
+                .arg("MEET")
+                .arg("127.0.0.1")
+                .arg(seed_port)
+                .query::<()>(con)?;
+        }
+        for (idx, con) in connections.iter_mut().enumerate() {
+            let slots: Vec<usize> =
+                (SLOT_COUNT * idx / masters..SLOT_COUNT * (idx + 1) / masters).collect();
+            cmd("CLUSTER").arg("ADDSLOTS").arg(slots).query::<()>(con)?;
+        }
+
+        let deadline = Instant::now() + self.startup_timeout;
+        for con in &mut connections {
+            loop {
+                let info: InfoDict = cmd("CLUSTER").arg("INFO").query(con)?;
+                let up = info.get("cluster_state") == Some("ok".to_string())
+                    && info.get("cluster_known_nodes") == Some(masters);
+                if up {
+                    break;
+                }
+                if Instant::now() >= deadline {
+                    fail!((
+                        ErrorKind::ClusterDown,
+                        "Test cluster did not come up in time"
+                    ));
+                }
+                thread::sleep(Duration::from_millis(50));
+            }
+        }
+        Ok(TestCluster { servers })
+    }
+}
+
+// Asks the OS for a free port that leaves room for the cluster bus.
+fn free_port() -> RedisResult<u16> {
+    loop {
+        let port = TcpListener::bind("127.0.0.1:0")?.local_addr()?.port();
+        if port <= MAX_PORT {
+            return Ok(port);
+        }
+    }
+}
+
+/// A disposable `redis-server` process for tests.
+///
+/// The server listens on a random port of `127.0.0.1`, keeps its files in a
+/// fresh temporary directory and does not save snapshots.  It is killed and
+/// its directory is removed when the `TestServer` is dropped.
+///
+/// ```rust,no_run
+/// # fn do_something() -> redis::RedisResult<()> {
+/// use redis::testing::TestServer;
+/// use redis::Commands;
+///
+/// let server = TestServer::builder().config("maxmemory", "10mb").start()?;
+/// let mut con = server.connection()?;
+/// let () = con.set("key", 42)?;
+/// # Ok(()) }
+/// ```
+pub struct TestServer {
+    process: Child,
+    dir: PathBuf,
+    addr: ConnectionAddr,
+}
+
+impl TestServer {
+    /// Starts a server with the default configuration.
+    pub fn start() -> RedisResult<TestServer> {
+        TestServer::builder().start()
+    }
+
+    /// Creates a builder to configure the server before starting it.
+    pub fn builder() -> TestServerBuilder {
+        TestServerBuilder {
+            executable: PathBuf::from("redis-server"),
+            config: vec![],
+            modules: vec![],
+            cluster: false,
+            tls: None,
+            startup_timeout: DEFAULT_STARTUP_TIMEOUT,
+        }
+    }
+
+    /// Returns the address the server listens on.
+    pub fn addr(&self) -> &ConnectionAddr {
+        &self.addr
+    }
+
+    /// Returns the port the server listens on, or `None` if it does not
+    /// listen on TCP.
+    pub fn port(&self) -> Option<u16> {
+        match self.addr {
+            ConnectionAddr::Tcp(_, port) | ConnectionAddr::TcpTls { port, .. } => Some(port),
+            ConnectionAddr::Unix(_) => None,
+        }
+    }
+
+    /// Returns the temporary directory of the server.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the connection info of the server.
+    pub fn connection_info(&self) -> ConnectionInfo {
+        ConnectionInfo {
+            addr: self.addr.clone(),
+            redis: Default::default(),
+            tcp_keepalive: None,
+        }
+    }
+
+    /// Creates a client for the server.
+    pub fn client(&self) -> RedisResult<Client> {
+        Client::open(self.connection_info())
+    }
+
+    /// Opens a new connection to the server.
+    pub fn connection(&self) -> RedisResult<Connection> {
+        self.client()?.get_connection()
+    }
+
+    fn wait_until_ready(&mut self, timeout: Duration) -> RedisResult<()> {
+        let deadline = Instant::now() + timeout;
+        let client = self.client()?;
+        loop {
+            let err = match client
+                .get_connection()
+                .and_then(|mut con| cmd("PING").query::<()>(&mut con))
+            {
+                Ok(()) => return Ok(()),
+                Err(err) => err,
+            };
+            if let Some(status) = self.process.try_wait()? {
+                fail!((
+                    ErrorKind::IoError,
+                    "Test server exited during startup",
+                    status.to_string()
+                ));
+            }
+            let starting = err.is_connection_refusal() || err.kind() == ErrorKind::BusyLoadingError;
+            if !starting || Instant::now() >= deadline {
+                return Err(err);
+            }
+            thread::sleep(Duration::from_millis(10));
+        }
+    }
+}
+
+impl Drop for TestServer {
+    fn drop(&mut self) {
+        let _ = self.process.kill();
+        let _ = self.process.wait();
+        let _ = fs::remove_dir_all(&self.dir);
+    }
+}
+
+/// A disposable redis cluster of [`TestServer`]s, one per master and
+/// without replicas.
+///
+/// Created by [`TestCluster::start`] or
+/// [`TestServerBuilder::start_cluster`].  All servers are stopped when the
+/// `TestCluster` is dropped.
+pub struct TestCluster {
+    servers: Vec<TestServer>,
+}
+
+impl TestCluster {
+    /// Starts a cluster of `masters` servers with the default configuration.
+    pub fn start(masters: usize) -> RedisResult<TestCluster> {
+        TestServer::builder().start_cluster(masters)
+    }
+
+    /// Returns the servers of the cluster.
+    pub fn servers(&self) -> &[TestServer] {
+        &self.servers
+    }
+
+    /// Returns the connection infos of all nodes.
+    pub fn nodes(&self) -> Vec<ConnectionInfo> {
+        self.servers
+            .iter()
+            .map(TestServer::connection_info)
+            .collect()
+    }
+
+    /// Creates a cluster client for the cluster.
+    #[cfg(feature = "cluster")]
+    #[cfg_attr(docsrs, doc(cfg(feature = "cluster")))]
+    pub fn client(&self) -> RedisResult<crate::cluster::ClusterClient> {
+        crate::cluster::ClusterClient::open(self.nodes())
+    }
+}
@@ -8,6 +8,7 @@ use std::hash::{BuildHasher, Hash};
 use std::io;
 use std::str::{from_utf8, Utf8Error};
 use std::string::FromUtf8Error;
+use std::time::{Duration, SystemTime, UNIX_EPOCH};
 
 macro_rules! invalid_type_error {
     ($v:expr, $det:expr) => {{
@@ -39,6 +40,164 @@ pub enum Expiry {
     PERSIST,
 }
 
+/// The expiry of a key, for use with `SET` and `GETEX`.
+///
+/// Unlike [`Expiry`] this does not make the caller pick the unit: durations
+/// and points in time are sent in seconds (`EX`/`EXAT`) if they are whole
+/// seconds and in milliseconds (`PX`/`PXAT`) otherwise.  Precision below a
+/// millisecond is rounded up to the next millisecond.  The server rejects
+/// an expiry of zero, so a zero duration and points in time at or before
+/// the epoch are sent as one millisecond, which has already passed as well.
+///
+/// ```rust,no_run
+/// # fn do_something() -> redis::RedisResult<()> {
+/// # let client = redis::Client::open("redis://127.0.0.1/").unwrap();
+/// # let mut con = client.get_connection().unwrap();
+/// use redis::{Commands, Expiration};
+/// use std::time::Duration;
+///
+/// // Sent as `SET key 42 PX 1500`.
+/// let _: () = con.set_with_expiration("key", 42, Expiration::In(Duration::from_millis(1500)))?;
+/// // Sent as `GETEX key PERSIST`.
+/// let value: i32 = con.get_with_expiration("key", Expiration::Persist)?;
+/// # Ok(()) }
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Expiration {
+    /// Expire the key at the given point in time.
+    At(SystemTime),
+    /// Expire the key once the given duration has passed.
+    In(Duration),
+    /// Remove the expiry of the key.  Only valid with `GETEX`, `SET`
+    /// rejects it without sending the command.
+    Persist,
+    /// Retain the current expiry of the key.  Only valid with `SET`, `GETEX`
+    /// rejects it without sending the command.
+    Keep,
+}
+
+impl From<Expiry> for Expiration {
+    fn from(expiry: Expiry) -> Expiration {
+        match expiry {
+            Expiry::EX(secs) => Expiration::In(Duration::from_secs(secs as u64)),
+            Expiry::PX(ms) => Expiration::In(Duration::from_millis(ms as u64)),
+            Expiry::EXAT(secs) => Expiration::At(UNIX_EPOCH + Duration::from_secs(secs as u64)),
+            Expiry::PXAT(ms) => Expiration::At(UNIX_EPOCH + Duration::from_millis(ms as u64)),
+            Expiry::PERSIST => Expiration::Persist,
+        }
+    }
+}
+
+impl ToRedisArgs for Expiration {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        let (duration, secs_option, ms_option) = match *self {
+            Expiration::In(duration) => (duration, "EX", "PX"),
+            Expiration::At(time) => (
+                time.duration_since(UNIX_EPOCH).unwrap_or_default(),
+                "EXAT",
+                "PXAT",
+            ),
+            Expiration::Persist => return out.write_arg(b"PERSIST"),
+            Expiration::Keep => return out.write_arg(b"KEEPTTL"),
+        };
+        let mut ms = duration.as_millis() as u64;
+        if duration.subsec_nanos() % 1_000_000 != 0 || ms == 0 {
+            ms += 1;
+        }
+        let (secs, subsec_ms) = (ms / 1000, ms % 1000);
+        if subsec_ms == 0 {
+            out.write_arg(secs_option.as_bytes());
+            secs.write_redis_args(out);
+        } else {
+            out.write_arg(ms_option.as_bytes());
+            ms.write_redis_args(out);
+        }
+    }
+
+    fn is_single_arg(&self) -> bool {
+        false
+    }
+}
+
+/// A glob-style pattern as used by `KEYS`, the `MATCH` option of the `SCAN`
+/// family and the pattern based pubsub commands.
+///
+/// Building patterns by concatenating user input is error prone: a segment
+/// containing `*`, `?` or `[` matches far more than intended.  [`literal`]
+/// and [`prefix`] escape their input so that it only matches itself.
+///
+/// ```rust,no_run
+/// # fn do_something() -> redis::RedisResult<()> {
+/// # let client = redis::Client::open("redis://127.0.0.1/").unwrap();
+/// # let mut con = client.get_connection().unwrap();
+/// use redis::{Commands, Pattern};
+///
+/// let user = "bob*";
+/// // Only matches keys starting with `session:bob*:`, not `session:bobby:`.
+/// let keys: Vec<String> = con.keys(Pattern::prefix(&format!("session:{}:", user)))?;
+/// # Ok(()) }
+/// ```
+///
+/// [`literal`]: Pattern::literal
+/// [`prefix`]: Pattern::prefix
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+pub struct Pattern(String);
+
+impl Pattern {
+    /// A pattern matching exactly `s`.
+    pub fn literal(s: &str) -> Pattern {
+        let mut pattern = String::with_capacity(s.len());
+        push_escaped(&mut pattern, s);
+        Pattern(pattern)
+    }
+
+    /// A pattern matching everything that starts with `s`.
+    pub fn prefix(s: &str) -> Pattern {
+        let mut pattern = String::with_capacity(s.len() + 1);
+        push_escaped(&mut pattern, s);
+        pattern.push('*');
+        Pattern(pattern)
+    }
+
+    /// A pattern taken as is, with `*`, `?`, `[...]` and `\` keeping their
+    /// special meaning.
+    pub fn raw<S: Into<String>>(pattern: S) -> Pattern {
+        Pattern(pattern.into())
+    }
+
+    /// Returns the pattern as it is sent to the server.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+}
+
+fn push_escaped(pattern: &mut String, s: &str) {
+    for c in s.chars() {
+        if let '*' | '?' | '[' | ']' | '\\' = c {
+            pattern.push('\\');
+        }
+        pattern.push(c);
+    }
+}
+
+impl fmt::Display for Pattern {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+impl ToRedisArgs for Pattern {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        out.write_arg(self.0.as_bytes())
+    }
+}
+
 /// Helper enum that is used in some situations to describe
 /// the behavior of arguments in a numeric context.
 #[derive(PartialEq, Eq, Clone, Debug, Copy)]
@@ -93,6 +252,14 @@ pub enum ErrorKind {
     ExtensionError,
     /// Attempt to write to a read-only server
     ReadOnly,
+    /// The connection was closed by the client, e.g. because it is being drained.
+    ClientClosed,
+    /// `OBJECT FREQ` or `OBJECT IDLETIME` was called while the configured
+    /// `maxmemory-policy` does not track the requested information.
+    WrongEvictionPolicy,
+    /// A request was not sent to a cluster node because the circuit breaker
+    /// of the node is open.
+    CircuitOpen,
 }
 
 /// Internal low-level redis value enum.
@@ -254,9 +421,13 @@ impl From<io::Error> for RedisError {
 }
 
 impl From<Utf8Error> for RedisError {
-    fn from(_: Utf8Error) -> RedisError {
+    fn from(err: Utf8Error) -> RedisError {
         RedisError {
-            repr: ErrorRepr::WithDescription(ErrorKind::TypeError, "Invalid UTF-8"),
+            repr: ErrorRepr::WithDescriptionAndDetail(
+                ErrorKind::TypeError,
+                "Invalid UTF-8",
+                format!("invalid byte at offset {}", err.valid_up_to()),
+            ),
         }
     }
 }
@@ -275,10 +446,8 @@ impl From<native_tls::Error> for RedisError {
 }
 
 impl From<FromUtf8Error> for RedisError {
-    fn from(_: FromUtf8Error) -> RedisError {
-        RedisError {
-            repr: ErrorRepr::WithDescription(ErrorKind::TypeError, "Cannot convert from UTF-8"),
-        }
+    fn from(err: FromUtf8Error) -> RedisError {
+        err.utf8_error().into()
     }
 }
 
@@ -366,7 +535,7 @@ impl RedisError {
     /// Returns the raw error code if available.
     pub fn code(&self) -> Option<&str> {
         match self.kind() {
-            ErrorKind::ResponseError => Some("ERR"),
+            ErrorKind::ResponseError | ErrorKind::WrongEvictionPolicy => Some("ERR"),
             ErrorKind::ExecAbortError => Some("EXECABORT"),
             ErrorKind::BusyLoadingError => Some("LOADING"),
             ErrorKind::NoScriptError => Some("NOSCRIPT"),
@@ -404,6 +573,9 @@ impl RedisError {
             ErrorKind::ExtensionError => "extension error",
             ErrorKind::ClientError => "client error",
             ErrorKind::ReadOnly => "read-only",
+            ErrorKind::ClientClosed => "client closed",
+            ErrorKind::WrongEvictionPolicy => "wrong eviction policy",
+            ErrorKind::CircuitOpen => "circuit open",
         }
     }
 
@@ -505,7 +677,7 @@ impl RedisError {
     ///
     /// The `ioerror_description` parameter will be prepended to the message in
     /// case an `IoError` is found.
-    #[cfg(feature = "connection-manager")] // Used to avoid "unused method" warning
+    #[cfg(any(feature = "aio", feature = "cluster"))] // Used to avoid "unused method" warning
     pub(crate) fn clone_mostly(&self, ioerror_description: &'static str) -> Self {
         let repr = match self.repr {
             ErrorRepr::WithDescription(kind, desc) => ErrorRepr::WithDescription(kind, desc),
@@ -536,6 +708,17 @@ pub fn make_extension_error(code: &str, detail: Option<&str>) -> RedisError {
     }
 }
 
+/// Validates that a binary key is valid UTF-8 before it is sent to the server.
+#[cfg(feature = "safe-encoding")]
+pub(crate) fn checked_utf8_key(key: &[u8]) -> RedisResult<&str> {
+    from_utf8(key).map_err(|_| {
+        RedisError::from((
+            ErrorKind::TypeError,
+            "Refusing to send a key that is not valid UTF-8",
+        ))
+    })
+}
+
 /// Library generic result type.
 pub type RedisResult<T> = Result<T, RedisError>;
 
@@ -614,6 +797,455 @@ impl InfoDict {
     }
 }
 
+/// The replication role of a server as returned by the `ROLE` command.
+///
+/// ```rust,no_run
+/// # fn do_something() -> redis::RedisResult<()> {
+/// # let client = redis::Client::open("redis://127.0.0.1/").unwrap();
+/// # let mut con = client.get_connection().unwrap();
+/// use redis::{Commands, Role};
+///
+/// match con.role()? {
+///     Role::Master { replicas, .. } => println!("master of {} replicas", replicas.len()),
+///     Role::Replica { master_host, master_port, .. } => {
+///         println!("replica of {}:{}", master_host, master_port)
+///     }
+///     Role::Sentinel { master_names } => println!("sentinel for {:?}", master_names),
+/// }
+/// # Ok(()) }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Role {
+    /// The server is a master.
+    Master {
+        /// The current master replication offset.
+        replication_offset: u64,
+        /// The replicas connected to this master.
+        replicas: Vec<ReplicaInfo>,
+    },
+    /// The server is a replica.
+    Replica {
+        /// The host of the master.
+        master_host: String,
+        /// The port of the master.
+        master_port: u16,
+        /// The state of the replication from the point of view of the master
+        /// (`connect`, `connecting`, `sync` or `connected`).
+        state: String,
+        /// The amount of data received from the master so far, `-1` if unknown.
+        offset: i64,
+    },
+    /// The server is a sentinel.
+    Sentinel {
+        /// The names of the masters monitored by this sentinel.
+        master_names: Vec<String>,
+    },
+}
+
+/// A replica as reported by the `ROLE` command of its master.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplicaInfo {
+    /// The host of the replica.
+    pub host: String,
+    /// The port of the replica.
+    pub port: u16,
+    /// The last replication offset acknowledged by the replica.
+    pub offset: u64,
+}
+
+/// How many replicas acknowledged a write, as checked with `WAIT`.
+///
+/// Returned by [`Commands::write_with_replication`](crate::Commands::write_with_replication).
+/// Too few acknowledgements are not an error: the write was applied by the
+/// master and may still reach the replicas later.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ReplicationOutcome {
+    /// At least the required number of replicas acknowledged the write.
+    Acknowledged(usize),
+    /// Fewer replicas than required acknowledged the write before the
+    /// timeout.
+    InsufficientReplicas {
+        /// The number of replicas that acknowledged the write.
+        acked: usize,
+        /// The number of replicas that was required.
+        required: usize,
+    },
+}
+
+impl ReplicationOutcome {
+    /// Creates the outcome of `acked` acknowledgements when `required` were
+    /// asked for.
+    pub fn new(acked: usize, required: usize) -> ReplicationOutcome {
+        if acked >= required {
+            ReplicationOutcome::Acknowledged(acked)
+        } else {
+            ReplicationOutcome::InsufficientReplicas { acked, required }
+        }
+    }
+
+    /// Returns true if enough replicas acknowledged the write.
+    pub fn is_acknowledged(&self) -> bool {
+        matches!(self, ReplicationOutcome::Acknowledged(_))
+    }
+
+    /// Returns the number of replicas that acknowledged the write.
+    pub fn acked(&self) -> usize {
+        match *self {
+            ReplicationOutcome::Acknowledged(acked) => acked,
+            ReplicationOutcome::InsufficientReplicas { acked, .. } => acked,
+        }
+    }
+}
+
+/// The payload of a status reply, like `OK`, `PONG` or the type name
+/// returned by `TYPE`.
+///
+/// Unlike `()`, which drops the payload, and `String`, which also accepts
+/// bulk strings, decoding a `Status` only succeeds for status replies.  A
+/// command that answers with nil, like `SET` with `NX`, can be decoded as
+/// `Option<Status>`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct Status(String);
+
+impl Status {
+    /// Returns whether the status is `OK`.
+    pub fn is_ok(&self) -> bool {
+        self.0 == "OK"
+    }
+
+    /// Returns the payload of the status.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the payload of the status as an owned string.
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl fmt::Display for Status {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// A string decoded with invalid UTF-8 sequences replaced by
+/// `U+FFFD REPLACEMENT CHARACTER`.
+///
+/// Decoding into `String` fails for data that is not valid UTF-8, so that
+/// binary values are never silently corrupted.  `LossyString` opts into the
+/// replacement where that is acceptable, e.g. for values that are only
+/// displayed.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Hash)]
+pub struct LossyString(String);
+
+impl LossyString {
+    /// Returns the decoded string.
+    pub fn as_str(&self) -> &str {
+        &self.0
+    }
+
+    /// Returns the decoded string as an owned string.
+    pub fn into_string(self) -> String {
+        self.0
+    }
+}
+
+impl fmt::Display for LossyString {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.0)
+    }
+}
+
+/// The details a server reports about itself in reply to `HELLO`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HelloInfo {
+    /// The name of the server, `redis` for Redis itself.
+    pub server: String,
+    /// The version of the server.
+    pub version: String,
+    /// The protocol version the connection uses from now on.
+    pub proto: u8,
+    /// The id of the connection, as listed by `CLIENT LIST`.
+    pub id: i64,
+    /// The mode the server runs in (`standalone`, `cluster` or `sentinel`).
+    pub mode: String,
+    /// The replication role of the server (`master` or `replica`).
+    pub role: String,
+    /// The names of the loaded modules.
+    pub modules: Vec<String>,
+}
+
+/// The internal representation of a value as returned by the
+/// `OBJECT ENCODING` command.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Encoding {
+    /// A string stored as raw bytes.
+    Raw,
+    /// A string holding an integer.
+    Int,
+    /// A short string allocated together with its object.
+    Embstr,
+    /// A hash, set or sorted set stored as a hash table.
+    Hashtable,
+    /// A small list, hash or sorted set stored as a listpack.
+    Listpack,
+    /// A small list, hash or sorted set stored as a ziplist (before Redis 7).
+    Ziplist,
+    /// A list stored as a linked list of listpacks or ziplists.
+    Quicklist,
+    /// A small set of integers.
+    Intset,
+    /// A sorted set stored as a skip list.
+    Skiplist,
+    /// A list stored as a linked list (before Redis 3.2).
+    Linkedlist,
+    /// A small hash stored as a zipmap (before Redis 2.6).
+    Zipmap,
+    /// A stream.
+    Stream,
+    /// An encoding unknown to this version of the library.
+    Other(String),
+}
+
+/// A serialized value as returned by the `DUMP` command and accepted by
+/// `RESTORE`.
+///
+/// The payload ends with the RDB version it was written with and a CRC64
+/// checksum, which [`validate`](DumpPayload::validate) checks before the
+/// payload is sent back to a server.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct DumpPayload(pub Vec<u8>);
+
+/// The RDB versions written by the servers this library knows about.
+const DUMP_RDB_VERSIONS: std::ops::RangeInclusive<u16> = 1..=12;
+
+impl DumpPayload {
+    /// Returns the RDB version the payload was written with, or `None` if
+    /// the payload is too short to have one.
+    pub fn rdb_version(&self) -> Option<u16> {
+        let len = self.0.len();
+        if len < 10 {
+            return None;
+        }
+        Some(u16::from_le_bytes([self.0[len - 10], self.0[len - 9]]))
+    }
+
+    /// Checks that the payload is complete, was written with a known RDB
+    /// version and matches its checksum.
+    ///
+    /// Servers refuse payloads from newer RDB versions than their own, so a
+    /// payload passing this check can still be rejected by an older server.
+    pub fn validate(&self) -> RedisResult<()> {
+        let version = match self.rdb_version() {
+            Some(version) => version,
+            None => fail!((
+                ErrorKind::ClientError,
+                "Invalid DUMP payload",
+                "payload is truncated".to_string()
+            )),
+        };
+        if !DUMP_RDB_VERSIONS.contains(&version) {
+            fail!((
+                ErrorKind::ClientError,
+                "Invalid DUMP payload",
+                format!("unsupported RDB version {}", version)
+            ));
+        }
+        let (data, checksum) = self.0.split_at(self.0.len() - 8);
+        if crc64(data).to_le_bytes() != checksum {
+            fail!((
+                ErrorKind::ClientError,
+                "Invalid DUMP payload",
+                "checksum mismatch".to_string()
+            ));
+        }
+        Ok(())
+    }
+}
+
+/// The CRC64 variant used by redis (Jones polynomial, reflected).
+fn crc64(data: &[u8]) -> u64 {
+    const fn table() -> [u64; 256] {
+        let mut table = [0u64; 256];
+        let mut i = 0;
+        while i < 256 {
+            let mut crc = i as u64;
+            let mut bit = 0;
+            while bit < 8 {
+                crc = if crc & 1 == 1 {
+                    (crc >> 1) ^ 0x95ac_9329_ac4b_c9b5
+                } else {
+                    crc >> 1
+                };
+                bit += 1;
+            }
+            table[i] = crc;
+            i += 1;
+        }
+        table
+    }
+    const TABLE: [u64; 256] = table();
+
+    data.iter().fold(0, |crc, &byte| {
+        TABLE[((crc ^ u64::from(byte)) & 0xff) as usize] ^ (crc >> 8)
+    })
+}
+
+/// The latency distributions reported by the `LATENCY HISTOGRAM` command.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct LatencyHistogram {
+    /// The distribution of every reported command, keyed by its name.
+    /// Subcommands are named like `client|id`.
+    pub commands: HashMap<String, CommandLatency>,
+}
+
+/// The latency distribution of a single command.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct CommandLatency {
+    /// The number of times the command was called.
+    pub calls: u64,
+    /// Pairs of the upper bound of a bucket in microseconds and the number
+    /// of calls that took at most that long, sorted by upper bound.  The
+    /// counts are cumulative, so the last one equals `calls`.
+    pub buckets: Vec<(u64, u64)>,
+}
+
+#[cfg(feature = "metrics")]
+impl LatencyHistogram {
+    /// Records the distributions with the `metrics` facade.
+    ///
+    /// The server reports cumulative bucket counts rather than individual
+    /// samples, so they are recorded as absolute counters in the layout of a
+    /// Prometheus histogram: `redis_server_command_calls` and
+    /// `redis_server_command_latency_usec_bucket`, labelled with `command`
+    /// and, for the buckets, with their upper bound as `le`.
+    pub fn record_metrics(&self) {
+        for (command, latency) in &self.commands {
+            metrics::absolute_counter!(
+                "redis_server_command_calls",
+                latency.calls,
+                "command" => command.clone()
+            );
+            for &(upper_bound, count) in &latency.buckets {
+                metrics::absolute_counter!(
+                    "redis_server_command_latency_usec_bucket",
+                    count,
+                    "command" => command.clone(),
+                    "le" => upper_bound.to_string()
+                );
+            }
+        }
+    }
+}
+
+/// An event tracked by the latency monitor, as used by the `LATENCY`
+/// commands.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum LatencyEvent {
+    /// A regular command, excluding fast commands.
+    Command,
+    /// A command with O(1) or O(log N) complexity.
+    FastCommand,
+    /// The `fork` system call for persistence or replication.
+    Fork,
+    /// Removing a temporary RDB file.
+    RdbUnlinkTempFile,
+    /// Writing to the AOF, covering all of the `aof-write-*` cases.
+    AofWrite,
+    /// Writing to the AOF without pending fsync or a child process.
+    AofWriteAlone,
+    /// Writing to the AOF while a child process is active.
+    AofWriteActiveChild,
+    /// Writing to the AOF while an fsync is pending.
+    AofWritePendingFsync,
+    /// The fsync of the AOF with `appendfsync always`.
+    AofFsyncAlways,
+    /// The `fstat` of the AOF.
+    AofFstat,
+    /// Renaming the AOF after a rewrite.
+    AofRename,
+    /// Writing the changes accumulated during an AOF rewrite.
+    AofRewriteDiffWrite,
+    /// The active expiry cycle.
+    ExpireCycle,
+    /// The eviction cycle.
+    EvictionCycle,
+    /// Deleting keys during the eviction cycle.
+    EvictionDel,
+    /// The active defragmentation cycle.
+    ActiveDefragCycle,
+    /// Background work while the server is blocked by a script or module.
+    WhileBlockedCron,
+    /// An event unknown to this version of the library.
+    Other(String),
+}
+
+impl LatencyEvent {
+    /// Returns the name of the event as used by the server.
+    pub fn as_str(&self) -> &str {
+        match self {
+            LatencyEvent::Command => "command",
+            LatencyEvent::FastCommand => "fast-command",
+            LatencyEvent::Fork => "fork",
+            LatencyEvent::RdbUnlinkTempFile => "rdb-unlink-temp-file",
+            LatencyEvent::AofWrite => "aof-write",
+            LatencyEvent::AofWriteAlone => "aof-write-alone",
+            LatencyEvent::AofWriteActiveChild => "aof-write-active-child",
+            LatencyEvent::AofWritePendingFsync => "aof-write-pending-fsync",
+            LatencyEvent::AofFsyncAlways => "aof-fsync-always",
+            LatencyEvent::AofFstat => "aof-fstat",
+            LatencyEvent::AofRename => "aof-rename",
+            LatencyEvent::AofRewriteDiffWrite => "aof-rewrite-diff-write",
+            LatencyEvent::ExpireCycle => "expire-cycle",
+            LatencyEvent::EvictionCycle => "eviction-cycle",
+            LatencyEvent::EvictionDel => "eviction-del",
+            LatencyEvent::ActiveDefragCycle => "active-defrag-cycle",
+            LatencyEvent::WhileBlockedCron => "while-blocked-cron",
+            LatencyEvent::Other(name) => name,
+        }
+    }
+}
+
+impl From<&str> for LatencyEvent {
+    fn from(name: &str) -> LatencyEvent {
+        match name {
+            "command" => LatencyEvent::Command,
+            "fast-command" => LatencyEvent::FastCommand,
+            "fork" => LatencyEvent::Fork,
+            "rdb-unlink-temp-file" => LatencyEvent::RdbUnlinkTempFile,
+            "aof-write" => LatencyEvent::AofWrite,
+            "aof-write-alone" => LatencyEvent::AofWriteAlone,
+            "aof-write-active-child" => LatencyEvent::AofWriteActiveChild,
+            "aof-write-pending-fsync" => LatencyEvent::AofWritePendingFsync,
+            "aof-fsync-always" => LatencyEvent::AofFsyncAlways,
+            "aof-fstat" => LatencyEvent::AofFstat,
+            "aof-rename" => LatencyEvent::AofRename,
+            "aof-rewrite-diff-write" => LatencyEvent::AofRewriteDiffWrite,
+            "expire-cycle" => LatencyEvent::ExpireCycle,
+            "eviction-cycle" => LatencyEvent::EvictionCycle,
+            "eviction-del" => LatencyEvent::EvictionDel,
+            "active-defrag-cycle" => LatencyEvent::ActiveDefragCycle,
+            "while-blocked-cron" => LatencyEvent::WhileBlockedCron,
+            _ => LatencyEvent::Other(name.to_string()),
+        }
+    }
+}
+
+/// The latest latency spike of an event as reported by `LATENCY LATEST`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LatencyLatestEntry {
+    /// The event that was slow.
+    pub event: LatencyEvent,
+    /// The unix timestamp of the latest spike in seconds.
+    pub timestamp: u64,
+    /// The duration of the latest spike in milliseconds.
+    pub latest_ms: u64,
+    /// The longest spike of the event ever recorded in milliseconds.
+    pub max_ms: u64,
+}
+
 /// Abstraction trait for redis command abstractions.
 pub trait RedisWrite {
     /// Accepts a serialized redis command.
@@ -623,6 +1255,24 @@ pub trait RedisWrite {
     fn write_arg_fmt(&mut self, arg: impl fmt::Display) {
         self.write_arg(arg.to_string().as_bytes())
     }
+
+    /// Hints that about `args` more arguments with `bytes` bytes in total
+    /// are about to be written.  Either number may be 0 if it is not known.
+    ///
+    /// Does nothing by default.
+    fn reserve(&mut self, bytes: usize, args: usize) {
+        let _ = (bytes, args);
+    }
+
+    /// Accepts an argument which is already held in a shared buffer.
+    ///
+    /// Writers which can keep the buffer instead of copying it, like
+    /// [`Cmd`](crate::Cmd), override this.  By default the argument is
+    /// copied with [`write_arg`](RedisWrite::write_arg).
+    #[cfg(feature = "bytes")]
+    fn write_arg_shared(&mut self, arg: bytes::Bytes) {
+        self.write_arg(&arg)
+    }
 }
 
 impl RedisWrite for Vec<Vec<u8>> {
@@ -633,6 +1283,10 @@ impl RedisWrite for Vec<Vec<u8>> {
     fn write_arg_fmt(&mut self, arg: impl fmt::Display) {
         self.push(arg.to_string().into_bytes())
     }
+
+    fn reserve(&mut self, _bytes: usize, args: usize) {
+        Vec::reserve(self, args)
+    }
 }
 
 /// Used to convert a value into one or multiple redis argument
@@ -681,6 +1335,7 @@ pub trait ToRedisArgs: Sized {
     where
         W: ?Sized + RedisWrite,
     {
+        out.reserve(0, items.len());
         for item in items.iter() {
             item.write_redis_args(out);
         }
@@ -695,6 +1350,7 @@ pub trait ToRedisArgs: Sized {
         I: Iterator<Item = &'a Self>,
         Self: 'a,
     {
+        out.reserve(0, items.size_hint().0);
         for item in items {
             item.write_redis_args(out);
         }
@@ -818,6 +1474,60 @@ impl ToRedisArgs for bool {
     }
 }
 
+/// Sends the point in time as seconds since the Unix epoch, as expected by
+/// commands like `EXPIREAT`.
+#[cfg(feature = "chrono")]
+impl<Tz: chrono::TimeZone> ToRedisArgs for chrono::DateTime<Tz> {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        self.timestamp().write_redis_args(out)
+    }
+}
+
+/// Sends the UUID in its hyphenated string form.
+#[cfg(feature = "uuid")]
+impl ToRedisArgs for uuid::Uuid {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        let mut buf = uuid::Uuid::encode_buffer();
+        out.write_arg(self.to_hyphenated().encode_lower(&mut buf).as_bytes())
+    }
+}
+
+impl ToRedisArgs for LatencyEvent {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        out.write_arg(self.as_str().as_bytes())
+    }
+}
+
+impl ToRedisArgs for DumpPayload {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        out.write_arg(&self.0)
+    }
+}
+
+/// Unlike other byte buffers, the argument is passed to
+/// [`RedisWrite::write_arg_shared`] without being copied.
+#[cfg(feature = "bytes")]
+impl ToRedisArgs for bytes::Bytes {
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        out.write_arg_shared(self.clone())
+    }
+}
+
 impl ToRedisArgs for String {
     fn write_redis_args<W>(&self, out: &mut W)
     where
@@ -894,6 +1604,14 @@ impl<T: ToRedisArgs> ToRedisArgs for &T {
     {
         (*self).write_redis_args(out)
     }
+
+    fn describe_numeric_behavior(&self) -> NumericBehavior {
+        (*self).describe_numeric_behavior()
+    }
+
+    fn is_single_arg(&self) -> bool {
+        (*self).is_single_arg()
+    }
 }
 
 /// @note: Redis cannot store empty sets so the application has to
@@ -951,6 +1669,31 @@ impl<T: ToRedisArgs + Hash + Eq + Ord, V: ToRedisArgs> ToRedisArgs for BTreeMap<
     }
 }
 
+/// this flattens HashMap into something that goes well with HMSET
+/// @note: Redis cannot store empty sets so the application has to
+/// check whether the set is empty and if so, not attempt to use that
+/// result
+impl<T: ToRedisArgs + Hash + Eq, V: ToRedisArgs, S: BuildHasher + Default> ToRedisArgs
+    for HashMap<T, V, S>
+{
+    fn write_redis_args<W>(&self, out: &mut W)
+    where
+        W: ?Sized + RedisWrite,
+    {
+        for (key, value) in self {
+            // otherwise things like HMSET will simply NOT work
+            assert!(key.is_single_arg() && value.is_single_arg());
+
+            key.write_redis_args(out);
+            value.write_redis_args(out);
+        }
+    }
+
+    fn is_single_arg(&self) -> bool {
+        self.len() <= 1
+    }
+}
+
 macro_rules! to_redis_args_for_tuple {
     () => ();
     ($($name:ident,)+) => (
@@ -1018,6 +1761,33 @@ to_redis_args_for_array! {
 ///
 /// In addition to what you can see from the docs, this is also implemented
 /// for tuples up to size 12 and for Vec<u8>.
+///
+/// # Nil and empty lists
+///
+/// Converting to `Vec<T>` is lenient and maps both a nil reply and an empty
+/// array to an empty vector.  Use `Option<Vec<T>>` to tell them apart: a nil
+/// reply becomes `None` and an empty array becomes `Some(vec![])`.  Every
+/// command method is generic over its return type, so this works for all
+/// commands returning a list:
+///
+/// ```rust,no_run
+/// # fn do_something() -> redis::RedisResult<()> {
+/// # let client = redis::Client::open("redis://127.0.0.1/")?;
+/// # let mut con = client.get_connection()?;
+/// use redis::Commands;
+/// use std::num::NonZeroUsize;
+///
+/// // `LPOP` with a count replies nil if the key does not exist.
+/// let popped: Option<Vec<String>> = con.lpop("my_list", NonZeroUsize::new(2))?;
+/// if popped.is_none() {
+///     println!("no such list");
+/// }
+/// # Ok(()) }
+/// ```
+///
+/// Note that redis deletes lists, sets and sorted sets once they become
+/// empty, and that most commands such as `LRANGE`, `SMEMBERS` or `ZRANGE`
+/// reply with an empty array rather than nil for a missing key.
 pub trait FromRedisValue: Sized {
     /// Given a redis `Value` this attempts to convert it into the given
     /// destination type.  If that fails because it's not compatible an
@@ -1120,6 +1890,9 @@ impl FromRedisValue for bool {
     }
 }
 
+/// Data that is not valid UTF-8 fails with a `TypeError` whose detail names
+/// the offset of the first invalid byte.  Use [`LossyString`] to replace
+/// invalid sequences instead.
 impl FromRedisValue for String {
     fn from_redis_value(v: &Value) -> RedisResult<String> {
         match *v {
@@ -1141,6 +1914,7 @@ impl<T: FromRedisValue> FromRedisValue for Vec<T> {
                 None => invalid_type_error!(v, "Response type not vector compatible."),
             },
             Value::Bulk(ref items) => FromRedisValue::from_redis_values(items),
+            // Use `Option<Vec<T>>` to distinguish nil from an empty array.
             Value::Nil => Ok(vec![]),
             _ => invalid_type_error!(v, "Response type not vector compatible."),
         }
@@ -1275,6 +2049,181 @@ impl FromRedisValue for InfoDict {
     }
 }
 
+/// Decodes the reply of the `TIME` command, a pair of the seconds and
+/// microseconds since the Unix epoch.
+impl FromRedisValue for SystemTime {
+    fn from_redis_value(v: &Value) -> RedisResult<SystemTime> {
+        let (secs, micros): (u64, u32) = match from_redis_value(v) {
+            Ok(time) => time,
+            Err(_) => invalid_type_error!(v, "Response type not TIME compatible"),
+        };
+        if micros >= 1_000_000 {
+            invalid_type_error!(v, "Microseconds of TIME response out of range");
+        }
+        Ok(UNIX_EPOCH + Duration::new(secs, micros * 1000))
+    }
+}
+
+impl FromRedisValue for Role {
+    fn from_redis_value(v: &Value) -> RedisResult<Role> {
+        let items = match *v {
+            Value::Bulk(ref items) if !items.is_empty() => items,
+            _ => invalid_type_error!(v, "Response type not ROLE compatible"),
+        };
+        let role: String = from_redis_value(&items[0])?;
+        match (&role[..], &items[1..]) {
+            ("master", [replication_offset, replicas]) => Ok(Role::Master {
+                replication_offset: from_redis_value(replication_offset)?,
+                replicas: from_redis_value(replicas)?,
+            }),
+            ("slave", [master_host, master_port, state, offset]) => Ok(Role::Replica {
+                master_host: from_redis_value(master_host)?,
+                master_port: from_redis_value(master_port)?,
+                state: from_redis_value(state)?,
+                offset: from_redis_value(offset)?,
+            }),
+            ("sentinel", [master_names]) => Ok(Role::Sentinel {
+                master_names: from_redis_value(master_names)?,
+            }),
+            _ => invalid_type_error!(v, "Unknown ROLE response"),
+        }
+    }
+}
+
+impl FromRedisValue for LossyString {
+    fn from_redis_value(v: &Value) -> RedisResult<LossyString> {
+        match *v {
+            Value::Data(ref bytes) => Ok(LossyString(String::from_utf8_lossy(bytes).into_owned())),
+            _ => String::from_redis_value(v).map(LossyString),
+        }
+    }
+}
+
+impl FromRedisValue for Status {
+    fn from_redis_value(v: &Value) -> RedisResult<Status> {
+        match *v {
+            Value::Okay => Ok(Status("OK".to_string())),
+            Value::Status(ref status) => Ok(Status(status.clone())),
+            _ => invalid_type_error!(v, "Response is not a status reply."),
+        }
+    }
+}
+
+impl FromRedisValue for HelloInfo {
+    fn from_redis_value(v: &Value) -> RedisResult<HelloInfo> {
+        let map: HashMap<String, Value> = from_redis_value(v)?;
+        let field = |name: &str| {
+            map.get(name)
+                .ok_or_else(|| invalid_type_error_inner!(v, "Response type not HELLO compatible"))
+        };
+        let modules: Vec<HashMap<String, Value>> = from_redis_value(field("modules")?)?;
+        Ok(HelloInfo {
+            server: from_redis_value(field("server")?)?,
+            version: from_redis_value(field("version")?)?,
+            proto: from_redis_value(field("proto")?)?,
+            id: from_redis_value(field("id")?)?,
+            mode: from_redis_value(field("mode")?)?,
+            role: from_redis_value(field("role")?)?,
+            modules: modules
+                .iter()
+                .filter_map(|module| module.get("name"))
+                .map(from_redis_value)
+                .collect::<RedisResult<_>>()?,
+        })
+    }
+}
+
+impl FromRedisValue for Encoding {
+    fn from_redis_value(v: &Value) -> RedisResult<Encoding> {
+        let encoding: String = from_redis_value(v)?;
+        Ok(match &encoding[..] {
+            "raw" => Encoding::Raw,
+            "int" => Encoding::Int,
+            "embstr" => Encoding::Embstr,
+            "hashtable" => Encoding::Hashtable,
+            "listpack" => Encoding::Listpack,
+            "ziplist" => Encoding::Ziplist,
+            "quicklist" => Encoding::Quicklist,
+            "intset" => Encoding::Intset,
+            "skiplist" => Encoding::Skiplist,
+            "linkedlist" => Encoding::Linkedlist,
+            "zipmap" => Encoding::Zipmap,
+            "stream" => Encoding::Stream,
+            _ => Encoding::Other(encoding),
+        })
+    }
+}
+
+impl FromRedisValue for DumpPayload {
+    fn from_redis_value(v: &Value) -> RedisResult<DumpPayload> {
+        match *v {
+            Value::Data(ref bytes) => Ok(DumpPayload(bytes.clone())),
+            _ => invalid_type_error!(v, "Response type not DUMP payload compatible"),
+        }
+    }
+}
+
+impl FromRedisValue for LatencyHistogram {
+    fn from_redis_value(v: &Value) -> RedisResult<LatencyHistogram> {
+        let items = match *v {
+            Value::Bulk(ref items) => items,
+            _ => invalid_type_error!(v, "Response type not LATENCY HISTOGRAM compatible"),
+        };
+        let mut pairs = items.chunks_exact(2);
+        let mut commands = HashMap::new();
+        for pair in &mut pairs {
+            commands.insert(from_redis_value(&pair[0])?, from_redis_value(&pair[1])?);
+        }
+        if !pairs.remainder().is_empty() {
+            invalid_type_error!(v, "Odd number of LATENCY HISTOGRAM elements");
+        }
+        Ok(LatencyHistogram { commands })
+    }
+}
+
+impl FromRedisValue for CommandLatency {
+    fn from_redis_value(v: &Value) -> RedisResult<CommandLatency> {
+        let fields: HashMap<String, Value> = from_redis_value(v)?;
+        let (calls, buckets) = match (fields.get("calls"), fields.get("histogram_usec")) {
+            (Some(calls), Some(buckets)) => (calls, buckets),
+            _ => invalid_type_error!(v, "Response type not LATENCY HISTOGRAM compatible"),
+        };
+        Ok(CommandLatency {
+            calls: from_redis_value(calls)?,
+            buckets: from_redis_value(buckets)?,
+        })
+    }
+}
+
+impl FromRedisValue for LatencyEvent {
+    fn from_redis_value(v: &Value) -> RedisResult<LatencyEvent> {
+        let name: String = from_redis_value(v)?;
+        Ok(LatencyEvent::from(&name[..]))
+    }
+}
+
+impl FromRedisValue for LatencyLatestEntry {
+    fn from_redis_value(v: &Value) -> RedisResult<LatencyLatestEntry> {
+        // Newer servers may append further fields.
+        match *v {
+            Value::Bulk(ref items) if items.len() >= 4 => Ok(LatencyLatestEntry {
+                event: from_redis_value(&items[0])?,
+                timestamp: from_redis_value(&items[1])?,
+                latest_ms: from_redis_value(&items[2])?,
+                max_ms: from_redis_value(&items[3])?,
+            }),
+            _ => invalid_type_error!(v, "Response type not LATENCY LATEST compatible"),
+        }
+    }
+}
+
+impl FromRedisValue for ReplicaInfo {
+    fn from_redis_value(v: &Value) -> RedisResult<ReplicaInfo> {
+        let (host, port, offset) = from_redis_value(v)?;
+        Ok(ReplicaInfo { host, port, offset })
+    }
+}
+
 impl<T: FromRedisValue> FromRedisValue for Option<T> {
     fn from_redis_value(v: &Value) -> RedisResult<Option<T>> {
         if *v == Value::Nil {
@@ -0,0 +1,195 @@
+//! Small helpers built on top of the commands.
+
+use std::time::{Duration, Instant, SystemTime};
+
+use crate::commands::Commands;
+use crate::config::ServerConfig;
+use crate::connection::ConnectionLike;
+use crate::types::{ErrorKind, RedisResult, ToRedisArgs};
+
+/// Measures how far the clocks of the client and the server are apart, in
+/// milliseconds.
+///
+/// The result is positive if the server clock is ahead of the local one and
+/// negative if it is behind.  The server time is assumed to have been taken
+/// halfway through the round-trip of the `TIME` command, so the result is
+/// only as accurate as half the round-trip time.
+///
+/// This is useful to check that `EXPIREAT` and `PXAT` timestamps computed
+/// from the local clock mean the same thing to the server.
+///
+/// ```rust,no_run
+/// # fn do_something() -> redis::RedisResult<()> {
+/// let client = redis::Client::open("redis://127.0.0.1/")?;
+/// let mut con = client.get_connection()?;
+/// let skew = redis::util::clock_skew(&mut con)?;
+/// if skew.abs() > 1000 {
+///     println!("server clock is {}ms off", skew);
+/// }
+/// # Ok(()) }
+/// ```
+pub fn clock_skew<C: ConnectionLike>(con: &mut C) -> RedisResult<i64> {
+    let local_before = SystemTime::now();
+    let started = Instant::now();
+    let server: SystemTime = con.time()?;
+    let local = local_before + started.elapsed() / 2;
+    Ok(match server.duration_since(local) {
+        Ok(ahead) => ahead.as_millis() as i64,
+        Err(behind) => -(behind.duration().as_millis() as i64),
+    })
+}
+
+/// How often or how recently a key was accessed, as tracked by the server
+/// for its `maxmemory-policy`.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum AccessInfo {
+    /// The logarithmic access frequency counter from `OBJECT FREQ`, tracked
+    /// under the LFU policies.
+    Frequency(u8),
+    /// The time since the last access from `OBJECT IDLETIME`, tracked under
+    /// all other policies.
+    IdleTime(Duration),
+}
+
+/// Reads the [`AccessInfo`] of keys, remembering the eviction policy of the
+/// server between calls.
+///
+/// The policy is read with `CONFIG GET maxmemory-policy` on first use.
+/// Should it change afterwards, the next call notices from the
+/// [`WrongEvictionPolicy`](ErrorKind::WrongEvictionPolicy) error, reads the
+/// policy again and retries.
+///
+/// ```rust,no_run
+/// # fn do_something() -> redis::RedisResult<()> {
+/// use redis::util::{AccessInfo, AccessInfoReader};
+///
+/// let client = redis::Client::open("redis://127.0.0.1/")?;
+/// let mut con = client.get_connection()?;
+/// let mut reader = AccessInfoReader::new();
+/// for key in &["a", "b", "c"] {
+///     match reader.key_access_info(&mut con, key)? {
+///         Some(AccessInfo::Frequency(freq)) => println!("{} is used {}", key, freq),
+///         Some(AccessInfo::IdleTime(idle)) => println!("{} is idle for {:?}", key, idle),
+///         None => println!("{} does not exist", key),
+///     }
+/// }
+/// # Ok(()) }
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct AccessInfoReader {
+    lfu: Option<bool>,
+}
+
+impl AccessInfoReader {
+    /// Creates a reader that reads the policy on first use.
+    pub fn new() -> AccessInfoReader {
+        AccessInfoReader::default()
+    }
+
+    /// Returns the access info of `key`, or `None` if the key does not
+    /// exist.
+    pub fn key_access_info<C, K>(&mut self, con: &mut C, key: K) -> RedisResult<Option<AccessInfo>>
+    where
+        C: ConnectionLike,
+        K: ToRedisArgs,
+    {
+        let lfu = match self.lfu {
+            Some(lfu) => lfu,
+            None => self.read_policy(con)?,
+        };
+        match read_access_info(con, &key, lfu) {
+            Err(err) if err.kind() == ErrorKind::WrongEvictionPolicy => {
+                let lfu = self.read_policy(con)?;
+                read_access_info(con, &key, lfu)
+            }
+            result => result,
+        }
+    }
+
+    fn read_policy<C: ConnectionLike>(&mut self, con: &mut C) -> RedisResult<bool> {
+        let config: ServerConfig = con.config_get("maxmemory-policy")?;
+        let lfu = matches!(config.get_maxmemory_policy(), Some(policy) if policy.is_lfu());
+        self.lfu = Some(lfu);
+        Ok(lfu)
+    }
+}
+
+/// Returns the access info of `key` with `OBJECT FREQ` or `OBJECT IDLETIME`,
+/// whichever the eviction policy of the server tracks, or `None` if the key
+/// does not exist.
+///
+/// The policy is read on every call, use an [`AccessInfoReader`] to read it
+/// only once for many keys.
+pub fn key_access_info<C, K>(con: &mut C, key: K) -> RedisResult<Option<AccessInfo>>
+where
+    C: ConnectionLike,
+    K: ToRedisArgs,
+{
+    AccessInfoReader::new().key_access_info(con, key)
+}
+
+fn read_access_info<C, K>(con: &mut C, key: &K, lfu: bool) -> RedisResult<Option<AccessInfo>>
+where
+    C: ConnectionLike,
+    K: ToRedisArgs,
+{
+    Ok(if lfu {
+        con.object_freq::<_, Option<u8>>(key)?
+            .map(AccessInfo::Frequency)
+    } else {
+        con.object_idletime::<_, Option<u64>>(key)?
+            .map(|secs| AccessInfo::IdleTime(Duration::from_secs(secs)))
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::types::Value;
+    use std::time::UNIX_EPOCH;
+
+    // Answers `TIME` with the local time shifted by `offset` seconds.
+    struct ShiftedClock {
+        offset: i64,
+    }
+
+    impl ConnectionLike for ShiftedClock {
+        fn req_packed_command(&mut self, _cmd: &[u8]) -> RedisResult<Value> {
+            let now = SystemTime::now().duration_since(UNIX_EPOCH).unwrap();
+            let secs = now.as_secs() as i64 + self.offset;
+            Ok(Value::Bulk(vec![
+                Value::Data(secs.to_string().into_bytes()),
+                Value::Data(now.subsec_micros().to_string().into_bytes()),
+            ]))
+        }
+
+        fn req_packed_commands(
+            &mut self,
+            _cmd: &[u8],
+            _offset: usize,
+            _count: usize,
+        ) -> RedisResult<Vec<Value>> {
+            unreachable!()
+        }
+
+        fn get_db(&self) -> i64 {
+            0
+        }
+
+        fn check_connection(&mut self) -> bool {
+            true
+        }
+
+        fn is_open(&self) -> bool {
+            true
+        }
+    }
+
+    #[test]
+    fn test_clock_skew_direction() {
+        let ahead = clock_skew(&mut ShiftedClock { offset: 60 }).unwrap();
+        assert!((59_000..=61_000).contains(&ahead), "{}", ahead);
+        let behind = clock_skew(&mut ShiftedClock { offset: -60 }).unwrap();
+        assert!((-61_000..=-59_000).contains(&behind), "{}", behind);
+    }
+}
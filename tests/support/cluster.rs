@@ -3,13 +3,14 @@
 
 use std::convert::identity;
 use std::env;
+use std::path::{Path, PathBuf};
 use std::process;
 use std::thread::sleep;
 use std::time::Duration;
 
 use tempfile::TempDir;
 
-use crate::support::build_keys_and_certs_for_tls;
+use crate::support::{build_keys_and_certs_for_tls, TlsFilePaths};
 
 use super::RedisServer;
 
@@ -50,6 +51,9 @@ impl ClusterType {
 pub struct RedisCluster {
     pub servers: Vec<RedisServer>,
     pub folders: Vec<TempDir>,
+    node_dirs: Vec<PathBuf>,
+    tls_paths: Option<TlsFilePaths>,
+    replicas: u16,
 }
 
 impl RedisCluster {
@@ -65,11 +69,10 @@ impl RedisCluster {
         let mut servers = vec![];
         let mut folders = vec![];
         let mut addrs = vec![];
+        let mut node_dirs = vec![];
         let start_port = 7000;
         let mut tls_paths = None;
 
-        let mut is_tls = false;
-
         if let ClusterType::TcpTls = ClusterType::get_intended() {
             // Create a shared set of keys in cluster mode
             let tempdir = tempfile::Builder::new()
@@ -79,50 +82,23 @@ impl RedisCluster {
             let files = build_keys_and_certs_for_tls(&tempdir);
             folders.push(tempdir);
             tls_paths = Some(files);
-            is_tls = true;
         }
 
         for node in 0..nodes {
             let port = start_port + node;
-
-            servers.push(RedisServer::new_with_addr(
-                ClusterType::build_addr(port),
+            let tempdir = tempfile::Builder::new()
+                .prefix("redis")
+                .tempdir()
+                .expect("failed to create tempdir");
+            servers.push(Self::start_node(
+                port,
                 tls_paths.clone(),
-                |cmd| {
-                    let tempdir = tempfile::Builder::new()
-                        .prefix("redis")
-                        .tempdir()
-                        .expect("failed to create tempdir");
-                    let acl_path = tempdir.path().join("users.acl");
-                    let acl_content = format!(
-                        "user {} on allcommands allkeys >{}",
-                        Self::username(),
-                        Self::password()
-                    );
-                    std::fs::write(&acl_path, acl_content).expect("failed to write acl file");
-                    cmd.arg("--cluster-enabled")
-                        .arg("yes")
-                        .arg("--cluster-config-file")
-                        .arg(&tempdir.path().join("nodes.conf"))
-                        .arg("--cluster-node-timeout")
-                        .arg("5000")
-                        .arg("--appendonly")
-                        .arg("yes")
-                        .arg("--aclfile")
-                        .arg(&acl_path);
-                    if is_tls {
-                        cmd.arg("--tls-cluster").arg("yes");
-                        if replicas > 0 {
-                            cmd.arg("--tls-replication").arg("yes");
-                        }
-                    }
-                    cmd.current_dir(&tempdir.path());
-                    folders.push(tempdir);
-                    addrs.push(format!("127.0.0.1:{}", port));
-                    dbg!(&cmd);
-                    cmd.spawn().unwrap()
-                },
+                replicas,
+                tempdir.path(),
             ));
+            node_dirs.push(tempdir.path().to_path_buf());
+            folders.push(tempdir);
+            addrs.push(format!("127.0.0.1:{}", port));
         }
 
         sleep(Duration::from_millis(100));
@@ -136,24 +112,70 @@ impl RedisCluster {
             cmd.arg("--cluster-replicas").arg(replicas.to_string());
         }
         cmd.arg("--cluster-yes");
-        if is_tls {
+        if tls_paths.is_some() {
             cmd.arg("--tls").arg("--insecure");
         }
         let status = dbg!(cmd).status().unwrap();
         assert!(status.success());
 
-        let cluster = RedisCluster { servers, folders };
+        let cluster = RedisCluster {
+            servers,
+            folders,
+            node_dirs,
+            tls_paths,
+            replicas,
+        };
         if replicas > 0 {
             cluster.wait_for_replicas(replicas);
         }
         cluster
     }
 
+    // Starts a cluster node that keeps its cluster config and data in `dir`,
+    // so that it rejoins the cluster when it is started again.
+    fn start_node(
+        port: u16,
+        tls_paths: Option<TlsFilePaths>,
+        replicas: u16,
+        dir: &Path,
+    ) -> RedisServer {
+        let is_tls = tls_paths.is_some();
+        RedisServer::new_with_addr(ClusterType::build_addr(port), tls_paths, |cmd| {
+            let acl_path = dir.join("users.acl");
+            let acl_content = format!(
+                "user {} on allcommands allkeys >{}",
+                Self::username(),
+                Self::password()
+            );
+            std::fs::write(&acl_path, acl_content).expect("failed to write acl file");
+            cmd.arg("--cluster-enabled")
+                .arg("yes")
+                .arg("--cluster-config-file")
+                .arg(dir.join("nodes.conf"))
+                .arg("--cluster-node-timeout")
+                .arg("5000")
+                .arg("--appendonly")
+                .arg("yes")
+                .arg("--aclfile")
+                .arg(&acl_path);
+            if is_tls {
+                cmd.arg("--tls-cluster").arg("yes");
+                if replicas > 0 {
+                    cmd.arg("--tls-replication").arg("yes");
+                }
+            }
+            cmd.current_dir(dir);
+            dbg!(&cmd);
+            cmd.spawn().unwrap()
+        })
+    }
+
     fn wait_for_replicas(&self, replicas: u16) {
         'server: for server in &self.servers {
             let conn_info = redis::ConnectionInfo {
                 addr: server.get_client_addr().clone(),
                 redis: Default::default(),
+                tcp_keepalive: None,
             };
             eprintln!(
                 "waiting until {:?} knows required number of replicas",
@@ -186,6 +208,26 @@ impl RedisCluster {
         }
     }
 
+    pub fn stop_server(&mut self, idx: usize) {
+        self.servers[idx].stop();
+    }
+
+    pub fn restart_server(&mut self, idx: usize) {
+        let port = match self.servers[idx].get_client_addr() {
+            redis::ConnectionAddr::Tcp(_, port) | redis::ConnectionAddr::TcpTls { port, .. } => {
+                *port
+            }
+            redis::ConnectionAddr::Unix(_) => unreachable!(),
+        };
+        self.servers[idx].stop();
+        self.servers[idx] = Self::start_node(
+            port,
+            self.tls_paths.clone(),
+            self.replicas,
+            &self.node_dirs[idx],
+        );
+    }
+
     pub fn iter_servers(&self) -> impl Iterator<Item = &RedisServer> {
         self.servers.iter()
     }
@@ -222,6 +264,7 @@ impl TestClusterContext {
                 .map(|server| redis::ConnectionInfo {
                     addr: server.get_client_addr().clone(),
                     redis: Default::default(),
+                    tcp_keepalive: None,
                 })
                 .collect(),
         );
@@ -113,6 +113,11 @@ impl RedisServer {
         redis_cmd
             .stdout(process::Stdio::null())
             .stderr(process::Stdio::null());
+        // Redis 7 refuses DEBUG commands unless they are enabled explicitly,
+        // while older versions do not know the option at all.
+        if redis_server_major_version() >= 7 {
+            redis_cmd.arg("--enable-debug-command").arg("yes");
+        }
         let tempdir = tempfile::Builder::new()
             .prefix("redis")
             .tempdir()
@@ -197,6 +202,23 @@ impl Drop for RedisServer {
     }
 }
 
+fn redis_server_major_version() -> u32 {
+    let output = match process::Command::new("redis-server")
+        .arg("--version")
+        .output()
+    {
+        Ok(output) => output,
+        Err(_) => return 0,
+    };
+    // The output looks like `Redis server v=7.0.0 sha=00000000:0 ...`.
+    String::from_utf8_lossy(&output.stdout)
+        .split_whitespace()
+        .find_map(|part| part.strip_prefix("v="))
+        .and_then(|version| version.split('.').next())
+        .and_then(|major| major.parse().ok())
+        .unwrap_or(0)
+}
+
 pub struct TestContext {
     pub server: RedisServer,
     pub client: redis::Client,
@@ -209,6 +231,7 @@ impl TestContext {
         let client = redis::Client::open(redis::ConnectionInfo {
             addr: server.get_client_addr().clone(),
             redis: Default::default(),
+            tcp_keepalive: None,
         })
         .unwrap();
         let mut con;
@@ -2,7 +2,7 @@
 
 use std::collections::HashSet;
 
-use redis::acl::{AclInfo, Rule};
+use redis::acl::{AclCategory, AclInfo, Rule};
 use redis::{Commands, Value};
 
 mod support;
@@ -93,6 +93,26 @@ fn test_acl_getsetdel_users() {
     assert_eq!(con.acl_users(), Ok(vec!["default".to_owned()]));
 }
 
+#[test]
+fn test_acl_categories() {
+    let ctx = TestContext::new();
+    let mut con = ctx.connection();
+
+    let categories: Vec<AclCategory> = con.acl_cat().expect("Got categories");
+    assert!(categories.contains(&AclCategory::Keyspace));
+    assert!(categories.contains(&AclCategory::Scripting));
+
+    let commands: HashSet<String> = con
+        .acl_cat_categoryname(AclCategory::Keyspace)
+        .expect("Got commands");
+    assert!(commands.contains("del"));
+    let commands: HashSet<String> = con
+        .acl_cat_categoryname(AclCategory::Hash)
+        .expect("Got commands");
+    assert!(commands.contains("hset"));
+    assert!(!commands.contains("del"));
+}
+
 #[test]
 fn test_acl_cat() {
     let ctx = TestContext::new();
@@ -14,11 +14,11 @@ fn test_args() {
         redis::cmd("SET")
             .arg("key1")
             .arg(b"foo")
-            .query_async(&mut con)
+            .query_async::<_, ()>(&mut con)
             .await?;
         redis::cmd("SET")
             .arg(&["key2", "bar"])
-            .query_async(&mut con)
+            .query_async::<_, ()>(&mut con)
             .await?;
         let result = redis::cmd("MGET")
             .arg(&["key1", "key2"])
@@ -30,6 +30,146 @@ fn test_args() {
     .unwrap();
 }
 
+#[test]
+fn test_shared_args() {
+    let ctx = TestContext::new();
+    let connect = ctx.async_connection();
+
+    block_on_all(connect.and_then(|mut con| async move {
+        let value = bytes::Bytes::from(vec![b'x'; 4 * 1024 * 1024]);
+        redis::cmd("SET")
+            .arg("key")
+            .arg(value.clone())
+            .query_async::<_, ()>(&mut con)
+            .await?;
+        let result: Vec<u8> = con.get("key").await?;
+        assert_eq!(result, value);
+        Ok(())
+    }))
+    .unwrap();
+}
+
+#[test]
+fn test_borrowed_args_only_live_for_the_call() {
+    let ctx = TestContext::new();
+    let connect = ctx.async_connection();
+
+    block_on_all(connect.and_then(|mut con| async move {
+        con.sadd::<_, _, ()>("src", 42).await?;
+        // The arguments are encoded when the future is created, so the keys
+        // and the member may be dropped before it is awaited.
+        let moved = {
+            let src = String::from("src");
+            let dst = String::from("dst");
+            let member = 42.to_string();
+            con.smove::<_, _, bool>(&src, &dst, &member)
+        };
+        assert!(moved.await?);
+        let members: Vec<i32> = con.smembers("dst").await?;
+        assert_eq!(members, vec![42]);
+        Ok(())
+    }))
+    .unwrap();
+}
+
+#[test]
+fn test_write_with_replication() {
+    let ctx = TestContext::new();
+    let connect = ctx.multiplexed_async_connection();
+
+    block_on_all(connect.and_then(|mut con| async move {
+        let write = redis::Cmd::set("key", 42);
+        let ((), outcome) = con
+            .write_with_replication(&write, 0, std::time::Duration::from_millis(100))
+            .await?;
+        assert_eq!(outcome, redis::ReplicationOutcome::Acknowledged(0));
+
+        // The server has no replicas at all.
+        let ((), outcome) = con
+            .write_with_replication(&write, 1, std::time::Duration::from_millis(100))
+            .await?;
+        assert_eq!(
+            outcome,
+            redis::ReplicationOutcome::InsufficientReplicas {
+                acked: 0,
+                required: 1
+            }
+        );
+        Ok(())
+    }))
+    .unwrap();
+}
+
+#[test]
+fn test_store_with_ttl() {
+    let ctx = TestContext::new();
+    let connect = ctx.multiplexed_async_connection();
+
+    block_on_all(connect.and_then(|mut con| async move {
+        let () = con.sadd("s1", &["a", "b", "c"]).await?;
+        let () = con.sadd("s2", &["b", "c", "d"]).await?;
+        let count = con
+            .store_with_ttl(
+                &redis::Cmd::sdiffstore(&["dest"][..], &["s1", "s2"]),
+                "dest",
+                std::time::Duration::from_secs(100),
+            )
+            .await?;
+        assert_eq!(count, 1);
+        let ttl: i64 = con.ttl("dest").await?;
+        assert!(0 < ttl && ttl <= 100, "ttl is {}", ttl);
+        Ok(())
+    }))
+    .unwrap();
+}
+
+#[test]
+fn test_supported_commands() {
+    let ctx = TestContext::new();
+    let connect = ctx.async_connection();
+
+    block_on_all(connect.and_then(|mut con| async move {
+        let support = con.supported_commands(&["get", "no-such-command"]).await?;
+        assert_eq!(support.len(), 2);
+        assert!(support["get"]);
+        assert!(!support["no-such-command"]);
+        Ok(())
+    }))
+    .unwrap();
+}
+
+#[test]
+fn test_query_streaming() {
+    let ctx = TestContext::new();
+    let connect = ctx.async_connection();
+
+    block_on_all(connect.and_then(|mut con| async move {
+        let members: Vec<u64> = (0..10_000).collect();
+        con.sadd::<_, _, ()>("foo", &members).await?;
+
+        let stream = redis::cmd("SMEMBERS")
+            .arg("foo")
+            .query_streaming_async::<u64, _>(&mut con)
+            .await?;
+        let mut seen: Vec<u64> = stream.try_collect().await?;
+        seen.sort_unstable();
+        assert_eq!(seen, members);
+
+        // Dropping the stream early skips the rest of the reply before the
+        // next request.
+        let mut stream = redis::cmd("SMEMBERS")
+            .arg("foo")
+            .query_streaming_async::<u64, _>(&mut con)
+            .await?;
+        assert!(stream.next().await.unwrap().is_ok());
+        drop(stream);
+        let count: usize = con.scard("foo").await?;
+        assert_eq!(count, members.len());
+        Ok(())
+    }))
+    .unwrap();
+}
+
 #[test]
 fn dont_panic_on_closed_multiplexed_connection() {
     let ctx = TestContext::new();
@@ -148,11 +288,11 @@ fn test_cmd(con: &MultiplexedConnection, i: i32) -> impl Future<Output = RedisRe
         redis::cmd("SET")
             .arg(&key[..])
             .arg(foo_val.as_bytes())
-            .query_async(&mut con)
+            .query_async::<_, ()>(&mut con)
             .await?;
         redis::cmd("SET")
             .arg(&[&key2, "bar"])
-            .query_async(&mut con)
+            .query_async::<_, ()>(&mut con)
             .await?;
         redis::cmd("MGET")
             .arg(&[&key_2, &key2_2])
@@ -221,6 +361,173 @@ fn test_args_with_errors_multiplexed_connection() {
     .unwrap();
 }
 
+#[test]
+fn test_drain_waits_for_in_flight_requests() {
+    use std::time::Duration;
+
+    let ctx = TestContext::new();
+    block_on_all(async move {
+        let mut con = ctx.multiplexed_async_connection().await?;
+        let mut slow_con = con.clone();
+        let slow = async move {
+            cmd("DEBUG")
+                .arg("SLEEP")
+                .arg(0.2)
+                .query_async::<_, ()>(&mut slow_con)
+                .await
+        };
+        let drain = async {
+            // Let the slow command reach the server before draining.
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            con.drain(Duration::from_secs(5)).await
+        };
+        let (slow, drain) = future::join(slow, drain).await;
+        slow?;
+        drain?;
+
+        let err = con.get::<_, Option<String>>("key").await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ClientClosed);
+        Ok::<_, redis::RedisError>(())
+    })
+    .unwrap();
+}
+
+#[test]
+fn test_empty_arg_lists_are_rejected() {
+    let ctx = TestContext::new();
+    block_on_all(async move {
+        let mut con = ctx.multiplexed_async_connection().await?;
+        let empty: &[i32] = &[];
+        let err = con.rpush::<_, _, ()>("key", empty).await.unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ClientError);
+        let err = redis::pipe()
+            .sadd("key", empty)
+            .query_async::<_, ()>(&mut con)
+            .await
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ClientError);
+        Ok::<_, redis::RedisError>(())
+    })
+    .unwrap();
+}
+
+#[test]
+fn test_args_only_need_to_be_send() {
+    use redis::{RedisWrite, ToRedisArgs};
+    use std::cell::Cell;
+
+    // `Cell` makes this `Send` but not `Sync`.
+    struct Counter(Cell<i64>);
+
+    impl ToRedisArgs for Counter {
+        fn write_redis_args<W>(&self, out: &mut W)
+        where
+            W: ?Sized + RedisWrite,
+        {
+            self.0.get().write_redis_args(out)
+        }
+    }
+
+    fn assert_send<T: Send>(value: T) -> T {
+        value
+    }
+
+    let ctx = TestContext::new();
+    block_on_all(async move {
+        let mut con = ctx.multiplexed_async_connection().await?;
+        assert_send(con.set::<_, _, ()>("key", Counter(Cell::new(42)))).await?;
+        let value: i64 = assert_send(con.get("key")).await?;
+        assert_eq!(value, 42);
+        let value: i64 = assert_send(con.incr("key", Counter(Cell::new(1)))).await?;
+        assert_eq!(value, 43);
+        Ok::<_, redis::RedisError>(())
+    })
+    .unwrap();
+}
+
+#[test]
+fn test_coalescing_connection_stampede() {
+    use redis::aio::CoalescingConnection;
+
+    const STAMPEDE: usize = 100;
+
+    fn get_calls(info: &redis::InfoDict, command: &str) -> usize {
+        // Lines look like `cmdstat_get:calls=1,usec=2,usec_per_call=2.00`.
+        info.get::<String>(&format!("cmdstat_{}", command))
+            .and_then(|stats| {
+                stats
+                    .split(',')
+                    .find_map(|stat| stat.strip_prefix("calls=").map(str::to_owned))
+            })
+            .map_or(0, |calls| calls.parse().unwrap())
+    }
+
+    let ctx = TestContext::new();
+    block_on_all(async move {
+        let con = ctx.multiplexed_async_connection().await?;
+        let mut con = CoalescingConnection::new(con);
+        con.set::<_, _, ()>("hot", 42).await?;
+        cmd("CONFIG")
+            .arg("RESETSTAT")
+            .query_async::<_, ()>(&mut con)
+            .await?;
+
+        let reads = (0..STAMPEDE).map(|_| {
+            let mut con = con.clone();
+            async move { con.get::<_, i32>("hot").await }
+        });
+        let values = future::try_join_all(reads).await?;
+        assert_eq!(values, vec![42; STAMPEDE]);
+
+        // Non-deterministic commands are never coalesced.
+        let randoms = (0..10).map(|_| {
+            let mut con = con.clone();
+            async move { cmd("RANDOMKEY").query_async::<_, String>(&mut con).await }
+        });
+        future::try_join_all(randoms).await?;
+
+        let info: redis::InfoDict = cmd("INFO")
+            .arg("commandstats")
+            .query_async(&mut con)
+            .await?;
+        assert_eq!(get_calls(&info, "get"), 1);
+        assert_eq!(get_calls(&info, "randomkey"), 10);
+        Ok::<_, redis::RedisError>(())
+    })
+    .unwrap();
+}
+
+#[test]
+fn test_coalescing_connection_transaction() {
+    use redis::aio::CoalescingConnection;
+
+    const READS: usize = 10;
+
+    let ctx = TestContext::new();
+    block_on_all(async move {
+        let con = ctx.multiplexed_async_connection().await?;
+        let mut con = CoalescingConnection::new(con);
+        con.set::<_, _, ()>("hot", 42).await?;
+
+        cmd("MULTI").query_async::<_, ()>(&mut con).await?;
+        // Every read is queued on its own instead of sharing a `QUEUED` reply.
+        let reads = (0..READS).map(|_| {
+            let mut con = con.clone();
+            async move { con.get::<_, String>("hot").await }
+        });
+        let queued = future::try_join_all(reads).await?;
+        assert_eq!(queued, vec!["QUEUED"; READS]);
+        let values: Vec<i32> = cmd("EXEC").query_async(&mut con).await?;
+        assert_eq!(values, vec![42; READS]);
+
+        // Reads are coalesced again after the transaction.
+        let value: i32 = con.get("hot").await?;
+        assert_eq!(value, 42);
+        Ok::<_, redis::RedisError>(())
+    })
+    .unwrap();
+}
+
 #[test]
 fn test_transaction_multiplexed_connection() {
     let ctx = TestContext::new();
@@ -276,7 +583,7 @@ fn test_async_scanning(batch_size: usize) {
                         redis::cmd("SADD")
                             .arg("foo")
                             .arg(x)
-                            .query_async(&mut con)
+                            .query_async::<_, ()>(&mut con)
                             .await?;
                         unseen.insert(x);
                     }
@@ -333,14 +640,14 @@ fn test_script() {
         script1
             .key("key1")
             .arg("foo")
-            .invoke_async(&mut con)
+            .invoke_async::<_, ()>(&mut con)
             .await?;
         let val: String = script2.key("key1").invoke_async(&mut con).await?;
         assert_eq!(val, "foo");
         script1
             .key("key1")
             .arg("bar")
-            .invoke_async(&mut con)
+            .invoke_async::<_, ()>(&mut con)
             .await?;
         let val: String = script2.key("key1").invoke_async(&mut con).await?;
         assert_eq!(val, "bar");
@@ -445,7 +752,9 @@ async fn invalid_password_issue_343() {
             db: 0,
             username: None,
             password: Some("asdcasc".to_string()),
+            ..Default::default()
         },
+        tcp_keepalive: None,
     };
     let client = redis::Client::open(coninfo).unwrap();
     let err = client
@@ -461,6 +770,124 @@ async fn invalid_password_issue_343() {
     );
 }
 
+#[tokio::test]
+async fn cancellable_unblocks_blocked_command() {
+    use redis::UnblockKind;
+    use std::time::{Duration, Instant};
+
+    let ctx = TestContext::new();
+    let mut coninfo = ctx.client.get_connection_info().clone();
+    coninfo.redis.fetch_client_id = true;
+    let client = redis::Client::open(coninfo).unwrap();
+    let mut con = client.get_async_connection().await.unwrap();
+    let client_id = con.client_id().unwrap();
+
+    let start = Instant::now();
+    let popped: Option<(String, String)> = client
+        .cancellable(
+            client_id,
+            UnblockKind::Timeout,
+            con.blpop("queue", 30),
+            tokio::time::sleep(Duration::from_millis(100)),
+        )
+        .await
+        .unwrap();
+    assert_eq!(popped, None);
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    let err = client
+        .cancellable(
+            client_id,
+            UnblockKind::Error,
+            con.blpop::<_, Option<(String, String)>>("queue", 30),
+            tokio::time::sleep(Duration::from_millis(100)),
+        )
+        .await
+        .unwrap_err();
+    assert_eq!(err.code(), Some("UNBLOCKED"));
+    assert!(start.elapsed() < Duration::from_secs(5));
+
+    // Not cancelled, the command completes on its own.
+    let () = con.rpush("queue", "item").await.unwrap();
+    let popped: Option<(String, String)> = client
+        .cancellable(
+            client_id,
+            UnblockKind::Error,
+            con.blpop("queue", 30),
+            future::pending(),
+        )
+        .await
+        .unwrap();
+    assert_eq!(popped, Some(("queue".to_string(), "item".to_string())));
+}
+
+#[tokio::test]
+async fn cancellable_with_cancel_already_complete() {
+    use redis::UnblockKind;
+    use std::time::{Duration, Instant};
+
+    let ctx = TestContext::new();
+    let mut coninfo = ctx.client.get_connection_info().clone();
+    coninfo.redis.fetch_client_id = true;
+    let client = redis::Client::open(coninfo).unwrap();
+    let mut con = client.get_async_connection().await.unwrap();
+    let client_id = con.client_id().unwrap();
+
+    // The first `CLIENT UNBLOCK` likely arrives before the server started
+    // blocking and has to be repeated.
+    let start = Instant::now();
+    let popped: Option<(String, String)> = client
+        .cancellable(
+            client_id,
+            UnblockKind::Timeout,
+            con.blpop("queue", 0),
+            future::ready(()),
+        )
+        .await
+        .unwrap();
+    assert_eq!(popped, None);
+    assert!(start.elapsed() < Duration::from_secs(5));
+}
+
+#[tokio::test]
+async fn multiplexed_keepalive_detects_paused_server() {
+    let ctx = TestContext::new();
+    let mut coninfo = ctx.client.get_connection_info().clone();
+    coninfo.redis.ping_interval = Some(std::time::Duration::from_millis(100));
+    let client = redis::Client::open(coninfo).unwrap();
+    let mut con = client.get_multiplexed_tokio_connection().await.unwrap();
+    let () = con.set("a", "b").await.unwrap();
+
+    // Stalls every client, including the keepalive `PING`
+    let mut pauser = ctx.async_connection().await.unwrap();
+    let () = cmd("CLIENT")
+        .arg("PAUSE")
+        .arg(2000)
+        .query_async(&mut pauser)
+        .await
+        .unwrap();
+
+    tokio::time::sleep(std::time::Duration::from_millis(800)).await;
+    assert!(con.is_closed());
+    let err = con.get::<_, Option<String>>("a").await.unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::IoError);
+}
+
+#[tokio::test]
+async fn multiplexed_keepalive_waits_for_blocking_commands() {
+    let ctx = TestContext::new();
+    let mut coninfo = ctx.client.get_connection_info().clone();
+    coninfo.redis.ping_interval = Some(std::time::Duration::from_millis(100));
+    let client = redis::Client::open(coninfo).unwrap();
+    let mut con = client.get_multiplexed_tokio_connection().await.unwrap();
+
+    // Blocks for many ping intervals without any other traffic.
+    let popped: Option<(String, String)> = con.blpop("queue", 1).await.unwrap();
+    assert_eq!(popped, None);
+    assert!(!con.is_closed());
+    let () = con.set("a", "b").await.unwrap();
+}
+
 mod pub_sub {
     use std::collections::HashMap;
     use std::time::Duration;
@@ -477,7 +904,9 @@ mod pub_sub {
             pubsub_conn.subscribe("phonewave").await?;
             let mut pubsub_stream = pubsub_conn.on_message();
             let mut publish_conn = ctx.async_connection().await?;
-            publish_conn.publish("phonewave", "banana").await?;
+            publish_conn
+                .publish::<_, _, ()>("phonewave", "banana")
+                .await?;
 
             let msg_payload: String = pubsub_stream.next().await.unwrap().get_payload()?;
             assert_eq!("banana".to_string(), msg_payload);
@@ -565,7 +994,7 @@ mod pub_sub {
             redis::cmd("SET")
                 .arg("foo")
                 .arg("bar")
-                .query_async(&mut conn)
+                .query_async::<_, ()>(&mut conn)
                 .await?;
 
             let res: String = redis::cmd("GET").arg("foo").query_async(&mut conn).await?;
@@ -10,6 +10,9 @@ use std::collections::{HashMap, HashSet};
 use std::thread::{sleep, spawn};
 use std::time::Duration;
 
+#[cfg(feature = "test-server")]
+use redis::testing::TestServer;
+
 use crate::support::*;
 
 mod support;
@@ -22,15 +25,41 @@ fn test_parse_redis_url() {
     assert!(redis::parse_redis_url("127.0.0.1").is_none());
 }
 
+#[cfg(feature = "test-server")]
+#[test]
+fn test_test_server() {
+    let server = TestServer::builder()
+        .config("maxmemory", "10mb")
+        .start()
+        .unwrap();
+    let dir = server.dir().to_path_buf();
+    assert!(dir.is_dir());
+    // The cluster bus port, 10000 higher, has to be valid as well.
+    assert!(server.port().unwrap() <= 55535);
+
+    let mut con = server.connection().unwrap();
+    let maxmemory: (String, u64) = redis::cmd("CONFIG")
+        .arg("GET")
+        .arg("maxmemory")
+        .query(&mut con)
+        .unwrap();
+    assert_eq!(maxmemory.1, 10 * 1024 * 1024);
+
+    drop(server);
+    assert!(!dir.exists());
+    assert!(con.req_command(&redis::cmd("PING")).is_err());
+}
+
 #[test]
 fn test_redis_url_fromstr() {
     let _info: ConnectionInfo = "redis://127.0.0.1:1234/0".parse().unwrap();
 }
 
+#[cfg(feature = "test-server")]
 #[test]
 fn test_args() {
-    let ctx = TestContext::new();
-    let mut con = ctx.connection();
+    let server = TestServer::start().unwrap();
+    let mut con = server.connection().unwrap();
 
     redis::cmd("SET").arg("key1").arg(b"foo").execute(&mut con);
     redis::cmd("SET").arg(&["key2", "bar"]).execute(&mut con);
@@ -41,10 +70,11 @@ fn test_args() {
     );
 }
 
+#[cfg(feature = "test-server")]
 #[test]
 fn test_getset() {
-    let ctx = TestContext::new();
-    let mut con = ctx.connection();
+    let server = TestServer::start().unwrap();
+    let mut con = server.connection().unwrap();
 
     redis::cmd("SET").arg("foo").arg(42).execute(&mut con);
     assert_eq!(redis::cmd("GET").arg("foo").query(&mut con), Ok(42));
@@ -56,19 +86,21 @@ fn test_getset() {
     );
 }
 
+#[cfg(feature = "test-server")]
 #[test]
 fn test_incr() {
-    let ctx = TestContext::new();
-    let mut con = ctx.connection();
+    let server = TestServer::start().unwrap();
+    let mut con = server.connection().unwrap();
 
     redis::cmd("SET").arg("foo").arg(42).execute(&mut con);
     assert_eq!(redis::cmd("INCR").arg("foo").query(&mut con), Ok(43usize));
 }
 
+#[cfg(feature = "test-server")]
 #[test]
 fn test_getdel() {
-    let ctx = TestContext::new();
-    let mut con = ctx.connection();
+    let server = TestServer::start().unwrap();
+    let mut con = server.connection().unwrap();
 
     redis::cmd("SET").arg("foo").arg(42).execute(&mut con);
 
@@ -114,11 +146,160 @@ fn test_getex() {
     assert_eq!(delayed_get, 420usize);
 }
 
+// The same as `test_getex`, without depending on when the server happens to
+// expire the key.
 #[test]
-fn test_info() {
+#[cfg(feature = "debug-commands")]
+fn test_getex_deterministic() {
+    let ctx = TestContext::new();
+    let mut con = ctx.connection();
+    let mut con = redis::testing::freeze_expiry(&mut con).unwrap();
+    let con = &mut *con;
+
+    redis::cmd("SET").arg("foo").arg(42usize).execute(con);
+
+    // Return of get_ex must match set value
+    let ret_value = con.get_ex::<_, usize>("foo", Expiry::EX(1)).unwrap();
+    assert_eq!(ret_value, 42usize);
+
+    // Get before expiry time must also return value
+    sleep(Duration::from_millis(100));
+    let delayed_get = con.get::<_, usize>("foo").unwrap();
+    assert_eq!(delayed_get, 42usize);
+
+    // Get after expiry time mustn't return value
+    assert!(redis::testing::wait_for_expired(con, "foo", Duration::from_secs(2)).unwrap());
+    let after_expire_get = con.get::<_, Option<usize>>("foo").unwrap();
+    assert_eq!(after_expire_get, None);
+
+    // Persist option test prep
+    redis::cmd("SET").arg("foo").arg(420usize).execute(con);
+
+    // Return of get_ex with persist option must match set value
+    let ret_value = con.get_ex::<_, usize>("foo", Expiry::PERSIST).unwrap();
+    assert_eq!(ret_value, 420usize);
+
+    // Get after persist get_ex must return value
+    sleep(Duration::from_millis(200));
+    let delayed_get = con.get::<_, usize>("foo").unwrap();
+    assert_eq!(delayed_get, 420usize);
+}
+
+#[test]
+fn test_set_and_get_with_expiration() {
+    use redis::Expiration;
+    use std::time::SystemTime;
+
+    let ctx = TestContext::new();
+    let mut con = ctx.connection();
+
+    let _: () = con
+        .set_with_expiration("foo", 42, Expiration::In(Duration::from_millis(1500)))
+        .unwrap();
+    let pttl: i64 = con.pttl("foo").unwrap();
+    assert!(pttl > 1000 && pttl <= 1500);
+
+    // Setting a new value keeps the expiry
+    let _: () = con
+        .set_with_expiration("foo", 43, Expiration::Keep)
+        .unwrap();
+    let pttl: i64 = con.pttl("foo").unwrap();
+    assert!(pttl > 1000 && pttl <= 1500);
+
+    let value: usize = con
+        .get_with_expiration(
+            "foo",
+            Expiration::At(SystemTime::now() + Duration::from_secs(60)),
+        )
+        .unwrap();
+    assert_eq!(value, 43);
+    let ttl: i64 = con.ttl("foo").unwrap();
+    assert!(ttl > 50 && ttl <= 60);
+
+    let value: usize = con.get_with_expiration("foo", Expiration::Persist).unwrap();
+    assert_eq!(value, 43);
+    assert_eq!(con.ttl("foo"), Ok(-1));
+
+    // The options only one of the commands supports are refused unsent.
+    let err = con
+        .set_with_expiration::<_, _, ()>("foo", 45, Expiration::Persist)
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::ClientError);
+    let err = con
+        .get_with_expiration::<_, usize>("foo", Expiration::Keep)
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::ClientError);
+    assert_eq!(con.get("foo"), Ok(43));
+
+    // Zero expiries are rejected by the server, so they are sent as 1ms.
+    let _: () = con
+        .set_with_expiration("foo", 44, Expiration::In(Duration::from_secs(0)))
+        .unwrap();
+    sleep(Duration::from_millis(10));
+    assert_eq!(con.exists("foo"), Ok(false));
+}
+
+#[test]
+fn test_time_and_clock_skew() {
+    use std::time::SystemTime;
+
+    let ctx = TestContext::new();
+    let mut con = ctx.connection();
+
+    let server: SystemTime = con.time().unwrap();
+    let local = SystemTime::now();
+    let diff = local
+        .duration_since(server)
+        .unwrap_or_else(|err| err.duration());
+    assert!(diff < Duration::from_secs(5));
+
+    let (secs, micros): (u64, u32) = con.time().unwrap();
+    assert!(secs > 0 && micros < 1_000_000);
+
+    // The server runs on the same machine.
+    let skew = redis::util::clock_skew(&mut con).unwrap();
+    assert!(skew.abs() < 5000);
+}
+
+#[test]
+fn test_pattern_matches_literally() {
+    use redis::Pattern;
+
     let ctx = TestContext::new();
     let mut con = ctx.connection();
 
+    let keys = [
+        "k*", "k?", "k[a]", "ka", "k]", "k\\", "k\\*", "k^", "k-", "kxyz", "k[a-z]",
+    ];
+    for key in &keys {
+        let _: () = con.set(*key, 1).unwrap();
+    }
+
+    // Every literal pattern matches its own key and nothing else.
+    for key in &keys {
+        let matched: Vec<String> = con.keys(Pattern::literal(key)).unwrap();
+        assert_eq!(matched, vec![key.to_string()], "pattern for {:?}", key);
+
+        let mut scanned: Vec<String> = con.scan_match(Pattern::literal(key)).unwrap().collect();
+        scanned.sort();
+        assert_eq!(scanned, vec![key.to_string()], "pattern for {:?}", key);
+    }
+
+    let mut matched: Vec<String> = con.keys(Pattern::prefix("k\\")).unwrap();
+    matched.sort();
+    assert_eq!(matched, vec!["k\\".to_string(), "k\\*".to_string()]);
+
+    let mut matched: Vec<String> = con.keys(Pattern::raw("k[a-z]*")).unwrap();
+    matched.sort();
+    assert_eq!(matched, vec!["ka".to_string(), "kxyz".to_string()]);
+}
+
+#[cfg(feature = "test-server")]
+#[test]
+fn test_info() {
+    let server = TestServer::start().unwrap();
+    let mut con = server.connection().unwrap();
+
     let info: redis::InfoDict = redis::cmd("INFO").query(&mut con).unwrap();
     assert_eq!(
         info.find(&"role"),
@@ -130,10 +311,11 @@ fn test_info() {
     assert!(info.contains_key(&"role"));
 }
 
+#[cfg(feature = "test-server")]
 #[test]
 fn test_hash_ops() {
-    let ctx = TestContext::new();
-    let mut con = ctx.connection();
+    let server = TestServer::start().unwrap();
+    let mut con = server.connection().unwrap();
 
     redis::cmd("HSET")
         .arg("foo")
@@ -174,10 +356,11 @@ fn test_unlink() {
     assert_eq!(con.unlink(&["foo", "bar"]), Ok(2));
 }
 
+#[cfg(feature = "test-server")]
 #[test]
 fn test_set_ops() {
-    let ctx = TestContext::new();
-    let mut con = ctx.connection();
+    let server = TestServer::start().unwrap();
+    let mut con = server.connection().unwrap();
 
     assert_eq!(con.sadd("foo", &[1, 2, 3]), Ok(3));
 
@@ -199,10 +382,11 @@ fn test_set_ops() {
     assert!(set.contains(&3i32));
 }
 
+#[cfg(feature = "test-server")]
 #[test]
 fn test_scan() {
-    let ctx = TestContext::new();
-    let mut con = ctx.connection();
+    let server = TestServer::start().unwrap();
+    let mut con = server.connection().unwrap();
 
     assert_eq!(con.sadd("foo", &[1, 2, 3]), Ok(3));
 
@@ -239,6 +423,74 @@ fn test_optionals() {
     assert_eq!(a, 0i32);
 }
 
+#[test]
+fn test_optional_vecs() {
+    use std::num::NonZeroUsize;
+
+    let ctx = TestContext::new();
+    let mut con = ctx.connection();
+
+    // Emptied keys are deleted, so they reply like missing keys.
+    assert_eq!(con.rpush("list", 1), Ok(1));
+    assert_eq!(con.rpop("list", None), Ok(1));
+    assert_eq!(con.sadd("set", 1), Ok(1));
+    assert_eq!(con.srem("set", 1), Ok(1));
+    assert_eq!(con.zadd("zset", 1, 1), Ok(1));
+    assert_eq!(con.zrem("zset", 1), Ok(1));
+
+    let empty = Some(Vec::<i32>::new());
+    assert_eq!(con.lrange("missing_list", 0, -1), Ok(empty.clone()));
+    assert_eq!(con.lrange("list", 0, -1), Ok(empty.clone()));
+    assert_eq!(con.smembers("missing_set"), Ok(empty.clone()));
+    assert_eq!(con.smembers("set"), Ok(empty.clone()));
+    assert_eq!(con.zrange("missing_zset", 0, -1), Ok(empty.clone()));
+    assert_eq!(con.zrange("zset", 0, -1), Ok(empty));
+    assert_eq!(con.zrange("missing_zset", 0, -1), Ok(Vec::<i32>::new()));
+
+    // LPOP with a count is one of the few commands replying nil.
+    let count = NonZeroUsize::new(2);
+    assert_eq!(con.lpop("missing_list", count), Ok(None::<Vec<i32>>));
+    assert_eq!(con.lpop("missing_list", count), Ok(Vec::<i32>::new()));
+    assert_eq!(con.rpush("list", &[1, 2, 3]), Ok(3));
+    assert_eq!(con.lpop("list", count), Ok(Some(vec![1, 2])));
+}
+
+#[test]
+fn test_query_streaming() {
+    let ctx = TestContext::new();
+    let mut con = ctx.connection();
+
+    let members: Vec<u64> = (0..10_000).collect();
+    redis::cmd("SADD")
+        .arg("foo")
+        .arg(&members)
+        .execute(&mut con);
+
+    let iter = redis::cmd("SMEMBERS")
+        .arg("foo")
+        .query_streaming::<u64>(&mut con)
+        .unwrap();
+    assert_eq!(iter.len(), members.len());
+    let mut seen: Vec<u64> = iter.map(|member| member.unwrap()).collect();
+    seen.sort_unstable();
+    assert_eq!(seen, members);
+
+    // Dropping the iterator early skips the rest of the reply.
+    let mut iter = redis::cmd("SMEMBERS")
+        .arg("foo")
+        .query_streaming::<u64>(&mut con)
+        .unwrap();
+    assert!(iter.next().unwrap().is_ok());
+    drop(iter);
+    assert_eq!(con.scard("foo"), Ok(members.len()));
+
+    let mut iter = redis::cmd("SMEMBERS")
+        .arg("missing")
+        .query_streaming::<u64>(&mut con)
+        .unwrap();
+    assert!(iter.next().is_none());
+}
+
 #[test]
 fn test_scanning() {
     let ctx = TestContext::new();
@@ -266,6 +518,35 @@ fn test_scanning() {
     assert_eq!(unseen.len(), 0);
 }
 
+#[test]
+fn test_scanning_from_cloned_cursor() {
+    let ctx = TestContext::new();
+    let mut con = ctx.connection();
+    let mut unseen = HashSet::new();
+
+    for x in 0..1000 {
+        redis::cmd("SADD").arg("foo").arg(x).execute(&mut con);
+        unseen.insert(x);
+    }
+
+    let mut cmd = redis::cmd("SSCAN");
+    cmd.arg("foo").cursor_arg(0).arg("COUNT").arg(10);
+    let (cursor, first): (u64, Vec<usize>) = cmd.query(&mut con).unwrap();
+    assert_ne!(cursor, 0);
+    cmd.set_cursor(cursor);
+
+    // The clone continues where the first batch ended instead of starting
+    // over, so every member is seen exactly once.
+    let copy = cmd.clone();
+    assert_eq!(copy.cursor(), Some(cursor));
+    let rest: Vec<usize> = copy.iter(&mut con).unwrap().collect();
+    assert_eq!(first.len() + rest.len(), 1000);
+    for x in first.into_iter().chain(rest) {
+        assert!(unseen.remove(&x));
+    }
+    assert_eq!(unseen.len(), 0);
+}
+
 #[test]
 fn test_filtered_scanning() {
     let ctx = TestContext::new();
@@ -386,6 +667,38 @@ fn test_pipeline_transaction() {
     assert_eq!(k2, 43);
 }
 
+#[test]
+fn test_pipeline_ignore_with_multi_command_entries() {
+    let ctx = TestContext::new();
+    let mut con = ctx.connection();
+
+    for &atomic in &[false, true] {
+        let mut pipe = redis::pipe();
+        if atomic {
+            pipe.atomic();
+        }
+        let ((k1, k2), len): ((i32, i32), usize) = pipe
+            .set("key_1", 42)
+            .ignore()
+            .add_commands(vec![
+                redis::Cmd::set("key_2", 43),
+                redis::Cmd::rpush("list", "x"),
+            ])
+            .ignore()
+            .add_commands(vec![redis::Cmd::get("key_1"), redis::Cmd::get("key_2")])
+            .add_commands(vec![])
+            .ignore()
+            .llen("list")
+            .del(&["key_1", "key_2", "list"])
+            .ignore()
+            .query(&mut con)
+            .unwrap();
+
+        assert_eq!((k1, k2), (42, 43));
+        assert_eq!(len, 1);
+    }
+}
+
 #[test]
 fn test_pipeline_transaction_with_errors() {
     let ctx = TestContext::new();
@@ -584,6 +897,106 @@ fn test_pubsub() {
     thread.join().expect("Something went wrong");
 }
 
+#[test]
+fn test_invalid_utf8_replies() {
+    use redis::LossyString;
+
+    let ctx = TestContext::new();
+    let mut con = ctx.connection();
+
+    let () = con.set("key", &b"ab\xffcd"[..]).unwrap();
+    let err = con.get::<_, String>("key").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TypeError);
+    assert_eq!(err.detail(), Some("invalid byte at offset 2"));
+    let lossy: LossyString = con.get("key").unwrap();
+    assert_eq!(lossy.as_str(), "ab\u{fffd}cd");
+
+    let () = con.hset("hash", "field", &b"\xff"[..]).unwrap();
+    let err = con
+        .hgetall::<_, HashMap<String, String>>("hash")
+        .unwrap_err();
+    assert_eq!(err.detail(), Some("invalid byte at offset 0"));
+    let map: HashMap<String, LossyString> = con.hgetall("hash").unwrap();
+    assert_eq!(map["field"].as_str(), "\u{fffd}");
+
+    let mut pubsub_con = ctx.connection();
+    let mut pubsub = pubsub_con.as_pubsub();
+    pubsub.subscribe("channel").unwrap();
+    assert_eq!(con.publish("channel", &b"\xfe"[..]), Ok(1));
+    let msg = pubsub.get_message().unwrap();
+    assert!(msg.get_payload::<String>().is_err());
+    assert_eq!(msg.get_payload_str(), None);
+    assert_eq!(msg.get_payload_bytes(), b"\xfe");
+    let lossy: LossyString = msg.get_payload().unwrap();
+    assert_eq!(lossy.as_str(), "\u{fffd}");
+}
+
+#[test]
+fn test_pubsub_confirmations() {
+    use redis::{Msg, SubscriptionKind};
+
+    let ctx = TestContext::new();
+    let mut con = ctx.connection();
+    let mut pubsub_con = ctx.connection();
+
+    let mut pubsub = pubsub_con.as_pubsub();
+    // `subscribe` only reads the confirmation of the first channel.
+    pubsub.subscribe(&["foo", "bar"]).unwrap();
+    assert_eq!(
+        pubsub.get_frame(),
+        Ok(Msg::Subscribed {
+            kind: SubscriptionKind::Channel,
+            channel: redis::Value::Data(b"bar".to_vec()),
+            count: 2,
+        })
+    );
+
+    assert_eq!(con.publish("bar", 23), Ok(1));
+    let msg = pubsub.get_message().unwrap();
+    assert_eq!(msg.get_channel_name(), "bar");
+    assert_eq!(msg.get_payload_str(), Some("23"));
+
+    // `get_message` skips the confirmations `subscribe` leaves unread.
+    pubsub.subscribe(&["baz", "qux"]).unwrap();
+    assert_eq!(con.publish("qux", 42), Ok(1));
+    let msg = pubsub.get_message().unwrap();
+    assert_eq!(msg.get_channel_name(), "qux");
+    assert_eq!(msg.get_payload_str(), Some("42"));
+}
+
+#[test]
+fn test_pubsub_introspection() {
+    let ctx = TestContext::new();
+    let mut con = ctx.connection();
+    let mut pubsub_con = ctx.connection();
+
+    let mut pubsub = pubsub_con.as_pubsub();
+    pubsub.subscribe("foo").unwrap();
+    pubsub.subscribe("bar").unwrap();
+    pubsub.psubscribe("baz*").unwrap();
+
+    let channels: HashSet<String> = con.pubsub_channels().unwrap();
+    assert_eq!(
+        channels,
+        vec!["foo".to_string(), "bar".to_string()]
+            .into_iter()
+            .collect()
+    );
+    let channels: Vec<String> = con.pubsub_channels_match("f*").unwrap();
+    assert_eq!(channels, vec!["foo".to_string()]);
+
+    let counts: Vec<(String, u64)> = con.pubsub_numsub(&["qux", "foo", "bar"]).unwrap();
+    assert_eq!(
+        counts,
+        vec![
+            ("qux".to_string(), 0),
+            ("foo".to_string(), 1),
+            ("bar".to_string(), 1),
+        ]
+    );
+    assert_eq!(con.pubsub_numpat(), Ok(1));
+}
+
 #[test]
 fn test_pubsub_unsubscribe() {
     let ctx = TestContext::new();
@@ -830,6 +1243,11 @@ fn test_nice_hash_api() {
     );
 
     assert_eq!(con.hget("my_hash", &["f2", "f4"]), Ok((2, 8)));
+
+    let fields: HashMap<_, _> = [("f5", 16), ("f6", 32)].iter().cloned().collect();
+    assert_eq!(con.hset_map("my_hash", &fields), Ok(2));
+    assert_eq!(con.hget("my_hash", &["f5", "f6"]), Ok((16, 32)));
+    assert_eq!(con.hdel("my_hash", &["f5", "f6"]), Ok(2));
     assert_eq!(con.hincr("my_hash", "f1", 1), Ok(2));
     assert_eq!(con.hincr("my_hash", "f2", 1.5f32), Ok(3.5f32));
     assert_eq!(con.hexists("my_hash", "f2"), Ok(true));
@@ -864,6 +1282,12 @@ fn test_nice_list_api() {
     assert_eq!(con.lset("my_list", 0, 4), Ok(true));
     assert_eq!(con.lrange("my_list", 0, 2), Ok((4, 3, 4)));
 
+    assert_eq!(con.linsert("my_list", redis::Where::Before, 3, 9), Ok(8));
+    assert_eq!(con.linsert("my_list", redis::Where::After, 3, 10), Ok(9));
+    assert_eq!(con.lrange("my_list", 0, 3), Ok((4, 9, 3, 10)));
+    assert_eq!(con.lrem("my_list", 0, 9), Ok(1));
+    assert_eq!(con.lrem("my_list", 0, 10), Ok(1));
+
     #[cfg(not(windows))]
     //Windows version of redis is limited to v3.x
     {
@@ -875,6 +1299,159 @@ fn test_nice_list_api() {
     }
 }
 
+#[test]
+fn test_empty_arg_lists_are_rejected() {
+    use redis::Cmd;
+
+    let ctx = TestContext::new();
+    let mut con = ctx.connection();
+
+    let empty: &[&str] = &[];
+    let pairs: &[(&str, &str)] = &[];
+    let commands = vec![
+        Cmd::del(empty),
+        Cmd::exists(empty),
+        Cmd::unlink(empty),
+        Cmd::set_multiple(pairs),
+        Cmd::mset_nx(pairs),
+        Cmd::hdel("key", empty),
+        Cmd::hset_multiple("key", pairs),
+        Cmd::lpush("key", empty),
+        Cmd::lpush_exists("key", empty),
+        Cmd::rpush("key", empty),
+        Cmd::rpush_exists("key", empty),
+        Cmd::sadd("key", empty),
+        Cmd::srem("key", empty),
+        Cmd::sdiff(empty),
+        Cmd::sinter(empty),
+        Cmd::sunion(empty),
+        Cmd::zrem("key", empty),
+        Cmd::pfcount(empty),
+    ];
+    for command in &commands {
+        let err = command.query::<()>(&mut con).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ClientError);
+        let mut pipe = redis::pipe();
+        pipe.set("key", 1).ignore().add_command(command.clone());
+        let err = pipe.query::<()>(&mut con).unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ClientError);
+    }
+
+    // Nothing was sent, not even the `SET` in the pipeline.
+    assert_eq!(con.exists("key"), Ok(false));
+    assert_eq!(con.lpush("key", &[1, 2]), Ok(2));
+
+    // The server rejects the command if the check is skipped.
+    let err = Cmd::lpush("key", empty)
+        .allow_empty_args()
+        .query::<()>(&mut con)
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::ResponseError);
+}
+
+#[test]
+fn test_unsigned_count_args() {
+    // Counts, offsets and sizes which must not be negative are unsigned.
+    let count: usize = 2;
+    assert_eq!(
+        redis::Cmd::zpopmax("key", count).get_packed_command(),
+        redis::cmd("ZPOPMAX").arg("key").arg(2).get_packed_command()
+    );
+    assert_eq!(
+        redis::Cmd::zmpop_min(&["key"], count).get_packed_command(),
+        redis::cmd("ZMPOP")
+            .arg(1)
+            .arg("key")
+            .arg("MIN")
+            .arg("COUNT")
+            .arg(2)
+            .get_packed_command()
+    );
+    assert_eq!(
+        redis::Cmd::setrange("key", count, "value").get_packed_command(),
+        redis::cmd("SETRANGE")
+            .arg("key")
+            .arg(2)
+            .arg("value")
+            .get_packed_command()
+    );
+}
+
+#[test]
+fn test_zadd_options() {
+    use redis::ZAddOptions;
+
+    assert_eq!(
+        redis::Cmd::zadd_options("key", "one", 1, ZAddOptions::default().gt().ch())
+            .get_packed_command(),
+        redis::cmd("ZADD")
+            .arg(&["key", "GT", "CH", "1", "one"])
+            .get_packed_command()
+    );
+    assert_eq!(
+        redis::Cmd::zadd_incr("key", "one", 2, ZAddOptions::default().xx().lt())
+            .get_packed_command(),
+        redis::cmd("ZADD")
+            .arg(&["key", "XX", "LT", "INCR", "2", "one"])
+            .get_packed_command()
+    );
+
+    let ctx = TestContext::new();
+    let mut con = ctx.connection();
+
+    let opts = ZAddOptions::default();
+    assert_eq!(
+        con.zadd_multiple_options("zset", &[(1, "one"), (2, "two")], opts),
+        Ok(2)
+    );
+    assert_eq!(con.zadd_options("zset", "one", 3, opts.gt().ch()), Ok(1));
+    assert_eq!(con.zadd_options("zset", "one", 2, opts.gt().ch()), Ok(0));
+    assert_eq!(con.zadd_options("zset", "three", 3, opts.xx()), Ok(0));
+    assert_eq!(con.zscore("zset", "one"), Ok(3));
+
+    assert_eq!(con.zadd_incr("zset", "two", 1.5, opts), Ok(Some(3.5)));
+    assert_eq!(con.zadd_incr("zset", "two", 1, opts.nx()), Ok(None::<f64>));
+    assert_eq!(con.zadd_incr("zset", "two", -1, opts.gt()), Ok(None::<f64>));
+}
+
+#[test]
+fn test_zadd_invalid_options() {
+    use redis::{ToRedisArgs, ZAddOptions};
+
+    let ctx = TestContext::new();
+    let mut con = ctx.connection();
+
+    let opts = ZAddOptions::default();
+    for invalid in &[
+        opts.nx().xx(),
+        opts.nx().gt(),
+        opts.nx().lt(),
+        opts.gt().lt(),
+    ] {
+        // The server rejects the combination too, with the same message.
+        let server_err = redis::cmd("ZADD")
+            .arg("zset")
+            .arg(invalid.to_redis_args())
+            .arg(1)
+            .arg("one")
+            .query::<usize>(&mut con)
+            .unwrap_err();
+        let client_err = con.zadd_options::<_, _, _, usize>("zset", "one", 1, *invalid);
+        let client_err = client_err.unwrap_err();
+        assert_eq!(client_err.kind(), ErrorKind::ClientError);
+        assert_eq!(server_err.kind(), ErrorKind::ResponseError);
+        assert!(client_err
+            .to_string()
+            .contains(server_err.detail().unwrap()));
+
+        let err = con
+            .zadd_incr::<_, _, _, Option<f64>>("zset", "one", 1, *invalid)
+            .unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ClientError);
+    }
+    assert_eq!(con.exists("zset"), Ok(false));
+}
+
 #[test]
 fn test_tuple_decoding_regression() {
     let ctx = TestContext::new();
@@ -948,6 +1525,81 @@ fn test_zrembylex() {
     assert_eq!(remaining, vec!["apple".to_string(), "grapes".to_string()]);
 }
 
+#[test]
+fn test_count_arguments() {
+    use redis::{Count, LposOptions};
+
+    let ctx = TestContext::new();
+    let mut con = ctx.connection();
+
+    let () = con.rpush("list", &["a", "b", "a", "a"]).unwrap();
+    assert_eq!(
+        con.lpos("list", "a", LposOptions::default().count_all()),
+        Ok(vec![0, 2, 3])
+    );
+    assert_eq!(
+        con.lpos("list", "a", LposOptions::default().count(2)),
+        Ok(vec![0, 2])
+    );
+
+    let () = con.sadd("set", "one").unwrap();
+    let members: Vec<String> = con.srandmember_count("set", Count::exactly(3)).unwrap();
+    assert_eq!(members, vec!["one"]);
+    let members: Vec<String> = con
+        .srandmember_count("set", Count::with_duplicates(3))
+        .unwrap();
+    assert_eq!(members, vec!["one", "one", "one"]);
+}
+
+#[test]
+fn test_sort_with_get_patterns() {
+    use redis::SortOptions;
+
+    let ctx = TestContext::new();
+    let mut con = ctx.connection();
+
+    let () = con.rpush("ids", &[1, 2, 3]).unwrap();
+    for (id, weight, name) in &[(1, 30, "one"), (2, 10, "two"), (3, 20, "three")] {
+        let () = con.set(format!("weight_{}", id), weight).unwrap();
+        let () = con.set(format!("name_{}", id), name).unwrap();
+    }
+
+    let opts = SortOptions::default().by("weight_*").get("#").get("name_*");
+    let sorted: Vec<(u32, String)> = con.sort("ids", opts).unwrap();
+    assert_eq!(
+        sorted,
+        vec![
+            (2, "two".to_string()),
+            (3, "three".to_string()),
+            (1, "one".to_string())
+        ]
+    );
+
+    let sorted: Vec<u32> = con
+        .sort("ids", SortOptions::default().desc().limit(0, 2))
+        .unwrap();
+    assert_eq!(sorted, vec![3, 2]);
+}
+
+#[test]
+fn test_zunionstore_weights() {
+    let ctx = TestContext::new();
+    let mut con = ctx.connection();
+
+    let () = con.zadd("{z}a", "x", 1).unwrap();
+    let () = con.zadd("{z}b", "x", 2).unwrap();
+    assert_eq!(
+        con.zunionstore_weights("{z}dst", &[("{z}a", 2), ("{z}b", 3)]),
+        Ok(1)
+    );
+    assert_eq!(con.zscore("{z}dst", "x"), Ok(8));
+    assert_eq!(
+        con.zinterstore_weights("{z}dst", &[("{z}a", 1), ("{z}b", 0)]),
+        Ok(1)
+    );
+    assert_eq!(con.zscore("{z}dst", "x"), Ok(1));
+}
+
 // Requires redis-server >= 6.2.0.
 // Not supported with the current appveyor/windows binary deployed.
 #[cfg(not(target_os = "windows"))]
@@ -991,6 +1643,302 @@ fn test_zrandmember() {
     assert_eq!(results.len(), 10);
 }
 
+#[test]
+fn test_dump_restore() {
+    let ctx = TestContext::new();
+    let mut con = ctx.connection();
+
+    assert_eq!(con.rpush("source", &[1, 2, 3]), Ok(3));
+    let payload: redis::DumpPayload = con.dump("source").unwrap();
+    assert!(payload.validate().is_ok());
+    assert_eq!(con.restore("target", 0, &payload), Ok(()));
+    assert_eq!(con.lrange("target", 0, -1), Ok(vec![1, 2, 3]));
+
+    let missing: Option<redis::DumpPayload> = con.dump("missing").unwrap();
+    assert_eq!(missing, None);
+}
+
+#[test]
+fn test_server_config() {
+    use redis::config::{ByteSize, MaxmemoryPolicy, NotifyFlags, ServerConfig};
+
+    let ctx = TestContext::new();
+    let mut con = ctx.connection();
+
+    let config = ServerConfig::new()
+        .maxmemory(ByteSize::mib(100))
+        .maxmemory_policy(MaxmemoryPolicy::AllkeysLru)
+        .notify_keyspace_events(NotifyFlags::EXPIRED | NotifyFlags::KEYEVENT)
+        .appendonly(true)
+        .timeout(Duration::from_secs(300))
+        .tcp_keepalive(Duration::from_secs(60));
+    for (parameter, value) in config.iter() {
+        let () = con.config_set(parameter, value).unwrap();
+    }
+
+    let current: ServerConfig = con.config_get("maxmemory").unwrap();
+    assert_eq!(current.get_maxmemory(), Some(ByteSize::mib(100)));
+    let current: ServerConfig = con.config_get("maxmemory-policy").unwrap();
+    assert_eq!(
+        current.get_maxmemory_policy(),
+        Some(MaxmemoryPolicy::AllkeysLru)
+    );
+    let current: ServerConfig = con.config_get("notify-keyspace-events").unwrap();
+    assert_eq!(
+        current.get_notify_keyspace_events(),
+        Some(NotifyFlags::EXPIRED | NotifyFlags::KEYEVENT)
+    );
+    let current: ServerConfig = con.config_get("appendonly").unwrap();
+    assert_eq!(current.get_appendonly(), Some(true));
+    let current: ServerConfig = con.config_get("timeout").unwrap();
+    assert_eq!(current.get_timeout(), Some(Duration::from_secs(300)));
+    let current: ServerConfig = con.config_get("tcp-keepalive").unwrap();
+    assert_eq!(current.get_tcp_keepalive(), Some(Duration::from_secs(60)));
+
+    let (_, policy): (String, MaxmemoryPolicy) = con.config_get("maxmemory-policy").unwrap();
+    assert_eq!(policy, MaxmemoryPolicy::AllkeysLru);
+
+    let () = con
+        .config_set(
+            "notify-keyspace-events",
+            NotifyFlags::ALL | NotifyFlags::KEYSPACE,
+        )
+        .unwrap();
+    let (_, flags): (String, NotifyFlags) = con.config_get("notify-keyspace-events").unwrap();
+    assert_eq!(flags, NotifyFlags::ALL | NotifyFlags::KEYSPACE);
+}
+
+#[cfg(feature = "test-server")]
+#[test]
+fn test_keyspace_notifications() {
+    use redis::config::{notify_config_check, NotifyFlags};
+
+    // The transport does not matter here, so a server of its own is enough.
+    let server = TestServer::start().unwrap();
+    let mut con = server.connection().unwrap();
+    let mut pubsub_con = server.connection().unwrap();
+
+    // Notifications are disabled by default.
+    let required = NotifyFlags::KEYSPACE | NotifyFlags::STRING;
+    let report = notify_config_check(&mut con, required).unwrap();
+    assert!(!report.is_ok());
+    assert_eq!(report.configured, NotifyFlags::NONE);
+    assert_eq!(report.missing, required);
+
+    let () = con.config_set("notify-keyspace-events", required).unwrap();
+    assert!(notify_config_check(&mut con, required).unwrap().is_ok());
+
+    // The subscription follows the database selected on the connection.
+    let () = redis::cmd("SELECT").arg(1).query(&mut pubsub_con).unwrap();
+    assert_eq!(pubsub_con.get_db(), 1);
+    let mut pubsub = pubsub_con.as_pubsub();
+    pubsub.psubscribe_keyspace(None, "key*").unwrap();
+
+    let () = redis::cmd("SELECT").arg(1).query(&mut con).unwrap();
+    let () = con.set("key", 42).unwrap();
+    let msg = pubsub.get_message().unwrap();
+    assert_eq!(msg.get_channel_name(), "__keyspace@1__:key");
+    assert_eq!(msg.get_payload(), Ok("set".to_string()));
+}
+
+#[test]
+fn test_write_with_replication() {
+    use redis::{ReplicationOutcome, Role};
+
+    let master = TestContext::new();
+    let port = match master.server.get_client_addr() {
+        redis::ConnectionAddr::Tcp(_, port) => *port,
+        // Replication needs a TCP master.
+        _ => return,
+    };
+    let replica = TestContext::new();
+    let mut con = master.connection();
+    let mut replica_con = replica.connection();
+    let () = redis::cmd("REPLICAOF")
+        .arg("127.0.0.1")
+        .arg(port)
+        .query(&mut replica_con)
+        .unwrap();
+    loop {
+        if let Role::Replica { state, .. } = replica_con.role().unwrap() {
+            if state == "connected" {
+                break;
+            }
+        }
+        sleep(Duration::from_millis(10));
+    }
+
+    let write = redis::Cmd::set("key", 42);
+    let (reply, outcome): (String, _) = con
+        .write_with_replication(&write, 1, Duration::from_secs(5))
+        .unwrap();
+    assert_eq!(reply, "OK");
+    assert_eq!(outcome, ReplicationOutcome::Acknowledged(1));
+
+    // A sleeping replica does not acknowledge anything.
+    let sleeper = spawn(move || {
+        redis::cmd("DEBUG")
+            .arg("SLEEP")
+            .arg(1)
+            .execute(&mut replica_con);
+    });
+    sleep(Duration::from_millis(100));
+    let write = redis::Cmd::incr("counter", 1);
+    let (counter, outcome): (i64, _) = con
+        .write_with_replication(&write, 1, Duration::from_millis(100))
+        .unwrap();
+    assert_eq!(counter, 1);
+    assert_eq!(
+        outcome,
+        ReplicationOutcome::InsufficientReplicas {
+            acked: 0,
+            required: 1
+        }
+    );
+    sleeper.join().unwrap();
+}
+
+#[test]
+fn test_write_with_replication_short_timeout() {
+    use redis::ReplicationOutcome;
+
+    let ctx = TestContext::new();
+    let mut con = ctx.connection();
+
+    // Would be sent as `WAIT 1 0` and block forever if not rounded up.
+    let (reply, outcome): (String, _) = con
+        .write_with_replication(&redis::Cmd::set("key", 42), 1, Duration::from_micros(1))
+        .unwrap();
+    assert_eq!(reply, "OK");
+    assert_eq!(
+        outcome,
+        ReplicationOutcome::InsufficientReplicas {
+            acked: 0,
+            required: 1
+        }
+    );
+}
+
+#[test]
+fn test_command_info_packing() {
+    assert_eq!(
+        redis::Cmd::command_info(&["get", "set"]).get_packed_command(),
+        redis::cmd("COMMAND")
+            .arg("INFO")
+            .arg("get")
+            .arg("set")
+            .get_packed_command()
+    );
+}
+
+#[test]
+fn test_supported_commands() {
+    let ctx = TestContext::new();
+    let mut con = ctx.connection();
+
+    let support = con
+        .supported_commands(&["get", "no-such-command", "SET"])
+        .unwrap();
+    assert_eq!(support.len(), 3);
+    assert!(support["get"]);
+    assert!(!support["no-such-command"]);
+    assert!(support["SET"]);
+
+    assert!(con.supported_commands(&[]).unwrap().is_empty());
+}
+
+#[test]
+fn test_store_with_ttl() {
+    let ctx = TestContext::new();
+    let mut con = ctx.connection();
+
+    let () = con.sadd("s1", &["a", "b", "c"]).unwrap();
+    let () = con.sadd("s2", &["b", "c", "d"]).unwrap();
+    // A plain SINTERSTORE would drop this TTL along with the old value.
+    let () = con.set_ex("dest", "old", 1000).unwrap();
+
+    let count = con
+        .store_with_ttl(
+            &redis::Cmd::sinterstore(&["dest"][..], &["s1", "s2"]),
+            "dest",
+            Duration::from_secs(100),
+        )
+        .unwrap();
+    assert_eq!(count, 2);
+    let members: BTreeSet<String> = con.smembers("dest").unwrap();
+    assert_eq!(members, ["b", "c"].iter().map(|m| m.to_string()).collect());
+    let ttl: i64 = con.ttl("dest").unwrap();
+    assert!(0 < ttl && ttl <= 100, "ttl is {}", ttl);
+
+    let count = con
+        .store_with_ttl(
+            &redis::Cmd::zunionstore("zdest", &["s1", "s2"]),
+            "zdest",
+            Duration::from_secs(100),
+        )
+        .unwrap();
+    assert_eq!(count, 4);
+    let ttl: i64 = con.ttl("zdest").unwrap();
+    assert!(0 < ttl && ttl <= 100, "ttl is {}", ttl);
+
+    // A zero TTL would delete the result, nothing is sent.
+    let err = con
+        .store_with_ttl(
+            &redis::Cmd::sinterstore(&["zdest"][..], &["s1", "s2"]),
+            "zdest",
+            Duration::from_secs(0),
+        )
+        .unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::ClientError);
+    assert_eq!(con.zcard("zdest"), Ok(4));
+
+    // Below a millisecond the TTL is rounded up instead of becoming zero.
+    let count = con
+        .store_with_ttl(
+            &redis::Cmd::sinterstore(&["dest"][..], &["s1", "s2"]),
+            "dest",
+            Duration::from_micros(500),
+        )
+        .unwrap();
+    assert_eq!(count, 2);
+    sleep(Duration::from_millis(10));
+    assert_eq!(con.exists("dest"), Ok(false));
+}
+
+#[test]
+fn test_latency_monitor() {
+    use redis::{LatencyEvent, LatencyLatestEntry};
+
+    let ctx = TestContext::new();
+    let mut con = ctx.connection();
+
+    redis::cmd("CONFIG")
+        .arg("SET")
+        .arg("latency-monitor-threshold")
+        .arg(1)
+        .execute(&mut con);
+    redis::cmd("DEBUG").arg("SLEEP").arg(0.01).execute(&mut con);
+
+    let latest: Vec<LatencyLatestEntry> = con.latency_latest().unwrap();
+    let entry = latest
+        .iter()
+        .find(|entry| entry.event == LatencyEvent::Command)
+        .unwrap();
+    assert!(entry.latest_ms >= 10);
+    assert!(entry.max_ms >= entry.latest_ms);
+
+    let history: Vec<(u64, u64)> = con.latency_history(LatencyEvent::Command).unwrap();
+    assert!(!history.is_empty());
+
+    assert_eq!(con.latency_reset_events(&[LatencyEvent::Command]), Ok(1));
+    let _: usize = con.latency_reset().unwrap();
+    redis::cmd("CONFIG")
+        .arg("SET")
+        .arg("latency-monitor-threshold")
+        .arg(0)
+        .execute(&mut con);
+}
+
 #[test]
 fn test_object_commands() {
     let ctx = TestContext::new();
@@ -1008,6 +1956,10 @@ fn test_object_commands() {
         con.object_encoding::<_, String>("object_key_int").unwrap(),
         "int"
     );
+    assert_eq!(
+        con.object_encoding("object_key_int"),
+        Ok(redis::Encoding::Int)
+    );
 
     assert_eq!(con.object_idletime::<_, i32>("object_key_str").unwrap(), 0);
     assert_eq!(con.object_refcount::<_, i32>("object_key_str").unwrap(), 1);
@@ -1025,3 +1977,90 @@ fn test_object_commands() {
     // get after that
     assert_eq!(con.object_freq::<_, i32>("object_key_str").unwrap(), 1);
 }
+
+#[test]
+fn test_client_unblock() {
+    use redis::UnblockKind;
+    use std::time::Instant;
+
+    let ctx = TestContext::new();
+    let mut coninfo = ctx.client.get_connection_info().clone();
+    coninfo.redis.fetch_client_id = true;
+    let client = redis::Client::open(coninfo).unwrap();
+    let mut con = client.get_connection().unwrap();
+    let client_id = con.client_id().unwrap();
+    assert_eq!(redis::Commands::client_id::<i64>(&mut con), Ok(client_id));
+    assert_eq!(client.unblock(client_id, UnblockKind::Timeout), Ok(false));
+
+    for &kind in &[UnblockKind::Timeout, UnblockKind::Error] {
+        let canceller = client.clone();
+        let start = Instant::now();
+        let unblocked = spawn(move || loop {
+            sleep(Duration::from_millis(50));
+            if canceller.unblock(client_id, kind).unwrap() {
+                break;
+            }
+        });
+        let popped = con.blpop::<_, Option<(String, String)>>("queue", 30);
+        unblocked.join().unwrap();
+        assert!(start.elapsed() < Duration::from_secs(5));
+        match kind {
+            UnblockKind::Timeout => assert_eq!(popped, Ok(None)),
+            UnblockKind::Error => assert_eq!(popped.unwrap_err().code(), Some("UNBLOCKED")),
+        }
+    }
+}
+
+#[test]
+fn test_key_access_info() {
+    use redis::config::MaxmemoryPolicy;
+    use redis::util::{key_access_info, AccessInfo, AccessInfoReader};
+
+    let ctx = TestContext::new();
+    let mut con = ctx.connection();
+    let () = con.set("key", 42).unwrap();
+
+    let () = con
+        .config_set("maxmemory-policy", MaxmemoryPolicy::AllkeysLru)
+        .unwrap();
+    let err = con.object_freq::<_, i32>("key").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WrongEvictionPolicy);
+    let mut reader = AccessInfoReader::new();
+    assert!(matches!(
+        reader.key_access_info(&mut con, "key"),
+        Ok(Some(AccessInfo::IdleTime(_)))
+    ));
+
+    let () = con
+        .config_set("maxmemory-policy", MaxmemoryPolicy::AllkeysLfu)
+        .unwrap();
+    let err = con.object_idletime::<_, i32>("key").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WrongEvictionPolicy);
+    // The reader still assumes LRU and has to notice the change.
+    assert!(matches!(
+        reader.key_access_info(&mut con, "key"),
+        Ok(Some(AccessInfo::Frequency(_)))
+    ));
+    assert!(matches!(
+        key_access_info(&mut con, "key"),
+        Ok(Some(AccessInfo::Frequency(_)))
+    ));
+    assert_eq!(key_access_info(&mut con, "missing"), Ok(None));
+}
+
+#[test]
+#[cfg(feature = "safe-encoding")]
+fn test_safe_encoding_rejects_binary_keys() {
+    let ctx = TestContext::new();
+    let mut con = ctx.connection();
+
+    let err = con.set_str::<_, ()>(b"\xff\xfekey", 42).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TypeError);
+    let err = con.get_str::<Option<i32>>(b"\xff\xfekey").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TypeError);
+
+    let () = con.set_str(b"key", 42).unwrap();
+    assert_eq!(con.get_str(b"key"), Ok(42));
+    assert_eq!(con.exists_str(b"key"), Ok(true));
+    assert_eq!(con.del_str(b"key"), Ok(1));
+}
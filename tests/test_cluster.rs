@@ -22,6 +22,24 @@ fn test_cluster_basics() {
     );
 }
 
+#[cfg(feature = "test-server")]
+#[test]
+fn test_cluster_of_test_servers() {
+    use redis::Commands;
+
+    let cluster = redis::testing::TestCluster::start(3).unwrap();
+    assert_eq!(cluster.servers().len(), 3);
+    let mut con = cluster.client().unwrap().get_connection().unwrap();
+
+    // The keys are spread over the slots of all three masters.
+    for i in 0..30 {
+        let () = con.set(format!("key{}", i), i).unwrap();
+    }
+    for i in 0..30 {
+        assert_eq!(con.get(format!("key{}", i)), Ok(i));
+    }
+}
+
 #[test]
 fn test_cluster_with_username_and_password() {
     let cluster = TestClusterContext::new_with_cluster_client_builder(3, 0, |builder| {
@@ -77,6 +95,46 @@ fn test_cluster_readonly() {
     );
 }
 
+#[test]
+fn test_cluster_write_with_replication() {
+    use redis::{Commands, ReplicationOutcome};
+
+    let cluster = TestClusterContext::new(6, 1);
+    let mut con = cluster.connection();
+
+    // The keys live on different masters, and each WAIT has to go to the
+    // master that served the write.
+    for key in &["{a}key", "{b}key", "{c}key"] {
+        let write = redis::Cmd::set(key, 42);
+        let ((), outcome) = con
+            .write_with_replication(&write, 1, std::time::Duration::from_secs(5))
+            .unwrap();
+        assert_eq!(outcome, ReplicationOutcome::Acknowledged(1));
+    }
+}
+
+#[test]
+fn test_cluster_store_with_ttl() {
+    use redis::Commands;
+
+    let cluster = TestClusterContext::new(3, 0);
+    let mut con = cluster.connection();
+
+    let () = con.sadd("{x}s1", &["a", "b", "c"]).unwrap();
+    let () = con.sadd("{x}s2", &["b", "c", "d"]).unwrap();
+    let count = con
+        .store_with_ttl(
+            &redis::Cmd::sunionstore(&["{x}dest"][..], &["{x}s1", "{x}s2"]),
+            "{x}dest",
+            std::time::Duration::from_secs(100),
+        )
+        .unwrap();
+    assert_eq!(count, 4);
+    assert_eq!(con.scard("{x}dest"), Ok(4));
+    let ttl: i64 = con.ttl("{x}dest").unwrap();
+    assert!(0 < ttl && ttl <= 100, "ttl is {}", ttl);
+}
+
 #[test]
 fn test_cluster_eval() {
     let cluster = TestClusterContext::new(3, 0);
@@ -116,6 +174,111 @@ fn test_cluster_script() {
     assert_eq!(rv, Ok(("1".to_string(), "2".to_string())));
 }
 
+#[test]
+fn test_cluster_mget_mset_across_slots() {
+    let cluster = TestClusterContext::new(3, 0);
+    cluster.wait_for_cluster_up();
+    let mut con = cluster.connection();
+
+    // `bar` and `key2` live on the first node, `key1` on the second and
+    // `foo`, `{foo}a` and `{foo}b` on the third one.
+    let pairs = [
+        ("foo", 1),
+        ("key1", 2),
+        ("bar", 3),
+        ("{foo}a", 4),
+        ("key2", 5),
+        ("{foo}b", 6),
+    ];
+    con.cluster_mset(&pairs).unwrap();
+
+    let values: Vec<Option<i32>> = con
+        .cluster_mget(&["{foo}b", "bar", "missing", "key1", "foo", "key2", "{foo}a"])
+        .unwrap();
+    assert_eq!(
+        values,
+        vec![Some(6), Some(3), None, Some(2), Some(1), Some(5), Some(4)]
+    );
+
+    let results: Vec<redis::RedisResult<Option<i32>>> =
+        con.cluster_mget_partial(&["foo", "missing", "key1"]);
+    assert_eq!(results, vec![Ok(Some(1)), Ok(None), Ok(Some(2))]);
+
+    let results = con.cluster_mset_partial(&[("foo", 7), ("key1", 8)]);
+    assert_eq!(results, vec![Ok(()), Ok(())]);
+    assert_eq!(
+        con.cluster_mget(&["key1", "foo"]),
+        Ok(vec![Some(8), Some(7)])
+    );
+}
+
+#[test]
+fn test_cluster_reshard() {
+    use redis::cluster::admin::{reshard, ReshardEvent};
+    use redis::Commands;
+
+    let cluster = TestClusterContext::new(3, 0);
+    cluster.wait_for_cluster_up();
+    let mut con = cluster.connection();
+
+    // All keys share the slot of `bar`, which lives on the first node.
+    let keys = ["{bar}1", "{bar}2", "{bar}3", "{bar}4", "{bar}5"];
+    for (i, key) in keys.iter().enumerate() {
+        let _: () = con.set(*key, i).unwrap();
+    }
+    let slot: u16 = redis::cmd("CLUSTER")
+        .arg("KEYSLOT")
+        .arg("bar")
+        .query(&mut con)
+        .unwrap();
+
+    let nodes: Vec<redis::ConnectionInfo> = cluster
+        .cluster
+        .iter_servers()
+        .map(|server| redis::ConnectionInfo {
+            addr: server.get_client_addr().clone(),
+            redis: Default::default(),
+            tcp_keepalive: None,
+        })
+        .collect();
+
+    let summary = reshard(nodes[0].clone(), nodes[1].clone(), &[slot], 2)
+        .unwrap()
+        .dry_run(true)
+        .run()
+        .unwrap();
+    assert_eq!((summary.slots, summary.keys), (1, 5));
+    let mut target = redis::Client::open(nodes[1].clone())
+        .unwrap()
+        .get_connection()
+        .unwrap();
+    assert_eq!(target.cluster_countkeysinslot(slot), Ok(0));
+
+    let mut events = Vec::new();
+    let summary = reshard(nodes[0].clone(), nodes[1].clone(), &[slot], 2)
+        .unwrap()
+        .on_progress(|event| events.push(event.clone()))
+        .run()
+        .unwrap();
+    assert_eq!((summary.slots, summary.keys), (1, 5));
+    assert_eq!(
+        events,
+        vec![
+            ReshardEvent::SlotStarted { slot },
+            ReshardEvent::KeysMigrated { slot, count: 2 },
+            ReshardEvent::KeysMigrated { slot, count: 2 },
+            ReshardEvent::KeysMigrated { slot, count: 1 },
+            ReshardEvent::SlotFinished { slot, keys: 5 },
+        ]
+    );
+    assert_eq!(target.cluster_countkeysinslot(slot), Ok(5));
+
+    let mut con = cluster.connection();
+    for (i, key) in keys.iter().enumerate() {
+        assert_eq!(con.get(*key), Ok(i));
+    }
+}
+
 #[test]
 fn test_cluster_pipeline() {
     let cluster = TestClusterContext::new(3, 0);
@@ -255,3 +418,182 @@ fn test_cluster_pipeline_ordering_with_improper_command() {
     let got = pipe.query::<Vec<String>>(&mut con).unwrap();
     assert_eq!(got, expected);
 }
+
+#[test]
+fn test_cluster_ask_redirect_during_migration() {
+    let cluster = TestClusterContext::new(3, 0);
+    cluster.wait_for_cluster_up();
+    let mut con = cluster.connection();
+
+    let key = "{migrating}key1";
+    let new_key = "{migrating}key2";
+    redis::cmd("SET").arg(key).arg("foo").execute(&mut con);
+
+    let mut nodes: Vec<redis::Connection> = cluster
+        .cluster
+        .iter_servers()
+        .map(|server| {
+            redis::Client::open(redis::ConnectionInfo {
+                addr: server.get_client_addr().clone(),
+                redis: Default::default(),
+                tcp_keepalive: None,
+            })
+            .unwrap()
+            .get_connection()
+            .unwrap()
+        })
+        .collect();
+    let ids: Vec<String> = nodes
+        .iter_mut()
+        .map(|node| redis::cmd("CLUSTER").arg("MYID").query(node).unwrap())
+        .collect();
+    let slot: u16 = redis::cmd("CLUSTER")
+        .arg("KEYSLOT")
+        .arg(key)
+        .query(&mut nodes[0])
+        .unwrap();
+    // Only the owner of the slot answers without a MOVED redirect
+    let source = nodes
+        .iter_mut()
+        .position(|node| {
+            redis::cmd("GET")
+                .arg(key)
+                .query::<Option<String>>(node)
+                .is_ok()
+        })
+        .unwrap();
+    let target = (source + 1) % nodes.len();
+    let (target_host, target_port) = match cluster.cluster.servers[target].get_client_addr() {
+        redis::ConnectionAddr::Tcp(host, port) => (host.clone(), *port),
+        redis::ConnectionAddr::TcpTls { host, port, .. } => (host.clone(), *port),
+        _ => unreachable!(),
+    };
+
+    redis::cmd("CLUSTER")
+        .arg("SETSLOT")
+        .arg(slot)
+        .arg("IMPORTING")
+        .arg(&ids[source])
+        .execute(&mut nodes[target]);
+    redis::cmd("CLUSTER")
+        .arg("SETSLOT")
+        .arg(slot)
+        .arg("MIGRATING")
+        .arg(&ids[target])
+        .execute(&mut nodes[source]);
+
+    // Keys which were not moved yet are still served by the source node, new keys are
+    // created on the target node after an ASK redirect.
+    assert_eq!(
+        redis::cmd("GET").arg(key).query(&mut con),
+        Ok("foo".to_string())
+    );
+    redis::cmd("SET").arg(new_key).arg("bar").execute(&mut con);
+    assert_eq!(
+        redis::cmd("GET").arg(new_key).query(&mut con),
+        Ok("bar".to_string())
+    );
+
+    redis::cmd("MIGRATE")
+        .arg(target_host)
+        .arg(target_port)
+        .arg("")
+        .arg(0)
+        .arg(5000)
+        .arg("KEYS")
+        .arg(key)
+        .execute(&mut nodes[source]);
+
+    assert_eq!(
+        redis::cmd("GET").arg(key).query(&mut con),
+        Ok("foo".to_string())
+    );
+    assert_eq!(
+        cluster_pipe()
+            .get(key)
+            .set(new_key, "baz")
+            .ignore()
+            .get(new_key)
+            .query(&mut con),
+        Ok(("foo".to_string(), "baz".to_string()))
+    );
+
+    // The target has to learn about the new owner first
+    let others = (0..nodes.len()).filter(|idx| *idx != target);
+    for idx in std::iter::once(target).chain(others) {
+        redis::cmd("CLUSTER")
+            .arg("SETSLOT")
+            .arg(slot)
+            .arg("NODE")
+            .arg(&ids[target])
+            .execute(&mut nodes[idx]);
+    }
+
+    // The migration is done, the client follows the MOVED redirect from now on
+    assert_eq!(
+        redis::cmd("MGET").arg(&[key, new_key]).query(&mut con),
+        Ok(("foo".to_string(), "baz".to_string()))
+    );
+}
+
+#[test]
+fn test_cluster_circuit_breaker() {
+    use std::sync::{Arc, Mutex};
+    use std::thread::sleep;
+    use std::time::Duration;
+
+    use redis::cluster::{key_slot, BreakerState};
+    use redis::{Commands, ErrorKind};
+
+    let events = Arc::new(Mutex::new(vec![]));
+    let sink = events.clone();
+    let mut cluster = TestClusterContext::new_with_cluster_client_builder(3, 0, |builder| {
+        builder
+            .circuit_breaker(2, Duration::from_millis(500))
+            .breaker_hook(move |node, state| sink.lock().unwrap().push((node.to_string(), state)))
+    });
+    cluster.wait_for_cluster_up();
+    let mut con = cluster.connection();
+
+    // The first node serves the lowest slots, the others are not affected.
+    let key = (0..)
+        .map(|i| format!("key{}", i))
+        .find(|key| key_slot(key.as_bytes()) < 5000)
+        .unwrap();
+    let other = (0..)
+        .map(|i| format!("other{}", i))
+        .find(|key| key_slot(key.as_bytes()) > 6000)
+        .unwrap();
+    let () = con.set(&key, 1).unwrap();
+
+    cluster.cluster.stop_server(0);
+    assert!(con.get::<_, i32>(&key).is_err());
+    let node = match events.lock().unwrap().first() {
+        Some((node, BreakerState::Open)) => node.clone(),
+        event => panic!("unexpected breaker event {:?}", event),
+    };
+    assert_eq!(con.breaker_state(&node), BreakerState::Open);
+
+    // Requests for the node fail fast while the breaker is open.
+    let err = con.get::<_, i32>(&key).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::CircuitOpen);
+    assert_eq!(con.set(&other, 2), Ok(()));
+
+    cluster.cluster.restart_server(0);
+    let mut recovered = false;
+    for _ in 0..50 {
+        sleep(Duration::from_millis(100));
+        if con.set::<_, _, ()>(&key, 3).is_ok() {
+            recovered = true;
+            break;
+        }
+    }
+    assert!(recovered);
+    assert_eq!(con.get(&key), Ok(3));
+    assert_eq!(con.breaker_state(&node), BreakerState::Closed);
+    // A probe may fail while the node is still starting up, which opens the
+    // breaker once more.
+    let events = events.lock().unwrap();
+    assert_eq!(events[1], (node.clone(), BreakerState::HalfOpen));
+    assert_eq!(events.last(), Some(&(node, BreakerState::Closed)));
+}
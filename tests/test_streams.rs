@@ -39,6 +39,7 @@ fn xadd_keyrange(con: &mut Connection, key: &str, start: i32, end: i32) {
 fn test_cmd_options() {
     // Tests the following command option builders....
     // xclaim_options
+    // xautoclaim_options
     // xread_options
     // maxlen enum
 
@@ -69,6 +70,12 @@ fn test_cmd_options() {
         "JUSTID"
     );
 
+    let empty = StreamAutoClaimOptions::default();
+    assert_eq!(ToRedisArgs::to_redis_args(&empty).len(), 0);
+
+    let opts = StreamAutoClaimOptions::default().count(25).with_justid();
+    assert_args!(&opts, "COUNT", "25", "JUSTID");
+
     // test maxlen options
 
     assert_args!(StreamMaxlen::Approx(10), "MAXLEN", "~", "10");
@@ -166,6 +173,73 @@ fn test_assorted_1() {
     assert_eq!(result, Ok(10));
 }
 
+#[test]
+fn test_xinfo_stream_reply() {
+    use redis::{FromRedisValue, Value};
+
+    fn data(item: &str) -> Value {
+        Value::Data(item.as_bytes().to_vec())
+    }
+
+    // As returned by Redis 7 for a stream with two entries.
+    let reply = Value::Bulk(vec![
+        data("length"),
+        Value::Int(2),
+        data("radix-tree-keys"),
+        Value::Int(1),
+        data("radix-tree-nodes"),
+        Value::Int(2),
+        data("last-generated-id"),
+        data("1000-1"),
+        data("max-deleted-entry-id"),
+        data("0-0"),
+        data("entries-added"),
+        Value::Int(2),
+        data("recorded-first-entry-id"),
+        data("1000-0"),
+        data("groups"),
+        Value::Int(0),
+        data("first-entry"),
+        Value::Bulk(vec![
+            data("1000-0"),
+            Value::Bulk(vec![data("hello"), data("world")]),
+        ]),
+        data("last-entry"),
+        Value::Bulk(vec![
+            data("1000-1"),
+            Value::Bulk(vec![data("hello"), data("world2")]),
+        ]),
+    ]);
+
+    let reply = StreamInfoStreamReply::from_redis_value(&reply).unwrap();
+    assert_eq!(reply.length, 2);
+    assert_eq!(reply.radix_tree_keys, 1);
+    assert_eq!(reply.radix_tree_nodes, 2);
+    assert_eq!(reply.last_generated_id, "1000-1");
+    assert_eq!(reply.max_deleted_entry_id.as_deref(), Some("0-0"));
+    assert_eq!(reply.entries_added, Some(2));
+    assert_eq!(reply.recorded_first_entry_id.as_deref(), Some("1000-0"));
+    assert_eq!(reply.groups, 0);
+    assert_eq!(reply.first_entry.id, "1000-0");
+    assert_eq!(reply.first_entry.get("hello"), Some("world".to_string()));
+    assert_eq!(reply.last_entry.id, "1000-1");
+    assert_eq!(reply.last_entry.get("hello"), Some("world2".to_string()));
+
+    // Empty streams have nil entries and older servers lack the new fields.
+    let reply = Value::Bulk(vec![
+        data("length"),
+        Value::Int(0),
+        data("first-entry"),
+        Value::Nil,
+        data("last-entry"),
+        Value::Nil,
+    ]);
+    let reply = StreamInfoStreamReply::from_redis_value(&reply).unwrap();
+    assert_eq!(reply.length, 0);
+    assert_eq!(reply.first_entry.id, "");
+    assert_eq!(reply.entries_added, None);
+}
+
 #[test]
 fn test_xgroup_create() {
     // Tests the following commands....
@@ -466,6 +540,163 @@ fn test_xclaim() {
     assert_eq!(claimed.len(), 10);
 }
 
+#[test]
+fn test_xautoclaim() {
+    // Tests the following commands....
+    // xautoclaim
+    // xautoclaim_options
+    let ctx = TestContext::new();
+    let mut con = ctx.connection();
+
+    let result: RedisResult<String> = con.xgroup_create_mkstream("k1", "g1", "$");
+    assert!(result.is_ok());
+    xadd_keyrange(&mut con, "k1", 0, 10);
+
+    // check out all messages with c1 without acking them
+    let reply: StreamReadReply = con
+        .xread_options(
+            &["k1"],
+            &[">"],
+            &StreamReadOptions::default().group("g1", "c1"),
+        )
+        .unwrap();
+    let ids: Vec<String> = reply.keys[0].ids.iter().map(|msg| msg.id.clone()).collect();
+
+    // sleep for 5ms
+    sleep(Duration::from_millis(5));
+
+    // claim the first 4 messages idle for > 4ms
+    let reply: StreamAutoClaimReply = con
+        .xautoclaim_options(
+            "k1",
+            "g1",
+            "c2",
+            4,
+            "0-0",
+            StreamAutoClaimOptions::default().count(4),
+        )
+        .unwrap();
+    assert_eq!(
+        reply.claimed.iter().map(|msg| &msg.id).collect::<Vec<_>>(),
+        ids[..4].iter().collect::<Vec<_>>()
+    );
+    assert!(reply.claimed[0].contains_key(&"h"));
+    assert_eq!(reply.next_stream_id, ids[4]);
+
+    // continue from where the last call stopped, only returning the ids
+    let reply: StreamAutoClaimReply = con
+        .xautoclaim_options(
+            "k1",
+            "g1",
+            "c3",
+            4,
+            &reply.next_stream_id,
+            StreamAutoClaimOptions::default().with_justid(),
+        )
+        .unwrap();
+    assert_eq!(
+        reply.claimed.iter().map(|msg| &msg.id).collect::<Vec<_>>(),
+        ids[4..].iter().collect::<Vec<_>>()
+    );
+    assert!(reply.claimed[0].map.is_empty());
+    assert_eq!(reply.next_stream_id, "0-0");
+
+    // nothing is idle long enough anymore
+    let reply: StreamAutoClaimReply = con.xautoclaim("k1", "g1", "c4", 60_000, "0-0").unwrap();
+    assert!(reply.claimed.is_empty());
+}
+
+#[test]
+fn test_xautoclaim_reply_shapes() {
+    use redis::{FromRedisValue, Value};
+
+    let entry = |id: &str| {
+        Value::Bulk(vec![
+            Value::Data(id.into()),
+            Value::Bulk(vec![Value::Data("h".into()), Value::Data("w".into())]),
+        ])
+    };
+
+    // Redis 6.2 has no list of deleted ids and reports them as nil entries.
+    let reply = StreamAutoClaimReply::from_redis_value(&Value::Bulk(vec![
+        Value::Data("0-0".into()),
+        Value::Bulk(vec![entry("1-0"), Value::Nil, entry("3-0")]),
+    ]))
+    .unwrap();
+    assert_eq!(reply.next_stream_id, "0-0");
+    assert_eq!(reply.claimed.len(), 2);
+    assert_eq!(reply.claimed[1].id, "3-0");
+    assert_eq!(reply.claimed[1].get("h"), Some("w".to_string()));
+    assert!(reply.deleted_ids.is_empty());
+
+    // Redis 7.0 with JUSTID.
+    let reply = StreamAutoClaimReply::from_redis_value(&Value::Bulk(vec![
+        Value::Data("4-0".into()),
+        Value::Bulk(vec![Value::Data("1-0".into())]),
+        Value::Bulk(vec![Value::Data("2-0".into())]),
+    ]))
+    .unwrap();
+    assert_eq!(reply.next_stream_id, "4-0");
+    assert_eq!(reply.claimed[0].id, "1-0");
+    assert!(reply.claimed[0].map.is_empty());
+    assert_eq!(reply.deleted_ids, vec!["2-0".to_string()]);
+
+    // Redis 7.0 with the full entries.
+    let reply = StreamAutoClaimReply::from_redis_value(&Value::Bulk(vec![
+        Value::Data("0-0".into()),
+        Value::Bulk(vec![entry("1-0")]),
+        Value::Bulk(vec![Value::Data("2-0".into()), Value::Data("3-0".into())]),
+    ]))
+    .unwrap();
+    assert_eq!(reply.next_stream_id, "0-0");
+    assert_eq!(reply.claimed[0].get("h"), Some("w".to_string()));
+    assert_eq!(
+        reply.deleted_ids,
+        vec!["2-0".to_string(), "3-0".to_string()]
+    );
+}
+
+#[test]
+fn test_xautoclaim_all() {
+    let ctx = TestContext::new();
+    let mut con = ctx.connection();
+
+    let result: RedisResult<String> = con.xgroup_create_mkstream("k1", "g1", "$");
+    assert!(result.is_ok());
+    xadd_keyrange(&mut con, "k1", 0, 10);
+    let reply: StreamReadReply = con
+        .xread_options(
+            &["k1"],
+            &[">"],
+            &StreamReadOptions::default().group("g1", "c1"),
+        )
+        .unwrap();
+    let ids: Vec<String> = reply.keys[0].ids.iter().map(|msg| msg.id.clone()).collect();
+
+    // A pending message that was deleted is dropped, and listed as deleted
+    // since Redis 7.0.
+    let () = con.xdel("k1", &[&ids[0]]).unwrap();
+    sleep(Duration::from_millis(5));
+
+    // The batches of three entries are claimed until the whole pending
+    // entries list was scanned.
+    let reply = xautoclaim_all(
+        &mut con,
+        "k1",
+        "g1",
+        "c2",
+        4,
+        StreamAutoClaimOptions::default().count(3),
+    )
+    .unwrap();
+    assert_eq!(
+        reply.claimed.iter().map(|msg| &msg.id).collect::<Vec<_>>(),
+        ids[1..].iter().collect::<Vec<_>>()
+    );
+    assert!(reply.deleted_ids.is_empty() || reply.deleted_ids == ids[..1]);
+    assert_eq!(reply.next_stream_id, "0-0");
+}
+
 #[test]
 fn test_xdel() {
     // Tests the following commands....
@@ -19,6 +19,26 @@ fn test_is_single_arg() {
     assert!(!twobytesvec.is_single_arg());
 }
 
+#[test]
+#[cfg(feature = "geospatial")]
+fn test_borrowed_args() {
+    use redis::geo::Unit;
+    use redis::{NumericBehavior, ToRedisArgs};
+
+    let units: &[&Unit] = &[&Unit::Kilometers, &Unit::Miles];
+    assert_eq!(units.to_redis_args(), vec![b"km".to_vec(), b"mi".to_vec()]);
+    assert!(!units.is_single_arg());
+    assert!((&[&Unit::Feet][..]).is_single_arg());
+
+    let keys = vec!["a", "b"];
+    assert!(!ToRedisArgs::is_single_arg(&&keys));
+    assert!(!(&[&keys][..]).is_single_arg());
+    assert_eq!(
+        ToRedisArgs::describe_numeric_behavior(&&1.5f64),
+        NumericBehavior::NumberIsFloat
+    );
+}
+
 #[test]
 fn test_info_dict() {
     use redis::{FromRedisValue, InfoDict, Value};
@@ -33,6 +53,168 @@ fn test_info_dict() {
     assert_eq!(d.get::<String>("key3"), None);
 }
 
+#[test]
+fn test_role() {
+    use redis::{FromRedisValue, ReplicaInfo, Role, Value};
+
+    let role: Role = FromRedisValue::from_redis_value(&Value::Bulk(vec![
+        Value::Data("master".into()),
+        Value::Int(3129659),
+        Value::Bulk(vec![
+            Value::Bulk(vec![
+                Value::Data("127.0.0.1".into()),
+                Value::Data("9001".into()),
+                Value::Data("3129242".into()),
+            ]),
+            Value::Bulk(vec![
+                Value::Data("127.0.0.1".into()),
+                Value::Data("9002".into()),
+                Value::Data("3129543".into()),
+            ]),
+        ]),
+    ]))
+    .unwrap();
+    assert_eq!(
+        role,
+        Role::Master {
+            replication_offset: 3129659,
+            replicas: vec![
+                ReplicaInfo {
+                    host: "127.0.0.1".into(),
+                    port: 9001,
+                    offset: 3129242,
+                },
+                ReplicaInfo {
+                    host: "127.0.0.1".into(),
+                    port: 9002,
+                    offset: 3129543,
+                },
+            ],
+        }
+    );
+
+    let role: Role = FromRedisValue::from_redis_value(&Value::Bulk(vec![
+        Value::Data("slave".into()),
+        Value::Data("127.0.0.1".into()),
+        Value::Int(9000),
+        Value::Data("connected".into()),
+        Value::Int(3167038),
+    ]))
+    .unwrap();
+    assert_eq!(
+        role,
+        Role::Replica {
+            master_host: "127.0.0.1".into(),
+            master_port: 9000,
+            state: "connected".into(),
+            offset: 3167038,
+        }
+    );
+
+    let role: Role = FromRedisValue::from_redis_value(&Value::Bulk(vec![
+        Value::Data("sentinel".into()),
+        Value::Bulk(vec![
+            Value::Data("resque-master".into()),
+            Value::Data("html-fragments-master".into()),
+        ]),
+    ]))
+    .unwrap();
+    assert_eq!(
+        role,
+        Role::Sentinel {
+            master_names: vec!["resque-master".into(), "html-fragments-master".into()],
+        }
+    );
+
+    let bad: Result<Role, _> =
+        FromRedisValue::from_redis_value(&Value::Bulk(vec![Value::Data("master".into())]));
+    assert!(bad.is_err());
+}
+
+#[test]
+fn test_expiration_unit_selection() {
+    use redis::{Expiration, Expiry, ToRedisArgs};
+    use std::time::{Duration, UNIX_EPOCH};
+
+    fn args(expiration: Expiration) -> Vec<String> {
+        expiration
+            .to_redis_args()
+            .into_iter()
+            .map(|arg| String::from_utf8(arg).unwrap())
+            .collect()
+    }
+
+    let after = |ms| Expiration::In(Duration::from_millis(ms));
+    assert_eq!(args(after(999)), vec!["PX", "999"]);
+    assert_eq!(args(after(1000)), vec!["EX", "1"]);
+    assert_eq!(args(after(1001)), vec!["PX", "1001"]);
+    assert_eq!(args(after(60_000)), vec!["EX", "60"]);
+
+    // Precision below a millisecond rounds up.
+    let after_nanos = |ns| Expiration::In(Duration::from_nanos(ns));
+    assert_eq!(args(after_nanos(999_999_999)), vec!["EX", "1"]);
+    assert_eq!(args(after_nanos(1_000_000_000)), vec!["EX", "1"]);
+    assert_eq!(args(after_nanos(1_000_000_001)), vec!["PX", "1001"]);
+    assert_eq!(args(after_nanos(1)), vec!["PX", "1"]);
+
+    // The server rejects an expiry of zero.
+    assert_eq!(args(after(0)), vec!["PX", "1"]);
+
+    let at = |ms| Expiration::At(UNIX_EPOCH + Duration::from_millis(ms));
+    assert_eq!(args(at(1_650_000_000_000)), vec!["EXAT", "1650000000"]);
+    assert_eq!(args(at(1_650_000_000_001)), vec!["PXAT", "1650000000001"]);
+    assert_eq!(args(at(0)), vec!["PXAT", "1"]);
+    assert_eq!(
+        args(Expiration::At(UNIX_EPOCH - Duration::from_secs(60))),
+        vec!["PXAT", "1"]
+    );
+
+    assert_eq!(args(Expiration::Persist), vec!["PERSIST"]);
+    assert_eq!(args(Expiration::Keep), vec!["KEEPTTL"]);
+
+    assert_eq!(Expiration::from(Expiry::PX(2000)), after(2000));
+    assert_eq!(args(Expiry::PX(2000).into()), vec!["EX", "2"]);
+    assert_eq!(
+        Expiration::from(Expiry::EXAT(1_650_000_000)),
+        at(1_650_000_000_000)
+    );
+}
+
+#[test]
+fn test_system_time() {
+    use redis::{FromRedisValue, Value};
+    use std::time::{Duration, SystemTime, UNIX_EPOCH};
+
+    let time: SystemTime = FromRedisValue::from_redis_value(&Value::Bulk(vec![
+        Value::Data("1650000000".into()),
+        Value::Data("123456".into()),
+    ]))
+    .unwrap();
+    assert_eq!(time, UNIX_EPOCH + Duration::new(1_650_000_000, 123_456_000));
+
+    let bad: Result<SystemTime, _> = FromRedisValue::from_redis_value(&Value::Bulk(vec![
+        Value::Data("1650000000".into()),
+        Value::Data("1000000".into()),
+    ]));
+    assert!(bad.is_err());
+
+    let bad: Result<SystemTime, _> = FromRedisValue::from_redis_value(&Value::Int(1_650_000_000));
+    assert!(bad.is_err());
+}
+
+#[test]
+fn test_pattern_escaping() {
+    use redis::Pattern;
+
+    assert_eq!(Pattern::literal("plain:key").as_str(), "plain:key");
+    assert_eq!(
+        Pattern::literal(r"a*b?c[d]e\f").as_str(),
+        r"a\*b\?c\[d\]e\\f"
+    );
+    assert_eq!(Pattern::prefix("user:*:").as_str(), r"user:\*:*");
+    assert_eq!(Pattern::raw("user:[ab]*").as_str(), "user:[ab]*");
+}
+
 #[test]
 fn test_i32() {
     use redis::{ErrorKind, FromRedisValue, Value};
@@ -74,6 +256,24 @@ fn test_vec() {
     assert_eq!(v, Ok(vec![1i32, 2, 3]));
 }
 
+#[test]
+fn test_optional_vec() {
+    use redis::{FromRedisValue, Value};
+
+    let v: Vec<i32> = FromRedisValue::from_redis_value(&Value::Nil).unwrap();
+    assert_eq!(v, vec![]);
+    let v: Vec<i32> = FromRedisValue::from_redis_value(&Value::Bulk(vec![])).unwrap();
+    assert_eq!(v, vec![]);
+
+    let v: Option<Vec<i32>> = FromRedisValue::from_redis_value(&Value::Nil).unwrap();
+    assert_eq!(v, None);
+    let v: Option<Vec<i32>> = FromRedisValue::from_redis_value(&Value::Bulk(vec![])).unwrap();
+    assert_eq!(v, Some(vec![]));
+    let v: Option<Vec<i32>> =
+        FromRedisValue::from_redis_value(&Value::Bulk(vec![Value::Data("1".into())])).unwrap();
+    assert_eq!(v, Some(vec![1]));
+}
+
 #[test]
 fn test_tuple() {
     use redis::{FromRedisValue, Value};
@@ -198,6 +398,7 @@ fn test_types_to_redis_args() {
     use redis::ToRedisArgs;
     use std::collections::BTreeMap;
     use std::collections::BTreeSet;
+    use std::collections::HashMap;
     use std::collections::HashSet;
 
     assert!(!5i32.to_redis_args().is_empty());
@@ -226,4 +427,608 @@ fn test_types_to_redis_args() {
         .collect::<BTreeMap<_, _>>()
         .to_redis_args()
         .is_empty());
+
+    // maps are flattened into field/value pairs, in key order for BTreeMap
+    let map: BTreeMap<_, _> = [("b", 6), ("a", 5)].iter().cloned().collect();
+    assert_eq!(
+        map.to_redis_args(),
+        vec![b"a".to_vec(), b"5".to_vec(), b"b".to_vec(), b"6".to_vec()]
+    );
+
+    let map: HashMap<_, _> = [("a", 5)].iter().cloned().collect();
+    assert_eq!(map.to_redis_args(), vec![b"a".to_vec(), b"5".to_vec()]);
+}
+
+#[test]
+fn test_pubsub_msg_kinds() {
+    use redis::{Msg, SubscriptionKind, Value};
+
+    fn frame(items: &[&str]) -> Value {
+        Value::Bulk(
+            items
+                .iter()
+                .map(|item| Value::Data(item.as_bytes().to_vec()))
+                .collect(),
+        )
+    }
+    fn data(item: &str) -> Value {
+        Value::Data(item.as_bytes().to_vec())
+    }
+
+    let msg = Msg::from(frame(&["message", "foo", "42"]));
+    assert_eq!(
+        msg,
+        Msg::Message {
+            channel: data("foo"),
+            payload: data("42"),
+        }
+    );
+    assert_eq!(msg.get_channel_name(), "foo");
+    assert_eq!(msg.get_payload(), Ok(42));
+    assert_eq!(msg.get_payload_str(), Some("42"));
+    assert_eq!(msg.get_payload_bytes(), b"42");
+    assert!(!msg.from_pattern());
+    assert_eq!(msg.get_pattern(), Ok(None::<String>));
+
+    let msg = Msg::from(frame(&["pmessage", "f*", "foo", "bar"]));
+    assert_eq!(
+        msg,
+        Msg::PMessage {
+            pattern: data("f*"),
+            channel: data("foo"),
+            payload: data("bar"),
+        }
+    );
+    assert!(msg.from_pattern());
+    assert_eq!(msg.get_pattern(), Ok("f*".to_string()));
+
+    let msg = Msg::from(frame(&["smessage", "foo", "bar"]));
+    assert_eq!(
+        msg,
+        Msg::SMessage {
+            channel: data("foo"),
+            payload: data("bar"),
+        }
+    );
+
+    for (kind, expected) in [
+        ("subscribe", SubscriptionKind::Channel),
+        ("psubscribe", SubscriptionKind::Pattern),
+        ("ssubscribe", SubscriptionKind::Shard),
+    ]
+    .iter()
+    {
+        let value = Value::Bulk(vec![data(kind), data("foo"), Value::Int(2)]);
+        assert_eq!(Msg::from_value(&value), None);
+        let msg = Msg::from(value);
+        assert_eq!(
+            msg,
+            Msg::Subscribed {
+                kind: *expected,
+                channel: data("foo"),
+                count: 2,
+            }
+        );
+        assert_eq!(msg.get_payload_str(), None);
+        assert_eq!(msg.get_payload_bytes(), b"");
+        assert!(msg.get_payload::<String>().is_err());
+    }
+
+    for (kind, expected) in [
+        ("unsubscribe", SubscriptionKind::Channel),
+        ("punsubscribe", SubscriptionKind::Pattern),
+        ("sunsubscribe", SubscriptionKind::Shard),
+    ]
+    .iter()
+    {
+        let msg = Msg::from(Value::Bulk(vec![data(kind), Value::Nil, Value::Int(0)]));
+        assert_eq!(
+            msg,
+            Msg::Unsubscribed {
+                kind: *expected,
+                channel: Value::Nil,
+                count: 0,
+            }
+        );
+        assert_eq!(msg.get_channel(), Ok(None::<String>));
+    }
+
+    let pong = frame(&["pong", ""]);
+    assert_eq!(Msg::from(pong.clone()), Msg::Other(pong));
+    assert_eq!(Msg::from(Value::Okay), Msg::Other(Value::Okay));
+    assert_eq!(Msg::from(Value::Okay).get_channel_name(), "?");
+
+    assert_eq!(
+        Msg::from_value(&frame(&["message", "foo", "42"])),
+        Some(Msg::Message {
+            channel: data("foo"),
+            payload: data("42"),
+        })
+    );
+}
+
+#[test]
+fn test_latency_histogram() {
+    use redis::{CommandLatency, FromRedisValue, LatencyHistogram, Value};
+
+    fn data(s: &str) -> Value {
+        Value::Data(s.as_bytes().to_vec())
+    }
+
+    // Captured from `LATENCY HISTOGRAM set client|id` on Redis 7.0.
+    let reply = Value::Bulk(vec![
+        data("set"),
+        Value::Bulk(vec![
+            data("calls"),
+            Value::Int(100000),
+            data("histogram_usec"),
+            Value::Bulk(vec![
+                Value::Int(1),
+                Value::Int(99583),
+                Value::Int(2),
+                Value::Int(99852),
+                Value::Int(4),
+                Value::Int(99914),
+                Value::Int(8),
+                Value::Int(99940),
+                Value::Int(16),
+                Value::Int(100000),
+            ]),
+        ]),
+        data("client|id"),
+        Value::Bulk(vec![
+            data("calls"),
+            Value::Int(1),
+            data("histogram_usec"),
+            Value::Bulk(vec![Value::Int(2), Value::Int(1)]),
+        ]),
+    ]);
+
+    let histogram = LatencyHistogram::from_redis_value(&reply).unwrap();
+    assert_eq!(histogram.commands.len(), 2);
+    assert_eq!(
+        histogram.commands["set"],
+        CommandLatency {
+            calls: 100000,
+            buckets: vec![(1, 99583), (2, 99852), (4, 99914), (8, 99940), (16, 100000),],
+        }
+    );
+    assert_eq!(
+        histogram.commands["client|id"],
+        CommandLatency {
+            calls: 1,
+            buckets: vec![(2, 1)],
+        }
+    );
+
+    let empty = LatencyHistogram::from_redis_value(&Value::Bulk(vec![])).unwrap();
+    assert!(empty.commands.is_empty());
+
+    assert!(LatencyHistogram::from_redis_value(&Value::Bulk(vec![data("set")])).is_err());
+    assert!(LatencyHistogram::from_redis_value(&Value::Okay).is_err());
+}
+
+#[test]
+#[cfg(feature = "chrono")]
+fn test_chrono_args() {
+    use chrono::{TimeZone, Utc};
+    use redis::ToRedisArgs;
+
+    let time = Utc.timestamp_opt(1_700_000_000, 999_000_000).unwrap();
+    assert_eq!(time.to_redis_args(), vec![b"1700000000".to_vec()]);
+}
+
+#[test]
+#[cfg(feature = "uuid")]
+fn test_uuid_args() {
+    use redis::ToRedisArgs;
+
+    let id = uuid::Uuid::from_u128(0x67e5_5044_10b1_426f_9247_bb68_0e5f_e0c8);
+    assert_eq!(
+        id.to_redis_args(),
+        vec![b"67e55044-10b1-426f-9247-bb680e5fe0c8".to_vec()]
+    );
+}
+
+#[test]
+fn test_bool_args() {
+    use redis::ToRedisArgs;
+
+    assert_eq!(true.to_redis_args(), vec![b"1".to_vec()]);
+    assert_eq!(false.to_redis_args(), vec![b"0".to_vec()]);
+}
+
+#[test]
+fn test_encoding() {
+    use redis::{Encoding, FromRedisValue, Value};
+
+    assert_eq!(
+        Encoding::from_redis_value(&Value::Status("listpack".into())),
+        Ok(Encoding::Listpack)
+    );
+    assert_eq!(
+        Encoding::from_redis_value(&Value::Data(b"embstr".to_vec())),
+        Ok(Encoding::Embstr)
+    );
+    assert_eq!(
+        Encoding::from_redis_value(&Value::Data(b"listpackex".to_vec())),
+        Ok(Encoding::Other("listpackex".to_string()))
+    );
+    assert!(Encoding::from_redis_value(&Value::Nil).is_err());
+}
+
+#[test]
+fn test_dump_payload() {
+    use redis::{DumpPayload, ErrorKind, FromRedisValue, RedisResult, ToRedisArgs, Value};
+
+    // `DUMP` of the integer 10, as written by Redis 5.
+    let bytes = b"\x00\xc0\n\t\x00\xbem\x06\x89Z(\x00\n".to_vec();
+    let payload: DumpPayload =
+        FromRedisValue::from_redis_value(&Value::Data(bytes.clone())).unwrap();
+    assert_eq!(payload.rdb_version(), Some(9));
+    assert!(payload.validate().is_ok());
+    assert_eq!(payload.to_redis_args(), vec![bytes.clone()]);
+
+    for idx in 0..bytes.len() {
+        for bit in 0..8 {
+            let mut corrupted = bytes.clone();
+            corrupted[idx] ^= 1 << bit;
+            let err = DumpPayload(corrupted).validate().unwrap_err();
+            assert_eq!(
+                err.kind(),
+                ErrorKind::ClientError,
+                "byte {} bit {}",
+                idx,
+                bit
+            );
+        }
+    }
+
+    for len in 0..bytes.len() {
+        assert!(DumpPayload(bytes[..len].to_vec()).validate().is_err());
+    }
+    assert_eq!(DumpPayload(vec![0; 9]).rdb_version(), None);
+
+    let v: RedisResult<DumpPayload> = FromRedisValue::from_redis_value(&Value::Int(1));
+    assert_eq!(v.unwrap_err().kind(), ErrorKind::TypeError);
+}
+
+#[test]
+fn test_latency_events() {
+    use redis::{FromRedisValue, LatencyEvent, LatencyLatestEntry, ToRedisArgs, Value};
+
+    let events = [
+        (LatencyEvent::Command, "command"),
+        (LatencyEvent::FastCommand, "fast-command"),
+        (LatencyEvent::Fork, "fork"),
+        (LatencyEvent::RdbUnlinkTempFile, "rdb-unlink-temp-file"),
+        (LatencyEvent::AofWrite, "aof-write"),
+        (LatencyEvent::AofWriteAlone, "aof-write-alone"),
+        (LatencyEvent::AofWriteActiveChild, "aof-write-active-child"),
+        (
+            LatencyEvent::AofWritePendingFsync,
+            "aof-write-pending-fsync",
+        ),
+        (LatencyEvent::AofFsyncAlways, "aof-fsync-always"),
+        (LatencyEvent::AofFstat, "aof-fstat"),
+        (LatencyEvent::AofRename, "aof-rename"),
+        (LatencyEvent::AofRewriteDiffWrite, "aof-rewrite-diff-write"),
+        (LatencyEvent::ExpireCycle, "expire-cycle"),
+        (LatencyEvent::EvictionCycle, "eviction-cycle"),
+        (LatencyEvent::EvictionDel, "eviction-del"),
+        (LatencyEvent::ActiveDefragCycle, "active-defrag-cycle"),
+        (LatencyEvent::WhileBlockedCron, "while-blocked-cron"),
+        (
+            LatencyEvent::Other("module-acquire-gil".into()),
+            "module-acquire-gil",
+        ),
+    ];
+    for (event, name) in &events {
+        assert_eq!(event.to_redis_args(), vec![name.as_bytes().to_vec()]);
+        let parsed: LatencyEvent =
+            FromRedisValue::from_redis_value(&Value::Data(name.as_bytes().to_vec())).unwrap();
+        assert_eq!(&parsed, event);
+    }
+
+    // As returned by `LATENCY LATEST` after `DEBUG SLEEP` and a slow fork.
+    let reply = Value::Bulk(vec![
+        Value::Bulk(vec![
+            Value::Data("command".into()),
+            Value::Int(1405067976),
+            Value::Int(251),
+            Value::Int(1001),
+        ]),
+        Value::Bulk(vec![
+            Value::Data("fork".into()),
+            Value::Int(1405067822),
+            Value::Int(184),
+            Value::Int(204),
+        ]),
+    ]);
+    let entries: Vec<LatencyLatestEntry> = FromRedisValue::from_redis_value(&reply).unwrap();
+    assert_eq!(
+        entries,
+        vec![
+            LatencyLatestEntry {
+                event: LatencyEvent::Command,
+                timestamp: 1405067976,
+                latest_ms: 251,
+                max_ms: 1001,
+            },
+            LatencyLatestEntry {
+                event: LatencyEvent::Fork,
+                timestamp: 1405067822,
+                latest_ms: 184,
+                max_ms: 204,
+            },
+        ]
+    );
+
+    let short = Value::Bulk(vec![Value::Data("fork".into()), Value::Int(1)]);
+    let entry: redis::RedisResult<LatencyLatestEntry> = FromRedisValue::from_redis_value(&short);
+    assert!(entry.is_err());
+}
+
+#[test]
+fn test_redis_write_defaults() {
+    use redis::{RedisWrite, ToRedisArgs};
+
+    // A writer implemented before `reserve` and `write_arg_shared` existed.
+    #[derive(Default)]
+    struct Args(Vec<Vec<u8>>);
+
+    impl RedisWrite for Args {
+        fn write_arg(&mut self, arg: &[u8]) {
+            self.0.push(arg.to_vec());
+        }
+    }
+
+    let mut out = Args::default();
+    vec!["a", "b"].write_redis_args(&mut out);
+    #[cfg(feature = "bytes")]
+    bytes::Bytes::from_static(b"c").write_redis_args(&mut out);
+    #[cfg(not(feature = "bytes"))]
+    "c".write_redis_args(&mut out);
+    assert_eq!(out.0, vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()]);
+}
+
+#[test]
+fn test_replication_outcome() {
+    use redis::ReplicationOutcome;
+
+    assert_eq!(
+        ReplicationOutcome::new(2, 1),
+        ReplicationOutcome::Acknowledged(2)
+    );
+    assert_eq!(
+        ReplicationOutcome::new(0, 0),
+        ReplicationOutcome::Acknowledged(0)
+    );
+    let outcome = ReplicationOutcome::new(1, 2);
+    assert_eq!(
+        outcome,
+        ReplicationOutcome::InsufficientReplicas {
+            acked: 1,
+            required: 2
+        }
+    );
+    assert!(!outcome.is_acknowledged());
+    assert_eq!(outcome.acked(), 1);
+}
+
+#[test]
+fn test_hello_info() {
+    use redis::{FromRedisValue, HelloInfo, Value};
+
+    let data = |s: &str| Value::Data(s.into());
+    let hello = HelloInfo::from_redis_value(&Value::Bulk(vec![
+        data("server"),
+        data("redis"),
+        data("version"),
+        data("7.2.4"),
+        data("proto"),
+        Value::Int(2),
+        data("id"),
+        Value::Int(5),
+        data("mode"),
+        data("standalone"),
+        data("role"),
+        data("master"),
+        data("modules"),
+        Value::Bulk(vec![Value::Bulk(vec![
+            data("name"),
+            data("search"),
+            data("ver"),
+            Value::Int(20809),
+        ])]),
+    ]))
+    .unwrap();
+    assert_eq!(
+        hello,
+        HelloInfo {
+            server: "redis".into(),
+            version: "7.2.4".into(),
+            proto: 2,
+            id: 5,
+            mode: "standalone".into(),
+            role: "master".into(),
+            modules: vec!["search".into()],
+        }
+    );
+
+    assert!(
+        HelloInfo::from_redis_value(&Value::Bulk(vec![data("server"), data("redis")])).is_err()
+    );
+}
+
+#[cfg(feature = "streams")]
+#[test]
+fn test_stream_range_reply() {
+    use redis::streams::{StreamId, StreamRangeReply};
+    use redis::{FromRedisValue, Value};
+
+    let entry = |id: &str, fields: &[&str]| {
+        Value::Bulk(vec![
+            Value::Data(id.into()),
+            Value::Bulk(fields.iter().map(|f| Value::Data((*f).into())).collect()),
+        ])
+    };
+    let reply = Value::Bulk(vec![
+        entry("1000-0", &["name", "alice", "age", "30"]),
+        entry("1000-1", &["name", "bob"]),
+    ]);
+
+    let entries: Vec<StreamId> = FromRedisValue::from_redis_value(&reply).unwrap();
+    assert_eq!(entries.len(), 2);
+    assert_eq!(entries[0].id, "1000-0");
+    assert_eq!(entries[0].get("name"), Some("alice".to_string()));
+    assert_eq!(entries[0].get("age"), Some(30));
+    assert_eq!(entries[1].id, "1000-1");
+    assert_eq!(entries[1].len(), 1);
+
+    let range = StreamRangeReply::from_redis_value(&reply).unwrap();
+    let ids: Vec<&str> = range.ids.iter().map(|entry| &entry.id[..]).collect();
+    assert_eq!(ids, ["1000-0", "1000-1"]);
+
+    assert!(StreamId::from_redis_value(&Value::Data("1000-0".into())).is_err());
+}
+
+#[test]
+fn test_wrong_eviction_policy_error() {
+    use redis::{parse_redis_value, ErrorKind};
+
+    let freq = b"-ERR An LFU maxmemory policy is not selected, access frequency not tracked. \
+        Please note that when switching between policies at runtime LRU and LFU data will \
+        take some time to adjust.\r\n";
+    let err = parse_redis_value(freq).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WrongEvictionPolicy);
+    assert_eq!(err.code(), Some("ERR"));
+
+    let idletime = b"-ERR An LFU maxmemory policy is selected, idle time not tracked. \
+        Please note that when switching between policies at runtime LRU and LFU data will \
+        take some time to adjust.\r\n";
+    let err = parse_redis_value(idletime).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::WrongEvictionPolicy);
+
+    let err = parse_redis_value(b"-ERR no such key\r\n").unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::ResponseError);
+}
+
+#[test]
+fn test_status() {
+    use redis::{FromRedisValue, Status, Value};
+
+    let ok = Status::from_redis_value(&Value::Okay).unwrap();
+    assert!(ok.is_ok());
+    assert_eq!(ok.as_str(), "OK");
+
+    let pong = Status::from_redis_value(&Value::Status("PONG".into())).unwrap();
+    assert!(!pong.is_ok());
+    assert_eq!(pong.to_string(), "PONG");
+    assert_eq!(pong.into_string(), "PONG");
+
+    // Bulk strings that look like a status are not coerced.
+    assert!(Status::from_redis_value(&Value::Data(b"OK".to_vec())).is_err());
+    assert!(Status::from_redis_value(&Value::Int(1)).is_err());
+
+    let skipped: Option<Status> = FromRedisValue::from_redis_value(&Value::Nil).unwrap();
+    assert_eq!(skipped, None);
+}
+
+#[test]
+fn test_invalid_utf8() {
+    use redis::{ErrorKind, FromRedisValue, LossyString, Value};
+
+    let data = Value::Data(b"ab\xffcd".to_vec());
+    let err = String::from_redis_value(&data).unwrap_err();
+    assert_eq!(err.kind(), ErrorKind::TypeError);
+    assert_eq!(err.detail(), Some("invalid byte at offset 2"));
+    let err = i64::from_redis_value(&Value::Data(b"1\xff".to_vec())).unwrap_err();
+    assert_eq!(err.detail(), Some("invalid byte at offset 1"));
+
+    let lossy = LossyString::from_redis_value(&data).unwrap();
+    assert_eq!(lossy.as_str(), "ab\u{fffd}cd");
+    let lossy = LossyString::from_redis_value(&Value::Okay).unwrap();
+    assert_eq!(lossy.into_string(), "OK");
+}
+
+#[test]
+fn test_count_to_redis_args() {
+    use redis::{Count, LposOptions, ToRedisArgs};
+
+    assert_eq!(Count::all().to_redis_args(), vec![b"0".to_vec()]);
+    assert_eq!(Count::exactly(5).to_redis_args(), vec![b"5".to_vec()]);
+    assert_eq!(
+        Count::with_duplicates(5).to_redis_args(),
+        vec![b"-5".to_vec()]
+    );
+    assert_eq!(
+        Count::with_duplicates(0).to_redis_args(),
+        vec![b"0".to_vec()]
+    );
+    assert_eq!(
+        LposOptions::default().count_all().to_redis_args(),
+        vec![b"COUNT".to_vec(), b"0".to_vec()]
+    );
+}
+
+#[test]
+fn test_zadd_options_validate() {
+    use redis::{ErrorKind, ZAddOptions};
+
+    let opts = ZAddOptions::default();
+    for invalid in &[
+        opts.nx().xx(),
+        opts.nx().gt(),
+        opts.nx().lt(),
+        opts.gt().lt(),
+        opts.xx().gt().lt().ch(),
+    ] {
+        let err = invalid.validate().unwrap_err();
+        assert_eq!(err.kind(), ErrorKind::ClientError, "{:?}", invalid);
+    }
+    for valid in &[
+        opts,
+        opts.nx().ch(),
+        opts.xx().gt(),
+        opts.xx().lt().ch(),
+        opts.gt().ch(),
+    ] {
+        assert_eq!(valid.validate(), Ok(()), "{:?}", valid);
+    }
+}
+
+#[test]
+fn test_repeated_keyword_arguments() {
+    use redis::{Cmd, SortOptions, ToRedisArgs};
+
+    let args = |args: &[&str]| {
+        args.iter()
+            .map(|arg| arg.as_bytes().to_vec())
+            .collect::<Vec<_>>()
+    };
+
+    // Every GET pattern gets its own GET token.
+    let opts = SortOptions::default()
+        .by("weight_*")
+        .get("#")
+        .get("name_*")
+        .limit(0, 10)
+        .desc();
+    assert_eq!(
+        opts.to_redis_args(),
+        args(&["BY", "weight_*", "LIMIT", "0", "10", "GET", "#", "GET", "name_*", "DESC"])
+    );
+    assert_eq!(
+        SortOptions::default().get(&["#", "name_*"]).to_redis_args(),
+        args(&["GET", "#", "GET", "name_*"])
+    );
+
+    // WEIGHTS is given once, followed by all weights.
+    assert_eq!(
+        Cmd::zunionstore_weights("dst", &[("a", 1), ("b", 2)]).get_packed_command(),
+        redis::cmd("ZUNIONSTORE")
+            .arg(&["dst", "2", "a", "b", "WEIGHTS", "1", "2"])
+            .get_packed_command()
+    );
 }